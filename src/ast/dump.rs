@@ -0,0 +1,191 @@
+use std::fmt::Write;
+
+use crate::data::vtype::*;
+
+use super::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+fn signature(signature: &FunctionSignature) -> String
+{
+	let parameters: Vec<String> = signature.parameters()
+		.iter()
+		.map(|param| format!("#{} {:?}", param.id(), param.vtype()))
+		.collect();
+
+	format!("{}({}) {:?}", signature.name(), parameters.join(", "), signature.return_type())
+}
+
+struct Dumper
+{
+	output: String,
+	depth: usize
+}
+
+impl Dumper
+{
+	fn line(&mut self, text: &str)
+	{
+		let _ = writeln!(self.output, "{}{}", "  ".repeat(self.depth), text);
+	}
+
+	fn nested(&mut self, dump: impl FnOnce(&mut Self))
+	{
+		self.depth += 1;
+		dump(self);
+		self.depth -= 1;
+	}
+
+	fn compound(&mut self, compound: &CompoundStatement)
+	{
+		self.line("Compound");
+		self.nested(|dumper| compound.statements().iter().for_each(|statement| dumper.statement(statement)));
+	}
+
+	fn statement(&mut self, statement: &Statement)
+	{
+		match statement.kind()
+		{
+			StatementKind::FunctionDefine(function) =>
+			{
+				self.line(&format!("FunctionDefine {}", signature(function.signature())));
+				self.nested(|dumper| dumper.compound(function.body()));
+			}
+
+			StatementKind::FunctionDeclare(function) => self.line(&format!("FunctionDeclare {}", signature(function.signature()))),
+
+			StatementKind::FunctionReturn(ret) =>
+			{
+				self.line("FunctionReturn");
+				self.nested(|dumper| ret.expression().iter().for_each(|expression| dumper.expression(expression)));
+			}
+
+			StatementKind::Expression(expr_stmt) =>
+			{
+				self.line("Expression");
+				self.nested(|dumper| dumper.expression(expr_stmt.expression()));
+			}
+
+			StatementKind::Compound(compound) => self.compound(compound),
+
+			StatementKind::Declare(declare) =>
+			{
+				self.line(&format!("Declare #{} {:?}", declare.identifier(), declare.vtype()));
+				self.nested(|dumper| dumper.expression(declare.expression()));
+			}
+
+			StatementKind::Assign(assign) =>
+			{
+				self.line(&format!("Assign #{}", assign.identifier()));
+				self.nested(|dumper| dumper.expression(assign.expression()));
+			}
+
+			StatementKind::Print(print) =>
+			{
+				self.line("Print");
+				self.nested(|dumper| dumper.expression(print.expression()));
+			}
+
+			StatementKind::If(if_stmt) =>
+			{
+				self.line("If");
+				self.nested(|dumper|
+				{
+					dumper.expression(if_stmt.condition());
+					dumper.compound(if_stmt.then_body());
+					if_stmt.else_body().iter().for_each(|else_body| dumper.statement(else_body));
+				});
+			}
+
+			StatementKind::While(while_stmt) =>
+			{
+				self.line("While");
+				self.nested(|dumper|
+				{
+					dumper.expression(while_stmt.condition());
+					dumper.compound(while_stmt.body());
+				});
+			}
+
+			StatementKind::Break(_) => self.line("Break"),
+			StatementKind::Continue(_) => self.line("Continue")
+		}
+	}
+
+	fn expression(&mut self, expression: &Expression)
+	{
+		let vtype = expression.vtype();
+
+		match expression.kind()
+		{
+			ExpressionKind::Literal(literal) =>
+			{
+				let value = match literal.literal().as_literal::<IntegerLiteral>()
+				{
+					Some(integer) => integer.value.to_string(),
+					None => literal.literal().as_literal::<BooleanLiteral>().unwrap().value.to_string()
+				};
+
+				self.line(&format!("Literal {} : {:?}", value, vtype));
+			}
+
+			ExpressionKind::FunctionCall(call) =>
+			{
+				self.line(&format!("FunctionCall {} : {:?}", call.name(), vtype));
+				self.nested(|dumper| call.passed_arguments().iter().for_each(|argument| dumper.expression(argument)));
+			}
+
+			ExpressionKind::Variable(variable) => self.line(&format!("Variable #{} : {:?}", variable.identifier(), vtype)),
+
+			ExpressionKind::Arithmetic(arith_expr) =>
+			{
+				self.line(&format!("Arithmetic {:?} : {:?}", arith_expr.op(), vtype));
+				self.nested(|dumper| { dumper.expression(arith_expr.left()); dumper.expression(arith_expr.right()); });
+			}
+
+			ExpressionKind::Comparison(cmp_expr) =>
+			{
+				self.line(&format!("Comparison {:?} : {:?}", cmp_expr.op(), vtype));
+				self.nested(|dumper| { dumper.expression(cmp_expr.left()); dumper.expression(cmp_expr.right()); });
+			}
+
+			ExpressionKind::Boolean(bool_expr) =>
+			{
+				self.line(&format!("Boolean {:?} : {:?}", bool_expr.op(), vtype));
+				self.nested(|dumper| { dumper.expression(bool_expr.left()); dumper.expression(bool_expr.right()); });
+			}
+		}
+	}
+}
+
+// One node per line, children indented under their parent. Variables and parameters
+// are shown by id, as names only survive in the tokens:
+//
+// FunctionDefine square(#1 Integer) Integer
+//   Compound
+//     FunctionReturn
+//       Arithmetic Multiply : Integer
+//         Variable #1 : Integer
+//         Variable #1 : Integer
+pub fn dump(root: &Root) -> String
+{
+	let mut dumper = Dumper { output: String::new(), depth: 0 };
+
+	for statement in root.statements.iter()
+	{
+		dumper.statement(statement);
+	}
+
+	dumper.output
+}
+
+pub fn dump_expression(expression: &Expression) -> String
+{
+	let mut dumper = Dumper { output: String::new(), depth: 0 };
+	dumper.expression(expression);
+
+	dumper.output
+}
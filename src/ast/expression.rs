@@ -3,15 +3,20 @@ use std::any::Any;
 use std::collections::VecDeque;
 
 use super::literal::Literal;
+use super::span::Span;
 
 use crate::{
 	data::{
 		ops::*,
 		vtype::*
 	},
-	parser::token::Token
+	parser::token::{
+		Token,
+		TokenSlice
+	}
 };
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ExpressionType
 {
 	Literal,
@@ -28,8 +33,15 @@ pub trait ExpressionTrait: DynClone
 	fn etype(&self) -> ExpressionType;
 
 	fn unparse(&self) -> VecDeque<Token>;
+	fn tokens(&self) -> &TokenSlice; // shared, for reading without a copy
 
 	fn as_any(&self) -> &dyn Any; // for downcasting
+	fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+	fn span(&self) -> Span
+	{
+		Span::from_tokens(self.tokens().iter())
+	}
 }
 
 dyn_clone::clone_trait_object!(ExpressionTrait);
@@ -37,8 +49,11 @@ dyn_clone::clone_trait_object!(ExpressionTrait);
 #[derive(Clone)]
 pub struct LiteralExpression
 {
-	tokens: VecDeque<Token>,
-	literal: Literal
+	tokens: TokenSlice,
+	literal: Literal,
+
+	// The constant expression this literal replaced, see `ast::fold`
+	folded_from: Option<Expression>
 }
 
 impl ExpressionTrait for LiteralExpression
@@ -55,32 +70,47 @@ impl ExpressionTrait for LiteralExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl LiteralExpression
 {
-	fn new(tokens: VecDeque<Token>, literal: Literal) -> Self
+	fn new(tokens: TokenSlice, literal: Literal, folded_from: Option<Expression>) -> Self
 	{
-		Self { tokens, literal }
+		Self { tokens, literal, folded_from }
 	}
 
 	pub fn literal(&self) -> &Literal
 	{
 		&self.literal
 	}
+
+	pub fn folded_from(&self) -> Option<&Expression>
+	{
+		self.folded_from.as_ref()
+	}
 }
 
 #[derive(Clone)]
 pub struct FunctionCallExpression
 {
-	tokens: VecDeque<Token>,
+	tokens: TokenSlice,
 	vtype: VType,
 	name: String,
 	passed_arguments: VecDeque<Expression>
@@ -100,18 +130,28 @@ impl ExpressionTrait for FunctionCallExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl FunctionCallExpression
 {
-	fn new(tokens: VecDeque<Token>, vtype: VType, name: String, passed_arguments: VecDeque<Expression>) -> Self
+	fn new(tokens: TokenSlice, vtype: VType, name: String, passed_arguments: VecDeque<Expression>) -> Self
 	{
 		Self { tokens, vtype, name, passed_arguments }
 	}
@@ -121,16 +161,16 @@ impl FunctionCallExpression
 		self.name.clone()
 	}
 
-	pub fn passed_arguments(&self) -> VecDeque<Expression>
+	pub fn passed_arguments(&self) -> &VecDeque<Expression>
 	{
-		self.passed_arguments.clone()
+		&self.passed_arguments
 	}
 }
 
 #[derive(Clone)]
 pub struct VariableExpression
 {
-	tokens: VecDeque<Token>,
+	tokens: TokenSlice,
 	vtype: VType,
 	identifier: u16
 }
@@ -149,18 +189,28 @@ impl ExpressionTrait for VariableExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl VariableExpression
 {
-	fn new(tokens: VecDeque<Token>, vtype: VType, identifier: u16) -> Self
+	fn new(tokens: TokenSlice, vtype: VType, identifier: u16) -> Self
 	{
 		Self { tokens, vtype, identifier }
 	}
@@ -179,7 +229,7 @@ impl VariableExpression
 #[derive(Clone)]
 pub struct ArithmeticExpression
 {
-	tokens: VecDeque<Token>,
+	tokens: TokenSlice,
 
 	vtype: VType,
 	op: ArithmeticOperation,
@@ -202,18 +252,28 @@ impl ExpressionTrait for ArithmeticExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl ArithmeticExpression
 {
-	pub fn new(tokens: VecDeque<Token>, vtype: VType, op: ArithmeticOperation, left: Expression, right: Expression) -> Self
+	pub fn new(tokens: TokenSlice, vtype: VType, op: ArithmeticOperation, left: Expression, right: Expression) -> Self
 	{
 		Self { tokens, vtype, op, left, right }
 	}
@@ -223,21 +283,21 @@ impl ArithmeticExpression
 		self.op.clone()
 	}
 
-	pub fn left(&self) -> Expression
+	pub fn left(&self) -> &Expression
 	{
-		self.left.clone()
+		&self.left
 	}
 
-	pub fn right(&self) -> Expression
+	pub fn right(&self) -> &Expression
 	{
-		self.right.clone()
+		&self.right
 	}
 }
 
 #[derive(Clone)]
 pub struct ComparisonExpression
 {
-	tokens: VecDeque<Token>,
+	tokens: TokenSlice,
 
 	op: ComparisonOperation,
 
@@ -259,18 +319,28 @@ impl ExpressionTrait for ComparisonExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl ComparisonExpression
 {
-	pub fn new(tokens: VecDeque<Token>, op: ComparisonOperation, left: Expression, right: Expression) -> Self
+	pub fn new(tokens: TokenSlice, op: ComparisonOperation, left: Expression, right: Expression) -> Self
 	{
 		Self { tokens, op, left, right }
 	}
@@ -280,21 +350,21 @@ impl ComparisonExpression
 		self.op.clone()
 	}
 
-	pub fn left(&self) -> Expression
+	pub fn left(&self) -> &Expression
 	{
-		self.left.clone()
+		&self.left
 	}
 
-	pub fn right(&self) -> Expression
+	pub fn right(&self) -> &Expression
 	{
-		self.right.clone()
+		&self.right
 	}
 }
 
 #[derive(Clone)]
 pub struct BooleanExpression
 {
-	tokens: VecDeque<Token>,
+	tokens: TokenSlice,
 
 	op: BooleanOperation,
 
@@ -316,18 +386,28 @@ impl ExpressionTrait for BooleanExpression
 
 	fn unparse(&self) -> VecDeque<Token>
 	{
-		self.tokens.clone()
+		self.tokens.iter().cloned().collect()
+	}
+
+	fn tokens(&self) -> &TokenSlice
+	{
+		&self.tokens
 	}
 
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl BooleanExpression
 {
-	pub fn new(tokens: VecDeque<Token>, op: BooleanOperation, left: Expression, right: Expression) -> Self
+	pub fn new(tokens: TokenSlice, op: BooleanOperation, left: Expression, right: Expression) -> Self
 	{
 		Self { tokens, op, left, right }
 	}
@@ -337,19 +417,125 @@ impl BooleanExpression
 		self.op.clone()
 	}
 
-	pub fn left(&self) -> Expression
+	pub fn left(&self) -> &Expression
 	{
-		self.left.clone()
+		&self.left
 	}
 
-	pub fn right(&self) -> Expression
+	pub fn right(&self) -> &Expression
 	{
-		self.right.clone()
+		&self.right
 	}
 }
 
 pub type ExpressionBox = Box<dyn ExpressionTrait>;
 
+// Closed counterpart of `Expression`, a match over it breaks when an expression type is added.
+// The nodes are borrowed, see `Expression::into_kind` for taking them.
+#[derive(Clone, Copy)]
+pub enum ExpressionKind<'a>
+{
+	Literal(&'a LiteralExpression),
+	FunctionCall(&'a FunctionCallExpression),
+	Variable(&'a VariableExpression),
+	Arithmetic(&'a ArithmeticExpression),
+	Comparison(&'a ComparisonExpression),
+	Boolean(&'a BooleanExpression)
+}
+
+// Owned counterpart of `ExpressionKind`
+#[derive(Clone)]
+pub enum OwnedExpressionKind
+{
+	Literal(LiteralExpression),
+	FunctionCall(FunctionCallExpression),
+	Variable(VariableExpression),
+	Arithmetic(ArithmeticExpression),
+	Comparison(ComparisonExpression),
+	Boolean(BooleanExpression)
+}
+
+impl ExpressionKind<'_>
+{
+	pub fn etype(&self) -> ExpressionType
+	{
+		match self
+		{
+			ExpressionKind::Literal(_)      => ExpressionType::Literal,
+			ExpressionKind::FunctionCall(_) => ExpressionType::FunctionCall,
+			ExpressionKind::Variable(_)     => ExpressionType::Variable,
+			ExpressionKind::Arithmetic(_)   => ExpressionType::Arithmetic,
+			ExpressionKind::Comparison(_)   => ExpressionType::Comparison,
+			ExpressionKind::Boolean(_)      => ExpressionType::Boolean
+		}
+	}
+}
+
+impl OwnedExpressionKind
+{
+	pub fn etype(&self) -> ExpressionType
+	{
+		match self
+		{
+			OwnedExpressionKind::Literal(_)      => ExpressionType::Literal,
+			OwnedExpressionKind::FunctionCall(_) => ExpressionType::FunctionCall,
+			OwnedExpressionKind::Variable(_)     => ExpressionType::Variable,
+			OwnedExpressionKind::Arithmetic(_)   => ExpressionType::Arithmetic,
+			OwnedExpressionKind::Comparison(_)   => ExpressionType::Comparison,
+			OwnedExpressionKind::Boolean(_)      => ExpressionType::Boolean
+		}
+	}
+}
+
+impl From<OwnedExpressionKind> for Expression
+{
+	fn from(kind: OwnedExpressionKind) -> Self
+	{
+		match kind
+		{
+			OwnedExpressionKind::Literal(expression)      => Expression::new(Box::new(expression)),
+			OwnedExpressionKind::FunctionCall(expression) => Expression::new(Box::new(expression)),
+			OwnedExpressionKind::Variable(expression)     => Expression::new(Box::new(expression)),
+			OwnedExpressionKind::Arithmetic(expression)   => Expression::new(Box::new(expression)),
+			OwnedExpressionKind::Comparison(expression)   => Expression::new(Box::new(expression)),
+			OwnedExpressionKind::Boolean(expression)      => Expression::new(Box::new(expression))
+		}
+	}
+}
+
+// Copies the borrowed node
+impl From<ExpressionKind<'_>> for Expression
+{
+	fn from(kind: ExpressionKind<'_>) -> Self
+	{
+		match kind
+		{
+			ExpressionKind::Literal(expression)      => Expression::new(Box::new(expression.clone())),
+			ExpressionKind::FunctionCall(expression) => Expression::new(Box::new(expression.clone())),
+			ExpressionKind::Variable(expression)     => Expression::new(Box::new(expression.clone())),
+			ExpressionKind::Arithmetic(expression)   => Expression::new(Box::new(expression.clone())),
+			ExpressionKind::Comparison(expression)   => Expression::new(Box::new(expression.clone())),
+			ExpressionKind::Boolean(expression)      => Expression::new(Box::new(expression.clone()))
+		}
+	}
+}
+
+impl<'a> From<&'a Expression> for ExpressionKind<'a>
+{
+	fn from(expression: &'a Expression) -> Self
+	{
+		expression.kind()
+	}
+}
+
+impl From<Expression> for OwnedExpressionKind
+{
+	fn from(expression: Expression) -> Self
+	{
+		expression.into_kind()
+	}
+}
+
 #[derive(Clone)]
 pub struct Expression
 {
@@ -369,38 +555,58 @@ impl Expression
 		self.expression.etype()
 	}
 
+	pub fn unparse(&self) -> VecDeque<Token>
+	{
+		self.expression.unparse()
+	}
+
+	pub fn tokens(&self) -> &TokenSlice
+	{
+		self.expression.tokens()
+	}
+
+	pub fn span(&self) -> Span
+	{
+		self.expression.span()
+	}
+
 	// New functions:
 	pub fn new(expression: ExpressionBox) -> Self
 	{
 		Self { expression }
 	}
 
-	pub fn new_literal(tokens: VecDeque<Token>, literal: Literal) -> Self
+	pub fn new_literal(tokens: TokenSlice, literal: Literal) -> Self
+	{
+		Self::new(Box::new(LiteralExpression::new(tokens, literal, None)))
+	}
+
+	pub fn new_folded_literal(tokens: TokenSlice, literal: Literal, folded_from: Expression) -> Self
 	{
-		Self::new(Box::new(LiteralExpression::new(tokens, literal)))
+		Self::new(Box::new(LiteralExpression::new(tokens, literal, Some(folded_from))))
 	}
 
-	pub fn new_function_call(tokens: VecDeque<Token>, vtype: VType, name: String, passed_arguments: VecDeque<Expression>) -> Self
+	pub fn new_function_call(tokens: TokenSlice, vtype: VType, name: String, passed_arguments: VecDeque<Expression>) -> Self
 	{
 		Self::new(Box::new(FunctionCallExpression::new(tokens, vtype, name, passed_arguments)))
 	}
 	
-	pub fn new_variable(tokens: VecDeque<Token>, vtype: VType, identifier: u16) -> Self
+	pub fn new_variable(tokens: TokenSlice, vtype: VType, identifier: u16) -> Self
 	{
 		Self::new(Box::new(VariableExpression::new(tokens, vtype, identifier)))
 	}
 
-	pub fn new_arithmetic(tokens: VecDeque<Token>, vtype: VType, op: ArithmeticOperation, left: Expression, right: Expression) -> Self
+	pub fn new_arithmetic(tokens: TokenSlice, vtype: VType, op: ArithmeticOperation, left: Expression, right: Expression) -> Self
 	{
 		Self::new(Box::new(ArithmeticExpression::new(tokens, vtype, op, left, right)))
 	}
 
-	pub fn new_comparison(tokens: VecDeque<Token>, op: ComparisonOperation, left: Expression, right: Expression) -> Self
+	pub fn new_comparison(tokens: TokenSlice, op: ComparisonOperation, left: Expression, right: Expression) -> Self
 	{
 		Self::new(Box::new(ComparisonExpression::new(tokens, op, left, right)))
 	}
 
-	pub fn new_boolean(tokens: VecDeque<Token>, op: BooleanOperation, left: Expression, right: Expression) -> Self
+	pub fn new_boolean(tokens: TokenSlice, op: BooleanOperation, left: Expression, right: Expression) -> Self
 	{
 		Self::new(Box::new(BooleanExpression::new(tokens, op, left, right)))
 	}
@@ -410,4 +616,48 @@ impl Expression
 	{
 		self.expression.as_any().downcast_ref::<T>()
 	}
+
+	fn downcast_ref<T: 'static>(&self) -> &T
+	{
+		self.as_expression::<T>()
+			.unwrap_or_else(|| panic!("expression of type `{:?}` is not implemented by this module", self.etype()))
+	}
+
+	fn downcast<T: 'static>(self) -> T
+	{
+		let etype = self.etype();
+
+		*self.expression
+			.into_any()
+			.downcast::<T>()
+			.unwrap_or_else(|_| panic!("expression of type `{:?}` is not implemented by this module", etype))
+	}
+
+	// Panics only for an `ExpressionTrait` implemented outside of this module
+	pub fn kind(&self) -> ExpressionKind<'_>
+	{
+		match self.etype()
+		{
+			ExpressionType::Literal      => ExpressionKind::Literal(self.downcast_ref()),
+			ExpressionType::FunctionCall => ExpressionKind::FunctionCall(self.downcast_ref()),
+			ExpressionType::Variable     => ExpressionKind::Variable(self.downcast_ref()),
+			ExpressionType::Arithmetic   => ExpressionKind::Arithmetic(self.downcast_ref()),
+			ExpressionType::Comparison   => ExpressionKind::Comparison(self.downcast_ref()),
+			ExpressionType::Boolean      => ExpressionKind::Boolean(self.downcast_ref())
+		}
+	}
+
+	// Takes the node out without copying it, panics like `kind`
+	pub fn into_kind(self) -> OwnedExpressionKind
+	{
+		match self.etype()
+		{
+			ExpressionType::Literal      => OwnedExpressionKind::Literal(self.downcast()),
+			ExpressionType::FunctionCall => OwnedExpressionKind::FunctionCall(self.downcast()),
+			ExpressionType::Variable     => OwnedExpressionKind::Variable(self.downcast()),
+			ExpressionType::Arithmetic   => OwnedExpressionKind::Arithmetic(self.downcast()),
+			ExpressionType::Comparison   => OwnedExpressionKind::Comparison(self.downcast()),
+			ExpressionType::Boolean      => OwnedExpressionKind::Boolean(self.downcast())
+		}
+	}
 }
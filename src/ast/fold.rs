@@ -0,0 +1,153 @@
+use std::fmt;
+
+use super::{
+	expression::*,
+	literal::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+#[derive(Debug, Clone)]
+pub enum FoldError
+{
+	DivisionByZero,
+	Overflow(ArithmeticOperation)
+}
+
+impl fmt::Display for FoldError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			FoldError::DivisionByZero =>
+				write!(f, "division by zero in a constant expression"),
+
+			FoldError::Overflow(op) =>
+				write!(f, "arithmetic operation `{:?}` overflows type `{:?}` in a constant expression", op, VType::Integer)
+		}
+	}
+}
+
+impl std::error::Error for FoldError {}
+
+fn integer(expression: &Expression) -> Option<i32>
+{
+	expression
+		.as_expression::<LiteralExpression>()?
+		.literal()
+		.as_literal::<IntegerLiteral>()
+		.map(|literal| literal.value)
+}
+
+fn boolean(expression: &Expression) -> Option<bool>
+{
+	expression
+		.as_expression::<LiteralExpression>()?
+		.literal()
+		.as_literal::<BooleanLiteral>()
+		.map(|literal| literal.value)
+}
+
+// The wrapped value and whether it overflowed, nothing for a division by zero
+fn fold_arithmetic(op: &ArithmeticOperation, left: i32, right: i32) -> Option<(i32, bool)>
+{
+	match op
+	{
+		ArithmeticOperation::Add      => Some(left.overflowing_add(right)),
+		ArithmeticOperation::Subtract => Some(left.overflowing_sub(right)),
+		ArithmeticOperation::Multiply => Some(left.overflowing_mul(right)),
+		ArithmeticOperation::Divide   => (right != 0).then(|| left.overflowing_div(right))
+	}
+}
+
+fn fold_comparison<T: PartialOrd>(op: &ComparisonOperation, left: T, right: T) -> bool
+{
+	match op
+	{
+		ComparisonOperation::IsEqual          => left == right,
+		ComparisonOperation::IsNotEqual       => left != right,
+		ComparisonOperation::IsGreater        => left > right,
+		ComparisonOperation::IsGreaterOrEqual => left >= right,
+		ComparisonOperation::IsLess           => left < right,
+		ComparisonOperation::IsLessOrEqual    => left <= right
+	}
+}
+
+// Rewrites an arithmetic, comparison or boolean expression whose operands are both
+// literals into a single literal, one level deep. Operands are expected to be folded
+// already, which the parser guarantees by folding every operator as it is applied.
+//
+// Arithmetic wraps like it does at runtime. An overflow is still folded into the
+// wrapped value next to the error, a division by zero is returned unchanged. The
+// literal keeps the expression it replaced for tools that print the source back, an
+// expression that does not fold is handed back as it is.
+pub fn fold(expression: Expression) -> (Expression, Option<FoldError>)
+{
+	let folded = match expression.etype()
+	{
+		ExpressionType::Arithmetic =>
+		{
+			let arith_expr = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+			match (integer(arith_expr.left()), integer(arith_expr.right()))
+			{
+				(Some(left), Some(right)) => match fold_arithmetic(&arith_expr.op(), left, right)
+				{
+					Some((value, false)) => Literal::new_integer(value),
+
+					Some((value, true)) =>
+					{
+						let error = FoldError::Overflow(arith_expr.op());
+						return (Expression::new_folded_literal(expression.tokens().clone(), Literal::new_integer(value), expression), Some(error));
+					}
+
+					None => return (expression, Some(FoldError::DivisionByZero))
+				},
+
+				_ => return (expression, None)
+			}
+		}
+
+		ExpressionType::Comparison =>
+		{
+			let cmp_expr = expression.as_expression::<ComparisonExpression>().unwrap();
+			let (left, right) = (cmp_expr.left(), cmp_expr.right());
+
+			// Booleans only compare for equality, anything else was already reported as mistyped
+			match (integer(left), integer(right), boolean(left), boolean(right), cmp_expr.op())
+			{
+				(Some(left), Some(right), _, _, op) =>
+					Literal::new_boolean(fold_comparison(&op, left, right)),
+
+				(_, _, Some(left), Some(right), op @ (ComparisonOperation::IsEqual | ComparisonOperation::IsNotEqual)) =>
+					Literal::new_boolean(fold_comparison(&op, left, right)),
+
+				_ => return (expression, None)
+			}
+		}
+
+		ExpressionType::Boolean =>
+		{
+			let bool_expr = expression.as_expression::<BooleanExpression>().unwrap();
+
+			match (boolean(bool_expr.left()), boolean(bool_expr.right()))
+			{
+				(Some(left), Some(right)) => Literal::new_boolean(match bool_expr.op()
+				{
+					BooleanOperation::And => left && right,
+					BooleanOperation::Or  => left || right
+				}),
+
+				_ => return (expression, None)
+			}
+		}
+
+		_ => return (expression, None)
+	};
+
+	(Expression::new_folded_literal(expression.tokens().clone(), folded, expression), None)
+}
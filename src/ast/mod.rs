@@ -1,5 +1,10 @@
 pub mod expression;
 pub mod literal;
 pub mod statement;
+pub mod span;
+
+pub mod fold;
+pub mod visit;
+pub mod dump;
 
 pub mod root;
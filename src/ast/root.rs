@@ -20,3 +20,11 @@ impl Root
 		self.statements.push_back(statement.clone());
 	}
 }
+
+impl Default for Root
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
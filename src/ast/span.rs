@@ -0,0 +1,69 @@
+use crate::parser::token::{
+	Token,
+	TokenInfo
+};
+
+// Source range of a node. Lines and columns are 1-based with an inclusive end like
+// `TokenInfo`, byte offsets are 0-based with an exclusive end so they slice the source.
+// Nodes built without tokens, outside of the parser, have the default empty span.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Span
+{
+	pub line_begin: usize,
+	pub column_begin: usize,
+	pub offset_begin: usize,
+
+	pub line_end: usize,
+	pub column_end: usize,
+	pub offset_end: usize
+}
+
+impl Span
+{
+	pub fn from_info(info: &TokenInfo) -> Self
+	{
+		Self
+		{
+			line_begin: info.line,
+			column_begin: info.column_begin,
+			offset_begin: info.offset_begin,
+
+			line_end: info.line,
+			column_end: info.column_end,
+			offset_end: info.offset_end
+		}
+	}
+
+	pub fn from_tokens<'a>(tokens: impl IntoIterator<Item = &'a Token, IntoIter: DoubleEndedIterator>) -> Self
+	{
+		let mut tokens = tokens.into_iter();
+
+		match (tokens.next(), tokens.next_back())
+		{
+			(Some(first), last) => Self::from_info(&first.info()).to(&Self::from_info(&last.unwrap_or(first).info())),
+			_ => Self::default()
+		}
+	}
+
+	// From the beginning of this span to the end of `other`
+	pub fn to(&self, other: &Span) -> Self
+	{
+		Self
+		{
+			line_end: other.line_end,
+			column_end: other.column_end,
+			offset_end: other.offset_end,
+			..*self
+		}
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.offset_begin == self.offset_end
+	}
+
+	pub fn text<'a>(&self, source: &'a str) -> &'a str
+	{
+		&source[self.offset_begin..self.offset_end]
+	}
+}
@@ -7,6 +7,7 @@ use crate::{
 };
 
 use super::expression::Expression;
+use super::span::Span;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StatementType
@@ -20,14 +21,26 @@ pub enum StatementType
 	Compound,
 	Declare,
 	Assign,
-	Print
+	Print,
+
+	If,
+	While,
+	Break,
+	Continue
 }
 
 pub trait StatementTrait: DynClone
 {
 	fn stype(&self) -> StatementType;
 	fn unparse(&self) -> VecDeque<Token>;
+	fn tokens(&self) -> &VecDeque<Token>; // borrowed, for reading without a copy
 	fn as_any(&self) -> &dyn Any; // for downcasting
+	fn into_any(self: Box<Self>) -> Box<dyn Any>;
+
+	fn span(&self) -> Span
+	{
+		Span::from_tokens(self.tokens())
+	}
 }
 
 dyn_clone::clone_trait_object!(StatementTrait);
@@ -53,10 +66,20 @@ impl StatementTrait for FunctionDefineStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl FunctionDefineStatement
@@ -97,10 +120,20 @@ impl StatementTrait for FunctionDeclareStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl FunctionDeclareStatement
@@ -136,10 +169,20 @@ impl StatementTrait for FunctionReturnStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl FunctionReturnStatement
@@ -175,10 +218,20 @@ impl StatementTrait for ExpressionStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl ExpressionStatement
@@ -214,10 +267,20 @@ impl StatementTrait for CompoundStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl CompoundStatement
@@ -255,10 +318,20 @@ impl StatementTrait for DeclareStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl DeclareStatement
@@ -305,10 +378,20 @@ impl StatementTrait for AssignStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl AssignStatement
@@ -349,10 +432,20 @@ impl StatementTrait for PrintStatement
 		self.tokens.clone()
 	}
 
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
 	fn as_any(&self) -> &dyn Any
 	{
 		self
 	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
 }
 
 impl PrintStatement
@@ -368,8 +461,351 @@ impl PrintStatement
 	}
 }
 
+#[derive(Clone)]
+pub struct IfStatement
+{
+	tokens: VecDeque<Token>,
+
+	condition: Expression,
+	then_body: CompoundStatement,
+	else_body: Option<Statement>
+}
+
+impl StatementTrait for IfStatement
+{
+	fn stype(&self) -> StatementType
+	{
+		StatementType::If
+	}
+
+	fn unparse(&self) -> VecDeque<Token>
+	{
+		self.tokens.clone()
+	}
+
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
+	fn as_any(&self) -> &dyn Any
+	{
+		self
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
+}
+
+impl IfStatement
+{
+	pub fn new(tokens: VecDeque<Token>, condition: Expression, then_body: CompoundStatement, else_body: Option<Statement>) -> Self
+	{
+		Self { tokens, condition, then_body, else_body }
+	}
+
+	pub fn condition(&self) -> &Expression
+	{
+		&self.condition
+	}
+
+	pub fn then_body(&self) -> &CompoundStatement
+	{
+		&self.then_body
+	}
+
+	// Either a `CompoundStatement` or, for `else if` chains, another `IfStatement`
+	pub fn else_body(&self) -> &Option<Statement>
+	{
+		&self.else_body
+	}
+}
+
+#[derive(Clone)]
+pub struct WhileStatement
+{
+	tokens: VecDeque<Token>,
+
+	condition: Expression,
+	body: CompoundStatement
+}
+
+impl StatementTrait for WhileStatement
+{
+	fn stype(&self) -> StatementType
+	{
+		StatementType::While
+	}
+
+	fn unparse(&self) -> VecDeque<Token>
+	{
+		self.tokens.clone()
+	}
+
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
+	fn as_any(&self) -> &dyn Any
+	{
+		self
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
+}
+
+impl WhileStatement
+{
+	pub fn new(tokens: VecDeque<Token>, condition: Expression, body: CompoundStatement) -> Self
+	{
+		Self { tokens, condition, body }
+	}
+
+	pub fn condition(&self) -> &Expression
+	{
+		&self.condition
+	}
+
+	pub fn body(&self) -> &CompoundStatement
+	{
+		&self.body
+	}
+}
+
+#[derive(Clone)]
+pub struct BreakStatement
+{
+	tokens: VecDeque<Token>
+}
+
+impl StatementTrait for BreakStatement
+{
+	fn stype(&self) -> StatementType
+	{
+		StatementType::Break
+	}
+
+	fn unparse(&self) -> VecDeque<Token>
+	{
+		self.tokens.clone()
+	}
+
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
+	fn as_any(&self) -> &dyn Any
+	{
+		self
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
+}
+
+impl BreakStatement
+{
+	pub fn new(tokens: VecDeque<Token>) -> Self
+	{
+		Self { tokens }
+	}
+}
+
+#[derive(Clone)]
+pub struct ContinueStatement
+{
+	tokens: VecDeque<Token>
+}
+
+impl StatementTrait for ContinueStatement
+{
+	fn stype(&self) -> StatementType
+	{
+		StatementType::Continue
+	}
+
+	fn unparse(&self) -> VecDeque<Token>
+	{
+		self.tokens.clone()
+	}
+
+	fn tokens(&self) -> &VecDeque<Token>
+	{
+		&self.tokens
+	}
+
+	fn as_any(&self) -> &dyn Any
+	{
+		self
+	}
+
+	fn into_any(self: Box<Self>) -> Box<dyn Any>
+	{
+		self
+	}
+}
+
+impl ContinueStatement
+{
+	pub fn new(tokens: VecDeque<Token>) -> Self
+	{
+		Self { tokens }
+	}
+}
+
 pub type StatementBox = Box<dyn StatementTrait>;
 
+// Closed counterpart of `Statement`, a match over it breaks when a statement type is added.
+// The nodes are borrowed, see `Statement::into_kind` for taking them.
+#[derive(Clone, Copy)]
+pub enum StatementKind<'a>
+{
+	FunctionDefine(&'a FunctionDefineStatement),
+	FunctionDeclare(&'a FunctionDeclareStatement),
+	FunctionReturn(&'a FunctionReturnStatement),
+	Expression(&'a ExpressionStatement),
+	Compound(&'a CompoundStatement),
+	Declare(&'a DeclareStatement),
+	Assign(&'a AssignStatement),
+	Print(&'a PrintStatement),
+	If(&'a IfStatement),
+	While(&'a WhileStatement),
+	Break(&'a BreakStatement),
+	Continue(&'a ContinueStatement)
+}
+
+// Owned counterpart of `StatementKind`
+#[derive(Clone)]
+pub enum OwnedStatementKind
+{
+	FunctionDefine(FunctionDefineStatement),
+	FunctionDeclare(FunctionDeclareStatement),
+	FunctionReturn(FunctionReturnStatement),
+	Expression(ExpressionStatement),
+	Compound(CompoundStatement),
+	Declare(DeclareStatement),
+	Assign(AssignStatement),
+	Print(PrintStatement),
+	If(IfStatement),
+	While(WhileStatement),
+	Break(BreakStatement),
+	Continue(ContinueStatement)
+}
+
+impl StatementKind<'_>
+{
+	pub fn stype(&self) -> StatementType
+	{
+		match self
+		{
+			StatementKind::FunctionDefine(_)  => StatementType::FunctionDefine,
+			StatementKind::FunctionDeclare(_) => StatementType::FunctionDeclare,
+			StatementKind::FunctionReturn(_)  => StatementType::FunctionReturn,
+			StatementKind::Expression(_)      => StatementType::Expression,
+			StatementKind::Compound(_)        => StatementType::Compound,
+			StatementKind::Declare(_)         => StatementType::Declare,
+			StatementKind::Assign(_)          => StatementType::Assign,
+			StatementKind::Print(_)           => StatementType::Print,
+			StatementKind::If(_)              => StatementType::If,
+			StatementKind::While(_)           => StatementType::While,
+			StatementKind::Break(_)           => StatementType::Break,
+			StatementKind::Continue(_)        => StatementType::Continue
+		}
+	}
+}
+
+impl OwnedStatementKind
+{
+	pub fn stype(&self) -> StatementType
+	{
+		match self
+		{
+			OwnedStatementKind::FunctionDefine(_)  => StatementType::FunctionDefine,
+			OwnedStatementKind::FunctionDeclare(_) => StatementType::FunctionDeclare,
+			OwnedStatementKind::FunctionReturn(_)  => StatementType::FunctionReturn,
+			OwnedStatementKind::Expression(_)      => StatementType::Expression,
+			OwnedStatementKind::Compound(_)        => StatementType::Compound,
+			OwnedStatementKind::Declare(_)         => StatementType::Declare,
+			OwnedStatementKind::Assign(_)          => StatementType::Assign,
+			OwnedStatementKind::Print(_)           => StatementType::Print,
+			OwnedStatementKind::If(_)              => StatementType::If,
+			OwnedStatementKind::While(_)           => StatementType::While,
+			OwnedStatementKind::Break(_)           => StatementType::Break,
+			OwnedStatementKind::Continue(_)        => StatementType::Continue
+		}
+	}
+}
+
+impl From<OwnedStatementKind> for Statement
+{
+	fn from(kind: OwnedStatementKind) -> Self
+	{
+		match kind
+		{
+			OwnedStatementKind::FunctionDefine(statement)  => Statement::new(Box::new(statement)),
+			OwnedStatementKind::FunctionDeclare(statement) => Statement::new(Box::new(statement)),
+			OwnedStatementKind::FunctionReturn(statement)  => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Expression(statement)      => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Compound(statement)        => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Declare(statement)         => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Assign(statement)          => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Print(statement)           => Statement::new(Box::new(statement)),
+			OwnedStatementKind::If(statement)              => Statement::new(Box::new(statement)),
+			OwnedStatementKind::While(statement)           => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Break(statement)           => Statement::new(Box::new(statement)),
+			OwnedStatementKind::Continue(statement)        => Statement::new(Box::new(statement))
+		}
+	}
+}
+
+// Copies the borrowed node
+impl From<StatementKind<'_>> for Statement
+{
+	fn from(kind: StatementKind<'_>) -> Self
+	{
+		match kind
+		{
+			StatementKind::FunctionDefine(statement)  => Statement::new(Box::new(statement.clone())),
+			StatementKind::FunctionDeclare(statement) => Statement::new(Box::new(statement.clone())),
+			StatementKind::FunctionReturn(statement)  => Statement::new(Box::new(statement.clone())),
+			StatementKind::Expression(statement)      => Statement::new(Box::new(statement.clone())),
+			StatementKind::Compound(statement)        => Statement::new(Box::new(statement.clone())),
+			StatementKind::Declare(statement)         => Statement::new(Box::new(statement.clone())),
+			StatementKind::Assign(statement)          => Statement::new(Box::new(statement.clone())),
+			StatementKind::Print(statement)           => Statement::new(Box::new(statement.clone())),
+			StatementKind::If(statement)              => Statement::new(Box::new(statement.clone())),
+			StatementKind::While(statement)           => Statement::new(Box::new(statement.clone())),
+			StatementKind::Break(statement)           => Statement::new(Box::new(statement.clone())),
+			StatementKind::Continue(statement)        => Statement::new(Box::new(statement.clone()))
+		}
+	}
+}
+
+impl<'a> From<&'a Statement> for StatementKind<'a>
+{
+	fn from(statement: &'a Statement) -> Self
+	{
+		statement.kind()
+	}
+}
+
+impl From<Statement> for OwnedStatementKind
+{
+	fn from(statement: Statement) -> Self
+	{
+		statement.into_kind()
+	}
+}
+
 #[derive(Clone)]
 pub struct Statement
 {
@@ -384,6 +820,16 @@ impl Statement
 		self.statement.stype()
 	}
 
+	pub fn unparse(&self) -> VecDeque<Token>
+	{
+		self.statement.unparse()
+	}
+
+	pub fn span(&self) -> Span
+	{
+		self.statement.span()
+	}
+
 	// New functions:
 	pub fn new(statement: StatementBox) -> Self
 	{
@@ -430,9 +876,85 @@ impl Statement
 		Self::new(Box::new(PrintStatement::new(tokens, expression)))
 	}
 
+	pub fn new_if(tokens: VecDeque<Token>, condition: Expression, then_body: CompoundStatement, else_body: Option<Statement>) -> Self
+	{
+		Self::new(Box::new(IfStatement::new(tokens, condition, then_body, else_body)))
+	}
+
+	pub fn new_while(tokens: VecDeque<Token>, condition: Expression, body: CompoundStatement) -> Self
+	{
+		Self::new(Box::new(WhileStatement::new(tokens, condition, body)))
+	}
+
+	pub fn new_break(tokens: VecDeque<Token>) -> Self
+	{
+		Self::new(Box::new(BreakStatement::new(tokens)))
+	}
+
+	pub fn new_continue(tokens: VecDeque<Token>) -> Self
+	{
+		Self::new(Box::new(ContinueStatement::new(tokens)))
+	}
+
 	// As function:
 	pub fn as_statement<T: 'static>(&self) -> Option<&T>
 	{
 		self.statement.as_any().downcast_ref::<T>()
 	}
+
+	fn downcast_ref<T: 'static>(&self) -> &T
+	{
+		self.as_statement::<T>()
+			.unwrap_or_else(|| panic!("statement of type `{:?}` is not implemented by this module", self.stype()))
+	}
+
+	fn downcast<T: 'static>(self) -> T
+	{
+		let stype = self.stype();
+
+		*self.statement
+			.into_any()
+			.downcast::<T>()
+			.unwrap_or_else(|_| panic!("statement of type `{:?}` is not implemented by this module", stype))
+	}
+
+	// Panics only for a `StatementTrait` implemented outside of this module
+	pub fn kind(&self) -> StatementKind<'_>
+	{
+		match self.stype()
+		{
+			StatementType::FunctionDefine  => StatementKind::FunctionDefine(self.downcast_ref()),
+			StatementType::FunctionDeclare => StatementKind::FunctionDeclare(self.downcast_ref()),
+			StatementType::FunctionReturn  => StatementKind::FunctionReturn(self.downcast_ref()),
+			StatementType::Expression      => StatementKind::Expression(self.downcast_ref()),
+			StatementType::Compound        => StatementKind::Compound(self.downcast_ref()),
+			StatementType::Declare         => StatementKind::Declare(self.downcast_ref()),
+			StatementType::Assign          => StatementKind::Assign(self.downcast_ref()),
+			StatementType::Print           => StatementKind::Print(self.downcast_ref()),
+			StatementType::If              => StatementKind::If(self.downcast_ref()),
+			StatementType::While           => StatementKind::While(self.downcast_ref()),
+			StatementType::Break           => StatementKind::Break(self.downcast_ref()),
+			StatementType::Continue        => StatementKind::Continue(self.downcast_ref())
+		}
+	}
+
+	// Takes the node out without copying it, panics like `kind`
+	pub fn into_kind(self) -> OwnedStatementKind
+	{
+		match self.stype()
+		{
+			StatementType::FunctionDefine  => OwnedStatementKind::FunctionDefine(self.downcast()),
+			StatementType::FunctionDeclare => OwnedStatementKind::FunctionDeclare(self.downcast()),
+			StatementType::FunctionReturn  => OwnedStatementKind::FunctionReturn(self.downcast()),
+			StatementType::Expression      => OwnedStatementKind::Expression(self.downcast()),
+			StatementType::Compound        => OwnedStatementKind::Compound(self.downcast()),
+			StatementType::Declare         => OwnedStatementKind::Declare(self.downcast()),
+			StatementType::Assign          => OwnedStatementKind::Assign(self.downcast()),
+			StatementType::Print           => OwnedStatementKind::Print(self.downcast()),
+			StatementType::If              => OwnedStatementKind::If(self.downcast()),
+			StatementType::While           => OwnedStatementKind::While(self.downcast()),
+			StatementType::Break           => OwnedStatementKind::Break(self.downcast()),
+			StatementType::Continue        => OwnedStatementKind::Continue(self.downcast())
+		}
+	}
 }
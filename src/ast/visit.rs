@@ -0,0 +1,487 @@
+use super::{
+	root::*,
+	statement::*,
+	expression::*
+};
+
+// Read-only traversal. Every `visit_*` method defaults to the matching `walk_*` function,
+// which visits the children of the node, so an override only needs to call it to keep descending.
+pub trait Visitor
+{
+	fn visit_root(&mut self, root: &Root)
+	{
+		walk_root(self, root);
+	}
+
+	fn visit_statement(&mut self, statement: &Statement)
+	{
+		walk_statement(self, statement);
+	}
+
+	fn visit_function_define(&mut self, function_define: &FunctionDefineStatement)
+	{
+		walk_function_define(self, function_define);
+	}
+
+	fn visit_function_declare(&mut self, _function_declare: &FunctionDeclareStatement) {}
+
+	fn visit_function_return(&mut self, function_return: &FunctionReturnStatement)
+	{
+		walk_function_return(self, function_return);
+	}
+
+	fn visit_expression_statement(&mut self, expression_statement: &ExpressionStatement)
+	{
+		walk_expression_statement(self, expression_statement);
+	}
+
+	fn visit_compound(&mut self, compound: &CompoundStatement)
+	{
+		walk_compound(self, compound);
+	}
+
+	fn visit_declare(&mut self, declare: &DeclareStatement)
+	{
+		walk_declare(self, declare);
+	}
+
+	fn visit_assign(&mut self, assign: &AssignStatement)
+	{
+		walk_assign(self, assign);
+	}
+
+	fn visit_print(&mut self, print: &PrintStatement)
+	{
+		walk_print(self, print);
+	}
+
+	fn visit_if(&mut self, if_statement: &IfStatement)
+	{
+		walk_if(self, if_statement);
+	}
+
+	fn visit_while(&mut self, while_statement: &WhileStatement)
+	{
+		walk_while(self, while_statement);
+	}
+
+	fn visit_break(&mut self, _break_statement: &BreakStatement) {}
+
+	fn visit_continue(&mut self, _continue_statement: &ContinueStatement) {}
+
+	fn visit_expression(&mut self, expression: &Expression)
+	{
+		walk_expression(self, expression);
+	}
+
+	fn visit_literal(&mut self, _literal: &LiteralExpression) {}
+
+	fn visit_function_call(&mut self, function_call: &FunctionCallExpression)
+	{
+		walk_function_call(self, function_call);
+	}
+
+	fn visit_variable(&mut self, _variable: &VariableExpression) {}
+
+	fn visit_arithmetic(&mut self, arithmetic: &ArithmeticExpression)
+	{
+		walk_arithmetic(self, arithmetic);
+	}
+
+	fn visit_comparison(&mut self, comparison: &ComparisonExpression)
+	{
+		walk_comparison(self, comparison);
+	}
+
+	fn visit_boolean(&mut self, boolean: &BooleanExpression)
+	{
+		walk_boolean(self, boolean);
+	}
+}
+
+pub fn walk_root<V: Visitor + ?Sized>(visitor: &mut V, root: &Root)
+{
+	for statement in root.statements.iter()
+	{
+		visitor.visit_statement(statement);
+	}
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement)
+{
+	match statement.kind()
+	{
+		StatementKind::FunctionDefine(function_define) => visitor.visit_function_define(function_define),
+		StatementKind::FunctionDeclare(function_declare) => visitor.visit_function_declare(function_declare),
+		StatementKind::FunctionReturn(function_return) => visitor.visit_function_return(function_return),
+		StatementKind::Expression(expression_statement) => visitor.visit_expression_statement(expression_statement),
+		StatementKind::Compound(compound) => visitor.visit_compound(compound),
+		StatementKind::Declare(declare) => visitor.visit_declare(declare),
+		StatementKind::Assign(assign) => visitor.visit_assign(assign),
+		StatementKind::Print(print) => visitor.visit_print(print),
+		StatementKind::If(if_statement) => visitor.visit_if(if_statement),
+		StatementKind::While(while_statement) => visitor.visit_while(while_statement),
+		StatementKind::Break(break_statement) => visitor.visit_break(break_statement),
+		StatementKind::Continue(continue_statement) => visitor.visit_continue(continue_statement)
+	}
+}
+
+pub fn walk_function_define<V: Visitor + ?Sized>(visitor: &mut V, function_define: &FunctionDefineStatement)
+{
+	visitor.visit_compound(function_define.body());
+}
+
+pub fn walk_function_return<V: Visitor + ?Sized>(visitor: &mut V, function_return: &FunctionReturnStatement)
+{
+	if let Some(expression) = function_return.expression()
+	{
+		visitor.visit_expression(expression);
+	}
+}
+
+pub fn walk_expression_statement<V: Visitor + ?Sized>(visitor: &mut V, expression_statement: &ExpressionStatement)
+{
+	visitor.visit_expression(expression_statement.expression());
+}
+
+pub fn walk_compound<V: Visitor + ?Sized>(visitor: &mut V, compound: &CompoundStatement)
+{
+	for statement in compound.statements().iter()
+	{
+		visitor.visit_statement(statement);
+	}
+}
+
+pub fn walk_declare<V: Visitor + ?Sized>(visitor: &mut V, declare: &DeclareStatement)
+{
+	visitor.visit_expression(declare.expression());
+}
+
+pub fn walk_assign<V: Visitor + ?Sized>(visitor: &mut V, assign: &AssignStatement)
+{
+	visitor.visit_expression(assign.expression());
+}
+
+pub fn walk_print<V: Visitor + ?Sized>(visitor: &mut V, print: &PrintStatement)
+{
+	visitor.visit_expression(print.expression());
+}
+
+pub fn walk_if<V: Visitor + ?Sized>(visitor: &mut V, if_statement: &IfStatement)
+{
+	visitor.visit_expression(if_statement.condition());
+	visitor.visit_compound(if_statement.then_body());
+
+	if let Some(else_body) = if_statement.else_body()
+	{
+		visitor.visit_statement(else_body);
+	}
+}
+
+pub fn walk_while<V: Visitor + ?Sized>(visitor: &mut V, while_statement: &WhileStatement)
+{
+	visitor.visit_expression(while_statement.condition());
+	visitor.visit_compound(while_statement.body());
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expression: &Expression)
+{
+	match expression.kind()
+	{
+		ExpressionKind::Literal(literal) => visitor.visit_literal(literal),
+		ExpressionKind::FunctionCall(function_call) => visitor.visit_function_call(function_call),
+		ExpressionKind::Variable(variable) => visitor.visit_variable(variable),
+		ExpressionKind::Arithmetic(arithmetic) => visitor.visit_arithmetic(arithmetic),
+		ExpressionKind::Comparison(comparison) => visitor.visit_comparison(comparison),
+		ExpressionKind::Boolean(boolean) => visitor.visit_boolean(boolean)
+	}
+}
+
+pub fn walk_function_call<V: Visitor + ?Sized>(visitor: &mut V, function_call: &FunctionCallExpression)
+{
+	for argument in function_call.passed_arguments().iter()
+	{
+		visitor.visit_expression(argument);
+	}
+}
+
+pub fn walk_arithmetic<V: Visitor + ?Sized>(visitor: &mut V, arithmetic: &ArithmeticExpression)
+{
+	visitor.visit_expression(arithmetic.left());
+	visitor.visit_expression(arithmetic.right());
+}
+
+pub fn walk_comparison<V: Visitor + ?Sized>(visitor: &mut V, comparison: &ComparisonExpression)
+{
+	visitor.visit_expression(comparison.left());
+	visitor.visit_expression(comparison.right());
+}
+
+pub fn walk_boolean<V: Visitor + ?Sized>(visitor: &mut V, boolean: &BooleanExpression)
+{
+	visitor.visit_expression(boolean.left());
+	visitor.visit_expression(boolean.right());
+}
+
+// Rewriting traversal, the nodes are immutable so every `fold_*` method returns a rebuilt
+// node. The defaults rebuild it from folded children through the matching `rebuild_*`
+// function and keep its tokens. A statement or expression may be replaced by one of
+// another type, a compound statement stays one since function and loop bodies need it.
+pub trait Folder
+{
+	fn fold_root(&mut self, root: Root) -> Root
+	{
+		rebuild_root(self, root)
+	}
+
+	fn fold_statement(&mut self, statement: Statement) -> Statement
+	{
+		rebuild_statement(self, statement)
+	}
+
+	fn fold_function_define(&mut self, function_define: FunctionDefineStatement) -> Statement
+	{
+		rebuild_function_define(self, function_define)
+	}
+
+	fn fold_function_declare(&mut self, function_declare: FunctionDeclareStatement) -> Statement
+	{
+		OwnedStatementKind::FunctionDeclare(function_declare).into()
+	}
+
+	fn fold_function_return(&mut self, function_return: FunctionReturnStatement) -> Statement
+	{
+		rebuild_function_return(self, function_return)
+	}
+
+	fn fold_expression_statement(&mut self, expression_statement: ExpressionStatement) -> Statement
+	{
+		rebuild_expression_statement(self, expression_statement)
+	}
+
+	fn fold_compound(&mut self, compound: CompoundStatement) -> CompoundStatement
+	{
+		rebuild_compound(self, compound)
+	}
+
+	fn fold_declare(&mut self, declare: DeclareStatement) -> Statement
+	{
+		rebuild_declare(self, declare)
+	}
+
+	fn fold_assign(&mut self, assign: AssignStatement) -> Statement
+	{
+		rebuild_assign(self, assign)
+	}
+
+	fn fold_print(&mut self, print: PrintStatement) -> Statement
+	{
+		rebuild_print(self, print)
+	}
+
+	fn fold_if(&mut self, if_statement: IfStatement) -> Statement
+	{
+		rebuild_if(self, if_statement)
+	}
+
+	fn fold_while(&mut self, while_statement: WhileStatement) -> Statement
+	{
+		rebuild_while(self, while_statement)
+	}
+
+	fn fold_break(&mut self, break_statement: BreakStatement) -> Statement
+	{
+		OwnedStatementKind::Break(break_statement).into()
+	}
+
+	fn fold_continue(&mut self, continue_statement: ContinueStatement) -> Statement
+	{
+		OwnedStatementKind::Continue(continue_statement).into()
+	}
+
+	fn fold_expression(&mut self, expression: Expression) -> Expression
+	{
+		rebuild_expression(self, expression)
+	}
+
+	fn fold_literal(&mut self, literal: LiteralExpression) -> Expression
+	{
+		OwnedExpressionKind::Literal(literal).into()
+	}
+
+	fn fold_function_call(&mut self, function_call: FunctionCallExpression) -> Expression
+	{
+		rebuild_function_call(self, function_call)
+	}
+
+	fn fold_variable(&mut self, variable: VariableExpression) -> Expression
+	{
+		OwnedExpressionKind::Variable(variable).into()
+	}
+
+	fn fold_arithmetic(&mut self, arithmetic: ArithmeticExpression) -> Expression
+	{
+		rebuild_arithmetic(self, arithmetic)
+	}
+
+	fn fold_comparison(&mut self, comparison: ComparisonExpression) -> Expression
+	{
+		rebuild_comparison(self, comparison)
+	}
+
+	fn fold_boolean(&mut self, boolean: BooleanExpression) -> Expression
+	{
+		rebuild_boolean(self, boolean)
+	}
+}
+
+pub fn rebuild_root<F: Folder + ?Sized>(folder: &mut F, root: Root) -> Root
+{
+	Root
+	{
+		statements: root.statements
+			.into_iter()
+			.map(|statement| folder.fold_statement(statement))
+			.collect()
+	}
+}
+
+pub fn rebuild_statement<F: Folder + ?Sized>(folder: &mut F, statement: Statement) -> Statement
+{
+	match statement.into_kind()
+	{
+		OwnedStatementKind::FunctionDefine(function_define) => folder.fold_function_define(function_define),
+		OwnedStatementKind::FunctionDeclare(function_declare) => folder.fold_function_declare(function_declare),
+		OwnedStatementKind::FunctionReturn(function_return) => folder.fold_function_return(function_return),
+		OwnedStatementKind::Expression(expression_statement) => folder.fold_expression_statement(expression_statement),
+		OwnedStatementKind::Compound(compound) => OwnedStatementKind::Compound(folder.fold_compound(compound)).into(),
+		OwnedStatementKind::Declare(declare) => folder.fold_declare(declare),
+		OwnedStatementKind::Assign(assign) => folder.fold_assign(assign),
+		OwnedStatementKind::Print(print) => folder.fold_print(print),
+		OwnedStatementKind::If(if_statement) => folder.fold_if(if_statement),
+		OwnedStatementKind::While(while_statement) => folder.fold_while(while_statement),
+		OwnedStatementKind::Break(break_statement) => folder.fold_break(break_statement),
+		OwnedStatementKind::Continue(continue_statement) => folder.fold_continue(continue_statement)
+	}
+}
+
+pub fn rebuild_function_define<F: Folder + ?Sized>(folder: &mut F, function_define: FunctionDefineStatement) -> Statement
+{
+	let body = folder.fold_compound(function_define.body().clone());
+
+	Statement::new_function_define(function_define.unparse(), function_define.signature().clone(), body)
+}
+
+pub fn rebuild_function_return<F: Folder + ?Sized>(folder: &mut F, function_return: FunctionReturnStatement) -> Statement
+{
+	let expression = function_return.expression()
+		.clone()
+		.map(|expression| folder.fold_expression(expression));
+
+	Statement::new_function_return(function_return.unparse(), expression)
+}
+
+pub fn rebuild_expression_statement<F: Folder + ?Sized>(folder: &mut F, expression_statement: ExpressionStatement) -> Statement
+{
+	let expression = folder.fold_expression(expression_statement.expression().clone());
+
+	Statement::new_expression(expression_statement.unparse(), expression)
+}
+
+pub fn rebuild_compound<F: Folder + ?Sized>(folder: &mut F, compound: CompoundStatement) -> CompoundStatement
+{
+	let statements = compound.statements()
+		.iter()
+		.map(|statement| folder.fold_statement(statement.clone()))
+		.collect();
+
+	CompoundStatement::new(compound.unparse(), statements)
+}
+
+pub fn rebuild_declare<F: Folder + ?Sized>(folder: &mut F, declare: DeclareStatement) -> Statement
+{
+	let expression = folder.fold_expression(declare.expression().clone());
+
+	Statement::new_declare(declare.unparse(), declare.vtype(), declare.identifier(), expression)
+}
+
+pub fn rebuild_assign<F: Folder + ?Sized>(folder: &mut F, assign: AssignStatement) -> Statement
+{
+	let expression = folder.fold_expression(assign.expression().clone());
+
+	Statement::new_assign(assign.unparse(), assign.identifier(), expression)
+}
+
+pub fn rebuild_print<F: Folder + ?Sized>(folder: &mut F, print: PrintStatement) -> Statement
+{
+	let expression = folder.fold_expression(print.expression().clone());
+
+	Statement::new_print(print.unparse(), expression)
+}
+
+pub fn rebuild_if<F: Folder + ?Sized>(folder: &mut F, if_statement: IfStatement) -> Statement
+{
+	let condition = folder.fold_expression(if_statement.condition().clone());
+	let then_body = folder.fold_compound(if_statement.then_body().clone());
+
+	let else_body = if_statement.else_body()
+		.clone()
+		.map(|else_body| folder.fold_statement(else_body));
+
+	Statement::new_if(if_statement.unparse(), condition, then_body, else_body)
+}
+
+pub fn rebuild_while<F: Folder + ?Sized>(folder: &mut F, while_statement: WhileStatement) -> Statement
+{
+	let condition = folder.fold_expression(while_statement.condition().clone());
+	let body = folder.fold_compound(while_statement.body().clone());
+
+	Statement::new_while(while_statement.unparse(), condition, body)
+}
+
+pub fn rebuild_expression<F: Folder + ?Sized>(folder: &mut F, expression: Expression) -> Expression
+{
+	match expression.into_kind()
+	{
+		OwnedExpressionKind::Literal(literal) => folder.fold_literal(literal),
+		OwnedExpressionKind::FunctionCall(function_call) => folder.fold_function_call(function_call),
+		OwnedExpressionKind::Variable(variable) => folder.fold_variable(variable),
+		OwnedExpressionKind::Arithmetic(arithmetic) => folder.fold_arithmetic(arithmetic),
+		OwnedExpressionKind::Comparison(comparison) => folder.fold_comparison(comparison),
+		OwnedExpressionKind::Boolean(boolean) => folder.fold_boolean(boolean)
+	}
+}
+
+pub fn rebuild_function_call<F: Folder + ?Sized>(folder: &mut F, function_call: FunctionCallExpression) -> Expression
+{
+	let arguments = function_call.passed_arguments()
+		.iter()
+		.map(|argument| folder.fold_expression(argument.clone()))
+		.collect();
+
+	Expression::new_function_call(function_call.tokens().clone(), function_call.vtype(), function_call.name(), arguments)
+}
+
+pub fn rebuild_arithmetic<F: Folder + ?Sized>(folder: &mut F, arithmetic: ArithmeticExpression) -> Expression
+{
+	let left = folder.fold_expression(arithmetic.left().clone());
+	let right = folder.fold_expression(arithmetic.right().clone());
+
+	Expression::new_arithmetic(arithmetic.tokens().clone(), arithmetic.vtype(), arithmetic.op(), left, right)
+}
+
+pub fn rebuild_comparison<F: Folder + ?Sized>(folder: &mut F, comparison: ComparisonExpression) -> Expression
+{
+	let left = folder.fold_expression(comparison.left().clone());
+	let right = folder.fold_expression(comparison.right().clone());
+
+	Expression::new_comparison(comparison.tokens().clone(), comparison.op(), left, right)
+}
+
+pub fn rebuild_boolean<F: Folder + ?Sized>(folder: &mut F, boolean: BooleanExpression) -> Expression
+{
+	let left = folder.fold_expression(boolean.left().clone());
+	let right = folder.fold_expression(boolean.right().clone());
+
+	Expression::new_boolean(boolean.tokens().clone(), boolean.op(), left, right)
+}
@@ -0,0 +1,22 @@
+use std::io;
+use std::process::ExitCode;
+
+use faren_canon::lsp::Server;
+
+// Speaks the Language Server Protocol over standard input and output
+fn main() -> ExitCode
+{
+	match Server::new().run(io::stdin().lock(), io::stdout().lock())
+	{
+		Ok(true) => ExitCode::SUCCESS,
+
+		// The protocol asks for a failure when the client exits without a shutdown first
+		Ok(false) => ExitCode::FAILURE,
+
+		Err(err) =>
+		{
+			eprintln!("error: {}", err);
+			ExitCode::FAILURE
+		}
+	}
+}
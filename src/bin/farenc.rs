@@ -0,0 +1,281 @@
+use std::io::{
+	self,
+	BufRead,
+	IsTerminal,
+	Read,
+	Write
+};
+
+use std::process::ExitCode;
+
+use faren_canon::ast::dump::dump;
+use faren_canon::fmt::*;
+use faren_canon::interp::{
+	run,
+	with_stack
+};
+use faren_canon::parser::*;
+use faren_canon::parser::lexer::lex;
+use faren_canon::parser::render::render_error;
+use faren_canon::repl::*;
+
+const USAGE: &str = "\
+usage: farenc <command> [options] [file]
+
+commands:
+  check     parse and type check, reporting every error
+  tokens    print the tokens the lexer produces
+  ast       print the parsed tree
+  run       interpret the program, starting at `main`
+  fmt       print the program in canonical form, comments included
+  repl      start an interactive session on standard input
+
+options:
+  --spaces <n>    indent with `n` spaces instead of tabs (fmt)
+
+The file is read from standard input when it is omitted or `-`.
+
+exit status:
+  0  success
+  1  the program has errors, or failed at runtime
+  2  bad usage, or the file could not be read";
+
+// Errors in the program are distinguished from errors in how the driver was invoked
+const EXIT_PROGRAM: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+
+enum Command
+{
+	Check,
+	Tokens,
+	Ast,
+	Run,
+	Fmt,
+	Repl
+}
+
+struct Arguments
+{
+	command: Command,
+	path: Option<String>,
+	options: FormatOptions
+}
+
+fn parse_arguments(arguments: &[String]) -> Result<Arguments, String>
+{
+	let (command, rest) = arguments.split_first().ok_or("no command given")?;
+
+	let command = match command.as_str()
+	{
+		"check" => Command::Check,
+		"tokens" => Command::Tokens,
+		"ast" => Command::Ast,
+		"run" => Command::Run,
+		"fmt" => Command::Fmt,
+		"repl" => Command::Repl,
+		_ => return Err(format!("unknown command `{}`", command))
+	};
+
+	let mut path = None;
+	let mut options = FormatOptions::default();
+	let mut rest = rest.iter();
+
+	while let Some(argument) = rest.next()
+	{
+		match argument.as_str()
+		{
+			"--spaces" =>
+			{
+				let count = rest.next().ok_or("expected a number after `--spaces`")?;
+				let count = count.parse::<usize>().map_err(|_| format!("invalid indentation width `{}`", count))?;
+				options.indent = Indent::Spaces(count);
+			}
+
+			_ if argument.starts_with("--") => return Err(format!("unknown option `{}`", argument)),
+
+			_ if path.is_some() => return Err(format!("unexpected argument `{}`", argument)),
+
+			_ => path = Some(argument.clone())
+		}
+	}
+
+	Ok(Arguments { command, path: path.filter(|path| path != "-"), options })
+}
+
+fn read_source(path: &Option<String>) -> io::Result<String>
+{
+	match path
+	{
+		Some(path) => std::fs::read_to_string(path),
+
+		None =>
+		{
+			let mut source = String::new();
+			io::stdin().read_to_string(&mut source)?;
+			Ok(source)
+		}
+	}
+}
+
+fn report(file_name: &str, errors: &[ParserError])
+{
+	let colour = io::stderr().is_terminal();
+	let mut stderr = io::stderr().lock();
+
+	for error in errors
+	{
+		let _ = writeln!(stderr, "{}", render_error(file_name, error, colour));
+	}
+
+	let _ = writeln!(stderr, "{} error{} in {}", errors.len(), if errors.len() == 1 { "" } else { "s" }, file_name);
+}
+
+fn interactive() -> io::Result<()>
+{
+	let mut repl = Repl::new();
+	let mut stdout = io::stdout().lock();
+
+	// Prompts would only clutter the output when the input is piped in
+	let prompt = io::stdin().is_terminal();
+
+	if prompt
+	{
+		writeln!(stdout, "farenc {}, `:help` lists the commands", env!("CARGO_PKG_VERSION"))?;
+	}
+
+	let mut lines = io::stdin().lock().lines();
+
+	loop
+	{
+		if prompt
+		{
+			write!(stdout, "{}", repl.prompt())?;
+			stdout.flush()?;
+		}
+
+		let Some(line) = lines.next() else
+		{
+			repl.submit(&mut stdout)?;
+			break;
+		};
+
+		if repl.feed(&line?, &mut stdout)? == Status::Quit
+		{
+			break;
+		}
+	}
+
+	Ok(())
+}
+
+fn farenc() -> ExitCode
+{
+	let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+	if arguments.first().is_some_and(|argument| argument == "--help" || argument == "-h")
+	{
+		println!("{}", USAGE);
+		return ExitCode::SUCCESS;
+	}
+
+	let arguments = match parse_arguments(&arguments)
+	{
+		Ok(arguments) => arguments,
+
+		Err(message) =>
+		{
+			eprintln!("error: {}\n\n{}", message, USAGE);
+			return ExitCode::from(EXIT_USAGE);
+		}
+	};
+
+	if let Command::Repl = arguments.command
+	{
+		return match interactive()
+		{
+			Ok(()) => ExitCode::SUCCESS,
+
+			Err(err) =>
+			{
+				eprintln!("error: {}", err);
+				ExitCode::from(EXIT_USAGE)
+			}
+		};
+	}
+
+	let file_name = arguments.path.clone().unwrap_or_else(|| "<stdin>".to_string());
+
+	let source = match read_source(&arguments.path)
+	{
+		Ok(source) => source,
+
+		Err(err) =>
+		{
+			eprintln!("error: could not read `{}`: {}", file_name, err);
+			return ExitCode::from(EXIT_USAGE);
+		}
+	};
+
+	if let Command::Tokens = arguments.command
+	{
+		let (tokens, errors) = lex(source.clone());
+		let mut stdout = io::stdout().lock();
+
+		for token in tokens.iter()
+		{
+			let info = token.info();
+			let _ = writeln!(stdout, "{}:{}-{}\t{}", info.line, info.column_begin, info.column_end, token);
+		}
+
+		if errors.is_empty()
+		{
+			return ExitCode::SUCCESS;
+		}
+
+		let errors: Vec<ParserError> = errors
+			.iter()
+			.map(|error| ParserError::new(&error.message, &error.info, &source))
+			.collect();
+
+		report(&file_name, &errors);
+		return ExitCode::from(EXIT_PROGRAM);
+	}
+
+	let (root, errors) = parse_root(source.clone());
+
+	// A tree with errors is still dumped, it is the best the parser could recover
+	if let Command::Ast = arguments.command
+	{
+		print!("{}", dump(&root));
+	}
+
+	if !errors.is_empty()
+	{
+		report(&file_name, &errors);
+		return ExitCode::from(EXIT_PROGRAM);
+	}
+
+	match arguments.command
+	{
+		Command::Run =>
+		{
+			if let Err(err) = run(&root, io::stdout().lock())
+			{
+				eprintln!("error: {}", err);
+				return ExitCode::from(EXIT_PROGRAM);
+			}
+		}
+
+		Command::Fmt => print!("{}", format(&root, &source, &arguments.options)),
+
+		Command::Check | Command::Ast | Command::Tokens | Command::Repl => ()
+	}
+
+	ExitCode::SUCCESS
+}
+
+fn main() -> ExitCode
+{
+	// `run` and `repl` interpret programs, which recurse on the host stack
+	with_stack(farenc)
+}
@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+use super::*;
+
+struct LoopContext
+{
+	start: u32,
+	breaks: Vec<usize>
+}
+
+struct FunctionCompiler<'a>
+{
+	indices: &'a HashMap<String, u16>,
+
+	// Variable identifiers are unique per program, slots are dense per function
+	slots: HashMap<u16, u16>,
+	code: Vec<Instruction>,
+	loops: Vec<LoopContext>
+}
+
+impl FunctionCompiler<'_>
+{
+	fn slot(&mut self, identifier: u16) -> u16
+	{
+		let next = self.slots.len() as u16;
+		*self.slots.entry(identifier).or_insert(next)
+	}
+
+	fn position(&self) -> u32
+	{
+		self.code.len() as u32
+	}
+
+	fn emit(&mut self, instruction: Instruction) -> usize
+	{
+		self.code.push(instruction);
+		self.code.len() - 1
+	}
+
+	fn patch(&mut self, at: usize, target: u32)
+	{
+		self.code[at] = match self.code[at]
+		{
+			Instruction::Jump(_) => Instruction::Jump(target),
+			Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+			other => unreachable!("patching non-jump instruction `{}`", other)
+		};
+	}
+
+	fn compile_compound(&mut self, compound: &CompoundStatement)
+	{
+		for statement in compound.statements()
+		{
+			self.compile_statement(statement);
+		}
+	}
+
+	fn compile_statement(&mut self, statement: &Statement)
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				self.compile_compound(statement.as_statement::<CompoundStatement>().unwrap());
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+
+				self.compile_expression(declare.expression());
+
+				let slot = self.slot(declare.identifier());
+				self.emit(Instruction::Store(slot));
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+
+				self.compile_expression(assign.expression());
+
+				let slot = self.slot(assign.identifier());
+				self.emit(Instruction::Store(slot));
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+
+				self.compile_expression(print.expression());
+				self.emit(Instruction::Print);
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+
+				self.compile_expression(express.expression());
+				self.emit(Instruction::Pop);
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				match ret.expression()
+				{
+					Some(expr) => self.compile_expression(expr),
+					None => { self.emit(Instruction::PushVoid); }
+				}
+
+				self.emit(Instruction::Return);
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				self.compile_expression(if_statement.condition());
+				let to_else = self.emit(Instruction::JumpIfFalse(0));
+
+				self.compile_compound(if_statement.then_body());
+
+				match if_statement.else_body()
+				{
+					Some(else_body) =>
+					{
+						let to_end = self.emit(Instruction::Jump(0));
+
+						let else_start = self.position();
+						self.patch(to_else, else_start);
+
+						self.compile_statement(else_body);
+
+						let end = self.position();
+						self.patch(to_end, end);
+					}
+
+					None =>
+					{
+						let end = self.position();
+						self.patch(to_else, end);
+					}
+				}
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				let start = self.position();
+
+				self.compile_expression(while_statement.condition());
+				let to_end = self.emit(Instruction::JumpIfFalse(0));
+
+				self.loops.push(LoopContext { start, breaks: Vec::new() });
+				self.compile_compound(while_statement.body());
+				let loop_context = self.loops.pop().unwrap();
+
+				self.emit(Instruction::Jump(start));
+
+				let end = self.position();
+				self.patch(to_end, end);
+
+				for at in loop_context.breaks
+				{
+					self.patch(at, end);
+				}
+			}
+
+			StatementType::Break =>
+			{
+				let at = self.emit(Instruction::Jump(0));
+
+				self.loops
+					.last_mut()
+					.expect("`break` outside of a loop")
+					.breaks
+					.push(at);
+			}
+
+			StatementType::Continue =>
+			{
+				let start = self.loops
+					.last()
+					.expect("`continue` outside of a loop")
+					.start;
+
+				self.emit(Instruction::Jump(start));
+			}
+
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => {}
+		}
+	}
+
+	fn compile_expression(&mut self, expression: &Expression)
+	{
+		match expression.etype()
+		{
+			ExpressionType::Literal =>
+			{
+				let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+
+				match literal.vtype()
+				{
+					VType::Integer => self.emit(Instruction::PushInteger(literal.as_literal::<IntegerLiteral>().unwrap().value)),
+					VType::Boolean => self.emit(Instruction::PushBoolean(literal.as_literal::<BooleanLiteral>().unwrap().value)),
+					VType::Void => self.emit(Instruction::PushVoid)
+				};
+			}
+
+			ExpressionType::Variable =>
+			{
+				let variable = expression.as_expression::<VariableExpression>().unwrap();
+
+				let slot = self.slot(variable.identifier());
+				self.emit(Instruction::Load(slot));
+			}
+
+			ExpressionType::FunctionCall =>
+			{
+				let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+
+				for argument in call.passed_arguments().iter()
+				{
+					self.compile_expression(argument);
+				}
+
+				let index = self.indices[&call.name()];
+				self.emit(Instruction::Call(index));
+			}
+
+			ExpressionType::Arithmetic =>
+			{
+				let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+				self.compile_expression(arith.left());
+				self.compile_expression(arith.right());
+
+				self.emit(match arith.op()
+				{
+					ArithmeticOperation::Add => Instruction::Add,
+					ArithmeticOperation::Subtract => Instruction::Subtract,
+					ArithmeticOperation::Multiply => Instruction::Multiply,
+					ArithmeticOperation::Divide => Instruction::Divide
+				});
+			}
+
+			ExpressionType::Comparison =>
+			{
+				let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+				self.compile_expression(cmp.left());
+				self.compile_expression(cmp.right());
+
+				self.emit(match cmp.op()
+				{
+					ComparisonOperation::IsEqual => Instruction::Equal,
+					ComparisonOperation::IsNotEqual => Instruction::NotEqual,
+					ComparisonOperation::IsGreater => Instruction::Greater,
+					ComparisonOperation::IsGreaterOrEqual => Instruction::GreaterOrEqual,
+					ComparisonOperation::IsLess => Instruction::Less,
+					ComparisonOperation::IsLessOrEqual => Instruction::LessOrEqual
+				});
+			}
+
+			ExpressionType::Boolean =>
+			{
+				let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+				// Short-circuit:
+				//   and: <left> jf F; <right>; jmp E; F: push false; E:
+				//   or:  <left> jf R; push true; jmp E; R: <right>; E:
+				self.compile_expression(boolean.left());
+				let to_other = self.emit(Instruction::JumpIfFalse(0));
+
+				match boolean.op()
+				{
+					BooleanOperation::And =>
+					{
+						self.compile_expression(boolean.right());
+						let to_end = self.emit(Instruction::Jump(0));
+
+						let other = self.position();
+						self.patch(to_other, other);
+						self.emit(Instruction::PushBoolean(false));
+
+						let end = self.position();
+						self.patch(to_end, end);
+					}
+
+					BooleanOperation::Or =>
+					{
+						self.emit(Instruction::PushBoolean(true));
+						let to_end = self.emit(Instruction::Jump(0));
+
+						let other = self.position();
+						self.patch(to_other, other);
+						self.compile_expression(boolean.right());
+
+						let end = self.position();
+						self.patch(to_end, end);
+					}
+				}
+			}
+		}
+	}
+}
+
+pub fn compile(root: &Root) -> Program
+{
+	let mut functions: Vec<Function> = Vec::new();
+	let mut indices: HashMap<String, u16> = HashMap::new();
+
+	for statement in root.statements.iter()
+	{
+		let signature = match statement.stype()
+		{
+			StatementType::FunctionDefine => statement.as_statement::<FunctionDefineStatement>().unwrap().signature(),
+			StatementType::FunctionDeclare => statement.as_statement::<FunctionDeclareStatement>().unwrap().signature(),
+			_ => continue
+		};
+
+		if let Entry::Vacant(entry) = indices.entry(signature.name())
+		{
+			entry.insert(functions.len() as u16);
+
+			functions.push(Function
+			{
+				name: signature.name(),
+				arity: signature.parameters().len() as u16,
+				slots: signature.parameters().len() as u16,
+				code: Vec::new(),
+				defined: false
+			});
+		}
+	}
+
+	for statement in root.statements.iter()
+	{
+		let Some(func_define) = statement.as_statement::<FunctionDefineStatement>() else
+		{
+			continue;
+		};
+
+		let mut compiler = FunctionCompiler
+		{
+			indices: &indices,
+			slots: HashMap::new(),
+			code: Vec::new(),
+			loops: Vec::new()
+		};
+
+		for param in func_define.signature().parameters()
+		{
+			compiler.slot(param.id());
+		}
+
+		compiler.compile_compound(func_define.body());
+
+		// Falling off the end of a function returns void
+		compiler.emit(Instruction::PushVoid);
+		compiler.emit(Instruction::Return);
+
+		let function = &mut functions[indices[&func_define.signature().name()] as usize];
+
+		function.slots = compiler.slots.len() as u16;
+		function.code = compiler.code;
+		function.defined = true;
+	}
+
+	Program { functions }
+}
@@ -0,0 +1,47 @@
+use std::fmt::Write;
+
+use super::*;
+
+// Lists every function with its instructions, calls are annotated with the callee name:
+//
+// function square (arity 1, slots 1)
+//   0000  load 0
+//   0001  load 0
+//   0002  mul
+//   0003  ret
+pub fn disassemble(program: &Program) -> String
+{
+	let mut output = String::new();
+
+	for (index, function) in program.functions.iter().enumerate()
+	{
+		if index != 0
+		{
+			output.push('\n');
+		}
+
+		if !function.defined
+		{
+			writeln!(output, "function {} (arity {}, declared only)", function.name, function.arity).unwrap();
+			continue;
+		}
+
+		writeln!(output, "function {} (arity {}, slots {})", function.name, function.arity, function.slots).unwrap();
+
+		for (at, instruction) in function.code.iter().enumerate()
+		{
+			match instruction
+			{
+				Instruction::Call(callee) =>
+				{
+					let callee_name = &program.functions[*callee as usize].name;
+					writeln!(output, "  {:04}  {:<16}; {}", at, instruction.to_string(), callee_name).unwrap();
+				}
+
+				_ => writeln!(output, "  {:04}  {}", at, instruction).unwrap()
+			}
+		}
+	}
+
+	output
+}
@@ -0,0 +1,103 @@
+pub mod compiler;
+pub mod vm;
+pub mod disasm;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction
+{
+	PushInteger(i32),
+	PushBoolean(bool),
+	PushVoid,
+	Pop,
+
+	Load(u16),
+	Store(u16),
+
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+
+	Equal,
+	NotEqual,
+	Greater,
+	GreaterOrEqual,
+	Less,
+	LessOrEqual,
+
+	Jump(u32),
+	JumpIfFalse(u32),
+
+	Call(u16),
+	Return,
+
+	Print
+}
+
+impl fmt::Display for Instruction
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Instruction::PushInteger(value) => write!(f, "push.i {}", value),
+			Instruction::PushBoolean(value) => write!(f, "push.b {}", value),
+			Instruction::PushVoid => write!(f, "push.v"),
+			Instruction::Pop => write!(f, "pop"),
+
+			Instruction::Load(slot) => write!(f, "load {}", slot),
+			Instruction::Store(slot) => write!(f, "store {}", slot),
+
+			Instruction::Add => write!(f, "add"),
+			Instruction::Subtract => write!(f, "sub"),
+			Instruction::Multiply => write!(f, "mul"),
+			Instruction::Divide => write!(f, "div"),
+
+			Instruction::Equal => write!(f, "eq"),
+			Instruction::NotEqual => write!(f, "ne"),
+			Instruction::Greater => write!(f, "gt"),
+			Instruction::GreaterOrEqual => write!(f, "ge"),
+			Instruction::Less => write!(f, "lt"),
+			Instruction::LessOrEqual => write!(f, "le"),
+
+			Instruction::Jump(target) => write!(f, "jmp {:04}", target),
+			Instruction::JumpIfFalse(target) => write!(f, "jf {:04}", target),
+
+			Instruction::Call(function) => write!(f, "call {}", function),
+			Instruction::Return => write!(f, "ret"),
+
+			Instruction::Print => write!(f, "print")
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Function
+{
+	pub name: String,
+	pub arity: u16,
+	pub slots: u16,
+	pub code: Vec<Instruction>,
+
+	// Declared functions without a body trap when called
+	pub defined: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct Program
+{
+	pub functions: Vec<Function>
+}
+
+impl Program
+{
+	pub fn function_index(&self, name: &str) -> Option<u16>
+	{
+		self.functions
+			.iter()
+			.position(|function| function.name == name)
+			.map(|index| index as u16)
+	}
+}
@@ -0,0 +1,290 @@
+use std::io::Write;
+
+use crate::data::vtype::*;
+
+use crate::interp::{
+	Value,
+	RuntimeError
+};
+
+use super::*;
+
+// Calls do not recurse on the host stack, so unlike the interpreter this needs no large stack
+pub const DEFAULT_MAX_FRAMES: usize = 1 << 16;
+
+struct CallFrame
+{
+	function: u16,
+	ip: usize,
+	base: usize
+}
+
+pub struct Vm<'a, W: Write>
+{
+	program: &'a Program,
+	stack: Vec<Value>,
+	frames: Vec<CallFrame>,
+	out: W,
+	max_frames: usize
+}
+
+impl<'a, W: Write> Vm<'a, W>
+{
+	pub fn new(program: &'a Program, out: W) -> Self
+	{
+		Self
+		{
+			program,
+			stack: Vec::new(),
+			frames: Vec::new(),
+			out,
+			max_frames: DEFAULT_MAX_FRAMES
+		}
+	}
+
+	pub fn set_max_frames(&mut self, max_frames: usize)
+	{
+		self.max_frames = max_frames;
+	}
+
+	pub fn output(&self) -> &W
+	{
+		&self.out
+	}
+
+	pub fn into_output(self) -> W
+	{
+		self.out
+	}
+
+	pub fn run(&mut self) -> Result<Value, RuntimeError>
+	{
+		if self.program.function_index("main").is_none()
+		{
+			return Err(RuntimeError::MissingMain);
+		}
+
+		self.call("main", Vec::new())
+	}
+
+	pub fn call(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError>
+	{
+		let index = self.program
+			.function_index(name)
+			.ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?;
+
+		let function = &self.program.functions[index as usize];
+
+		if function.arity as usize != arguments.len()
+		{
+			return Err(RuntimeError::MismatchedArguments
+			{
+				name: name.to_string(),
+				expected: function.arity as usize,
+				got: arguments.len()
+			});
+		}
+
+		let entry_depth = self.frames.len();
+		let entry_height = self.stack.len();
+
+		self.stack.extend(arguments);
+
+		let result = self.enter(index).and_then(|_| self.execute(entry_depth));
+
+		// A trap leaves frames behind, unwind them so the VM can be reused
+		self.frames.truncate(entry_depth);
+		self.stack.truncate(entry_height);
+
+		result
+	}
+
+	fn enter(&mut self, index: u16) -> Result<(), RuntimeError>
+	{
+		let function = &self.program.functions[index as usize];
+
+		if !function.defined
+		{
+			return Err(RuntimeError::UndefinedFunction(function.name.clone()));
+		}
+
+		if self.frames.len() >= self.max_frames
+		{
+			return Err(RuntimeError::StackOverflow(self.max_frames));
+		}
+
+		let base = self.stack.len() - function.arity as usize;
+		self.stack.resize(base + function.slots as usize, Value::Void);
+
+		self.frames.push(CallFrame { function: index, ip: 0, base });
+
+		Ok(())
+	}
+
+	fn pop(&mut self) -> Value
+	{
+		self.stack.pop().expect("operand stack underflow")
+	}
+
+	fn pop_integer(&mut self) -> Result<i32, RuntimeError>
+	{
+		match self.pop()
+		{
+			Value::Integer(value) => Ok(value),
+			other => Err(RuntimeError::TypeMismatch { expected: VType::Integer, got: other.vtype() })
+		}
+	}
+
+	fn pop_boolean(&mut self) -> Result<bool, RuntimeError>
+	{
+		match self.pop()
+		{
+			Value::Boolean(value) => Ok(value),
+			other => Err(RuntimeError::TypeMismatch { expected: VType::Boolean, got: other.vtype() })
+		}
+	}
+
+	fn execute(&mut self, entry_depth: usize) -> Result<Value, RuntimeError>
+	{
+		let program = self.program;
+
+		let frame = self.frames.last().unwrap();
+
+		let mut code = &program.functions[frame.function as usize].code;
+		let mut ip = frame.ip;
+		let mut base = frame.base;
+
+		loop
+		{
+			let instruction = code[ip];
+			ip += 1;
+
+			match instruction
+			{
+				Instruction::PushInteger(value) => self.stack.push(Value::Integer(value)),
+				Instruction::PushBoolean(value) => self.stack.push(Value::Boolean(value)),
+				Instruction::PushVoid => self.stack.push(Value::Void),
+
+				Instruction::Pop =>
+				{
+					self.pop();
+				}
+
+				Instruction::Load(slot) =>
+				{
+					let value = self.stack[base + slot as usize];
+					self.stack.push(value);
+				}
+
+				Instruction::Store(slot) =>
+				{
+					let value = self.pop();
+					self.stack[base + slot as usize] = value;
+				}
+
+				Instruction::Add | Instruction::Subtract | Instruction::Multiply | Instruction::Divide =>
+				{
+					let rhs = self.pop_integer()?;
+					let lhs = self.pop_integer()?;
+
+					let value = match instruction
+					{
+						Instruction::Add => lhs.wrapping_add(rhs),
+						Instruction::Subtract => lhs.wrapping_sub(rhs),
+						Instruction::Multiply => lhs.wrapping_mul(rhs),
+						_ =>
+						{
+							if rhs == 0
+							{
+								return Err(RuntimeError::DivisionByZero);
+							}
+
+							lhs.wrapping_div(rhs)
+						}
+					};
+
+					self.stack.push(Value::Integer(value));
+				}
+
+				Instruction::Equal | Instruction::NotEqual =>
+				{
+					let rhs = self.pop();
+					let lhs = self.pop();
+
+					let equal = lhs == rhs;
+					self.stack.push(Value::Boolean(if instruction == Instruction::Equal { equal } else { !equal }));
+				}
+
+				Instruction::Greater | Instruction::GreaterOrEqual | Instruction::Less | Instruction::LessOrEqual =>
+				{
+					let rhs = self.pop_integer()?;
+					let lhs = self.pop_integer()?;
+
+					let value = match instruction
+					{
+						Instruction::Greater => lhs > rhs,
+						Instruction::GreaterOrEqual => lhs >= rhs,
+						Instruction::Less => lhs < rhs,
+						_ => lhs <= rhs
+					};
+
+					self.stack.push(Value::Boolean(value));
+				}
+
+				Instruction::Jump(target) => ip = target as usize,
+
+				Instruction::JumpIfFalse(target) =>
+				{
+					if !self.pop_boolean()?
+					{
+						ip = target as usize;
+					}
+				}
+
+				Instruction::Call(index) =>
+				{
+					self.frames.last_mut().unwrap().ip = ip;
+					self.enter(index)?;
+
+					let frame = self.frames.last().unwrap();
+
+					code = &program.functions[frame.function as usize].code;
+					ip = 0;
+					base = frame.base;
+				}
+
+				Instruction::Return =>
+				{
+					let value = self.pop();
+					let frame = self.frames.pop().unwrap();
+
+					self.stack.truncate(frame.base);
+
+					if self.frames.len() == entry_depth
+					{
+						return Ok(value);
+					}
+
+					self.stack.push(value);
+
+					let frame = self.frames.last().unwrap();
+
+					code = &program.functions[frame.function as usize].code;
+					ip = frame.ip;
+					base = frame.base;
+				}
+
+				Instruction::Print =>
+				{
+					let value = self.pop();
+					writeln!(self.out, "{}", value).map_err(RuntimeError::Output)?;
+				}
+			}
+		}
+	}
+}
+
+pub fn run<W: Write>(program: &Program, out: W) -> Result<Value, RuntimeError>
+{
+	Vm::new(program, out).run()
+}
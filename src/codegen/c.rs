@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+// Signed overflow and division by zero are undefined in C, the helpers give
+// them the same wrapping and trapping behaviour as the interpreter. Functions
+// of the program are all prefixed with `faren_`, the helpers never are, and are
+// inline so that a program leaving some of them unused still builds warning free.
+const PRELUDE: &str = "\
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+static inline int32_t add_i32(int32_t a, int32_t b) { return (int32_t)((uint32_t)a + (uint32_t)b); }
+static inline int32_t sub_i32(int32_t a, int32_t b) { return (int32_t)((uint32_t)a - (uint32_t)b); }
+static inline int32_t mul_i32(int32_t a, int32_t b) { return (int32_t)((uint32_t)a * (uint32_t)b); }
+
+static inline int32_t div_i32(int32_t a, int32_t b)
+{
+	if (b == 0)
+	{
+		fputs(\"division by zero\\n\", stderr);
+		exit(1);
+	}
+
+	if (a == INT32_MIN && b == -1)
+	{
+		return INT32_MIN;
+	}
+
+	return a / b;
+}
+";
+
+pub fn c_type(vtype: &VType) -> &'static str
+{
+	match vtype
+	{
+		VType::Void => "void",
+		VType::Integer => "int32_t",
+		VType::Boolean => "bool"
+	}
+}
+
+pub fn function_name(name: &str) -> String
+{
+	format!("faren_{}", name)
+}
+
+pub fn variable_name(identifier: u16) -> String
+{
+	format!("v{}", identifier)
+}
+
+fn prototype(signature: &FunctionSignature) -> String
+{
+	let parameters: Vec<String> = signature.parameters()
+		.iter()
+		.map(|param| format!("{} {}", c_type(&param.vtype()), variable_name(param.id())))
+		.collect();
+
+	let parameters = if parameters.is_empty() { "void".to_string() } else { parameters.join(", ") };
+
+	format!("{} {}({})", c_type(&signature.return_type()), function_name(&signature.name()), parameters)
+}
+
+struct Emitter
+{
+	output: String,
+	indent: usize
+}
+
+impl Emitter
+{
+	fn line(&mut self, text: &str)
+	{
+		for _ in 0..self.indent
+		{
+			self.output.push('\t');
+		}
+
+		self.output.push_str(text);
+		self.output.push('\n');
+	}
+
+	fn emit_compound(&mut self, compound: &CompoundStatement)
+	{
+		self.line("{");
+		self.indent += 1;
+
+		for statement in compound.statements()
+		{
+			self.emit_statement(statement);
+		}
+
+		self.indent -= 1;
+		self.line("}");
+	}
+
+	fn emit_statement(&mut self, statement: &Statement)
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				self.emit_compound(statement.as_statement::<CompoundStatement>().unwrap());
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+
+				let text = format!(
+					"{} {} = {};",
+					c_type(&declare.vtype()),
+					variable_name(declare.identifier()),
+					expression(declare.expression())
+				);
+
+				self.line(&text);
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+
+				self.line(&format!("{} = {};", variable_name(assign.identifier()), expression(assign.expression())));
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+				let expr = print.expression();
+
+				match expr.vtype()
+				{
+					VType::Integer => self.line(&format!("printf(\"%\" PRId32 \"\\n\", {});", expression(expr))),
+					VType::Boolean => self.line(&format!("puts({} ? \"true\" : \"false\");", expression(expr))),
+
+					VType::Void =>
+					{
+						self.line(&format!("{};", expression(expr)));
+						self.line("puts(\"void\");");
+					}
+				}
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+
+				self.line(&format!("(void){};", expression(express.expression())));
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				match ret.expression()
+				{
+					Some(expr) => self.line(&format!("return {};", expression(expr))),
+					None => self.line("return;")
+				}
+			}
+
+			StatementType::If =>
+			{
+				let mut if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				self.line(&format!("if ({})", strip_parens(expression(if_statement.condition()))));
+				self.emit_compound(if_statement.then_body());
+
+				// Flatten `else if` chains instead of nesting them
+				while let Some(else_body) = if_statement.else_body()
+				{
+					match else_body.as_statement::<IfStatement>()
+					{
+						Some(else_if) =>
+						{
+							self.line(&format!("else if ({})", strip_parens(expression(else_if.condition()))));
+							self.emit_compound(else_if.then_body());
+
+							if_statement = else_if;
+						}
+
+						None =>
+						{
+							self.line("else");
+							self.emit_statement(else_body);
+
+							break;
+						}
+					}
+				}
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				self.line(&format!("while ({})", strip_parens(expression(while_statement.condition()))));
+				self.emit_compound(while_statement.body());
+			}
+
+			StatementType::Break => self.line("break;"),
+			StatementType::Continue => self.line("continue;"),
+
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => {}
+		}
+	}
+}
+
+fn strip_parens(text: String) -> String
+{
+	match text.strip_prefix('(').and_then(|inner| inner.strip_suffix(')'))
+	{
+		// Only strip when the outer pair belongs together, as in `(a) && (b)` it does not
+		Some(inner) if balanced(inner) => inner.to_string(),
+		_ => text
+	}
+}
+
+fn balanced(text: &str) -> bool
+{
+	let mut depth: i32 = 0;
+
+	for c in text.chars()
+	{
+		match c
+		{
+			'(' => depth += 1,
+			')' => depth -= 1,
+			_ => {}
+		}
+
+		if depth < 0
+		{
+			return false;
+		}
+	}
+
+	depth == 0
+}
+
+// Every compound expression is fully parenthesised, precedence never has to be reconsidered
+fn expression(expression: &Expression) -> String
+{
+	match expression.etype()
+	{
+		ExpressionType::Literal =>
+		{
+			let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+
+			match literal.vtype()
+			{
+				VType::Integer => literal.as_literal::<IntegerLiteral>().unwrap().value.to_string(),
+				VType::Boolean => literal.as_literal::<BooleanLiteral>().unwrap().value.to_string(),
+				VType::Void => "((void)0)".to_string()
+			}
+		}
+
+		ExpressionType::Variable =>
+		{
+			let variable = expression.as_expression::<VariableExpression>().unwrap();
+			variable_name(variable.identifier())
+		}
+
+		ExpressionType::FunctionCall =>
+		{
+			let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+
+			let arguments: Vec<String> = call.passed_arguments()
+				.iter()
+				.map(|argument| strip_parens(self::expression(argument)))
+				.collect();
+
+			format!("{}({})", function_name(&call.name()), arguments.join(", "))
+		}
+
+		ExpressionType::Arithmetic =>
+		{
+			let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+			let helper = match arith.op()
+			{
+				ArithmeticOperation::Add => "add_i32",
+				ArithmeticOperation::Subtract => "sub_i32",
+				ArithmeticOperation::Multiply => "mul_i32",
+				ArithmeticOperation::Divide => "div_i32"
+			};
+
+			format!(
+				"{}({}, {})",
+				helper,
+				strip_parens(self::expression(arith.left())),
+				strip_parens(self::expression(arith.right()))
+			)
+		}
+
+		ExpressionType::Comparison =>
+		{
+			let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+			let op = match cmp.op()
+			{
+				ComparisonOperation::IsEqual => "==",
+				ComparisonOperation::IsNotEqual => "!=",
+				ComparisonOperation::IsGreater => ">",
+				ComparisonOperation::IsGreaterOrEqual => ">=",
+				ComparisonOperation::IsLess => "<",
+				ComparisonOperation::IsLessOrEqual => "<="
+			};
+
+			format!("({} {} {})", self::expression(cmp.left()), op, self::expression(cmp.right()))
+		}
+
+		ExpressionType::Boolean =>
+		{
+			let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+			let op = match boolean.op()
+			{
+				BooleanOperation::And => "&&",
+				BooleanOperation::Or => "||"
+			};
+
+			format!("({} {} {})", self::expression(boolean.left()), op, self::expression(boolean.right()))
+		}
+	}
+}
+
+pub fn generate(root: &Root) -> String
+{
+	let mut emitter = Emitter { output: String::from(PRELUDE), indent: 0 };
+
+	let mut declared = HashSet::new();
+	let mut entry = None;
+
+	emitter.line("");
+
+	for statement in root.statements.iter()
+	{
+		let signature = match statement.stype()
+		{
+			StatementType::FunctionDefine => statement.as_statement::<FunctionDefineStatement>().unwrap().signature(),
+			StatementType::FunctionDeclare => statement.as_statement::<FunctionDeclareStatement>().unwrap().signature(),
+			_ => continue
+		};
+
+		if declared.insert(signature.name())
+		{
+			emitter.line(&format!("{};", prototype(signature)));
+		}
+	}
+
+	for statement in root.statements.iter()
+	{
+		let Some(func_define) = statement.as_statement::<FunctionDefineStatement>() else
+		{
+			continue;
+		};
+
+		if func_define.signature().name() == "main"
+		{
+			entry = Some(func_define.signature().return_type());
+		}
+
+		emitter.line("");
+		emitter.line(&prototype(func_define.signature()));
+		emitter.emit_compound(func_define.body());
+	}
+
+	if let Some(return_type) = entry
+	{
+		emitter.line("");
+		emitter.line("int main(void)");
+		emitter.line("{");
+
+		match return_type
+		{
+			VType::Integer => emitter.line(&format!("\treturn (int){}();", function_name("main"))),
+
+			_ =>
+			{
+				emitter.line(&format!("\t(void){}();", function_name("main")));
+				emitter.line("\treturn 0;");
+			}
+		}
+
+		emitter.line("}");
+	}
+
+	emitter.output
+}
@@ -0,0 +1,3 @@
+pub mod c;
+pub mod wat;
+pub mod x86_64;
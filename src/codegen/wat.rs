@@ -0,0 +1,464 @@
+use std::collections::BTreeSet;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+// Host functions are imported from this module, see `HOST_PRINT_*`
+pub const HOST_MODULE: &str = "env";
+
+pub const HOST_PRINT_INT: &str = "print_int";
+pub const HOST_PRINT_BOOL: &str = "print_bool";
+pub const HOST_PRINT_VOID: &str = "print_void";
+
+// Functions of the program get a prefix of their own, so that they can never clash
+// with `$faren_div` or the imported host prints. Exported names are left as written.
+pub fn function_name(name: &str) -> String
+{
+	format!("$fn_{}", name)
+}
+
+pub fn variable_name(identifier: u16) -> String
+{
+	format!("$v{}", identifier)
+}
+
+fn result_clause(vtype: &VType) -> &'static str
+{
+	match vtype
+	{
+		VType::Void => "",
+		VType::Integer | VType::Boolean => " (result i32)"
+	}
+}
+
+fn params_clause(signature: &FunctionSignature, named: bool) -> String
+{
+	signature.parameters()
+		.iter()
+		.map(|param| if named { format!(" (param {} i32)", variable_name(param.id())) } else { " (param i32)".to_string() })
+		.collect()
+}
+
+fn collect_locals(statements: &[Statement], locals: &mut Vec<u16>)
+{
+	for statement in statements
+	{
+		match statement.stype()
+		{
+			StatementType::Declare =>
+			{
+				locals.push(statement.as_statement::<DeclareStatement>().unwrap().identifier());
+			}
+
+			StatementType::Compound =>
+			{
+				collect_locals(statement.as_statement::<CompoundStatement>().unwrap().statements(), locals);
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				collect_locals(if_statement.then_body().statements(), locals);
+
+				if let Some(else_body) = if_statement.else_body()
+				{
+					collect_locals(std::slice::from_ref(else_body), locals);
+				}
+			}
+
+			StatementType::While =>
+			{
+				collect_locals(statement.as_statement::<WhileStatement>().unwrap().body().statements(), locals);
+			}
+
+			_ => {}
+		}
+	}
+}
+
+struct Emitter
+{
+	output: String,
+	indent: usize,
+
+	// Label numbers of the enclosing loops, innermost last
+	loops: Vec<usize>,
+	next_label: usize,
+
+	host_prints: BTreeSet<&'static str>
+}
+
+impl Emitter
+{
+	fn line(&mut self, text: &str)
+	{
+		for _ in 0..self.indent
+		{
+			self.output.push('\t');
+		}
+
+		self.output.push_str(text);
+		self.output.push('\n');
+	}
+
+	fn emit_statements(&mut self, statements: &[Statement])
+	{
+		for statement in statements
+		{
+			self.emit_statement(statement);
+		}
+	}
+
+	fn emit_statement(&mut self, statement: &Statement)
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				self.emit_statements(statement.as_statement::<CompoundStatement>().unwrap().statements());
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+
+				self.emit_expression(declare.expression());
+				self.line(&format!("local.set {}", variable_name(declare.identifier())));
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+
+				self.emit_expression(assign.expression());
+				self.line(&format!("local.set {}", variable_name(assign.identifier())));
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+
+				self.emit_expression(print.expression());
+
+				let host_print = match print.expression().vtype()
+				{
+					VType::Integer => HOST_PRINT_INT,
+					VType::Boolean => HOST_PRINT_BOOL,
+					VType::Void => HOST_PRINT_VOID
+				};
+
+				self.host_prints.insert(host_print);
+				self.line(&format!("call ${}", host_print));
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+
+				self.emit_expression(express.expression());
+
+				if express.expression().vtype() != VType::Void
+				{
+					self.line("drop");
+				}
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				if let Some(expr) = ret.expression()
+				{
+					self.emit_expression(expr);
+				}
+
+				self.line("return");
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				self.emit_expression(if_statement.condition());
+
+				self.line("if");
+				self.indent += 1;
+				self.emit_statements(if_statement.then_body().statements());
+				self.indent -= 1;
+
+				if let Some(else_body) = if_statement.else_body()
+				{
+					self.line("else");
+					self.indent += 1;
+					self.emit_statement(else_body);
+					self.indent -= 1;
+				}
+
+				self.line("end");
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				let label = self.next_label;
+				self.next_label += 1;
+
+				self.line(&format!("block $break{}", label));
+				self.indent += 1;
+				self.line(&format!("loop $continue{}", label));
+				self.indent += 1;
+
+				self.emit_expression(while_statement.condition());
+				self.line("i32.eqz");
+				self.line(&format!("br_if $break{}", label));
+
+				self.loops.push(label);
+				self.emit_statements(while_statement.body().statements());
+				self.loops.pop();
+
+				self.line(&format!("br $continue{}", label));
+
+				self.indent -= 1;
+				self.line("end");
+				self.indent -= 1;
+				self.line("end");
+			}
+
+			StatementType::Break =>
+			{
+				let label = *self.loops.last().expect("`break` outside of a loop");
+				self.line(&format!("br $break{}", label));
+			}
+
+			StatementType::Continue =>
+			{
+				let label = *self.loops.last().expect("`continue` outside of a loop");
+				self.line(&format!("br $continue{}", label));
+			}
+
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => {}
+		}
+	}
+
+	fn emit_expression(&mut self, expression: &Expression)
+	{
+		match expression.etype()
+		{
+			ExpressionType::Literal =>
+			{
+				let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+
+				match literal.vtype()
+				{
+					VType::Integer => self.line(&format!("i32.const {}", literal.as_literal::<IntegerLiteral>().unwrap().value)),
+					VType::Boolean => self.line(&format!("i32.const {}", literal.as_literal::<BooleanLiteral>().unwrap().value as i32)),
+					VType::Void => {}
+				}
+			}
+
+			ExpressionType::Variable =>
+			{
+				let variable = expression.as_expression::<VariableExpression>().unwrap();
+				self.line(&format!("local.get {}", variable_name(variable.identifier())));
+			}
+
+			ExpressionType::FunctionCall =>
+			{
+				let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+
+				for argument in call.passed_arguments().iter()
+				{
+					self.emit_expression(argument);
+				}
+
+				self.line(&format!("call {}", function_name(&call.name())));
+			}
+
+			ExpressionType::Arithmetic =>
+			{
+				let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+				self.emit_expression(arith.left());
+				self.emit_expression(arith.right());
+
+				self.line(match arith.op()
+				{
+					ArithmeticOperation::Add => "i32.add",
+					ArithmeticOperation::Subtract => "i32.sub",
+					ArithmeticOperation::Multiply => "i32.mul",
+					ArithmeticOperation::Divide => "call $faren_div"
+				});
+			}
+
+			ExpressionType::Comparison =>
+			{
+				let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+				self.emit_expression(cmp.left());
+				self.emit_expression(cmp.right());
+
+				self.line(match cmp.op()
+				{
+					ComparisonOperation::IsEqual => "i32.eq",
+					ComparisonOperation::IsNotEqual => "i32.ne",
+					ComparisonOperation::IsGreater => "i32.gt_s",
+					ComparisonOperation::IsGreaterOrEqual => "i32.ge_s",
+					ComparisonOperation::IsLess => "i32.lt_s",
+					ComparisonOperation::IsLessOrEqual => "i32.le_s"
+				});
+			}
+
+			ExpressionType::Boolean =>
+			{
+				let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+				self.emit_expression(boolean.left());
+
+				self.line("if (result i32)");
+				self.indent += 1;
+
+				match boolean.op()
+				{
+					BooleanOperation::And =>
+					{
+						self.emit_expression(boolean.right());
+						self.indent -= 1;
+						self.line("else");
+						self.indent += 1;
+						self.line("i32.const 0");
+					}
+
+					BooleanOperation::Or =>
+					{
+						self.line("i32.const 1");
+						self.indent -= 1;
+						self.line("else");
+						self.indent += 1;
+						self.emit_expression(boolean.right());
+					}
+				}
+
+				self.indent -= 1;
+				self.line("end");
+			}
+		}
+	}
+}
+
+pub fn generate(root: &Root) -> String
+{
+	let mut emitter = Emitter
+	{
+		output: String::new(),
+		indent: 1,
+		loops: Vec::new(),
+		next_label: 0,
+		host_prints: BTreeSet::new()
+	};
+
+	let mut defined = BTreeSet::new();
+
+	for statement in root.statements.iter()
+	{
+		let Some(func_define) = statement.as_statement::<FunctionDefineStatement>() else
+		{
+			continue;
+		};
+
+		let signature = func_define.signature();
+		defined.insert(signature.name());
+
+		emitter.line(&format!(
+			"(func {} (export \"{}\"){}{}",
+			function_name(&signature.name()),
+			signature.name(),
+			params_clause(signature, true),
+			result_clause(&signature.return_type())
+		));
+
+		emitter.indent += 1;
+
+		let mut locals = Vec::new();
+		collect_locals(func_define.body().statements(), &mut locals);
+
+		for local in locals
+		{
+			emitter.line(&format!("(local {} i32)", variable_name(local)));
+		}
+
+		emitter.emit_statements(func_define.body().statements());
+
+		// The parser guarantees a `return` on every path, the validator still needs the stack typed
+		if signature.return_type() != VType::Void
+		{
+			emitter.line("unreachable");
+		}
+
+		emitter.indent -= 1;
+		emitter.line(")");
+	}
+
+	let mut module = String::from("(module\n");
+
+	for host_print in emitter.host_prints.iter()
+	{
+		let param = if *host_print == HOST_PRINT_VOID { "" } else { " (param i32)" };
+
+		module.push_str(&format!("\t(import \"{}\" \"{}\" (func ${}{}))\n", HOST_MODULE, host_print, host_print, param));
+	}
+
+	// Functions that are only declared are expected from the host
+	for statement in root.statements.iter()
+	{
+		let Some(func_declare) = statement.as_statement::<FunctionDeclareStatement>() else
+		{
+			continue;
+		};
+
+		let signature = func_declare.signature();
+
+		if defined.insert(signature.name())
+		{
+			module.push_str(&format!(
+				"\t(import \"{}\" \"{}\" (func {}{}{}))\n",
+				HOST_MODULE,
+				signature.name(),
+				function_name(&signature.name()),
+				params_clause(signature, false),
+				result_clause(&signature.return_type())
+			));
+		}
+	}
+
+	// `i32.div_s` traps on `i32::MIN / -1`, the interpreter wraps instead
+	module.push_str("\t(func $faren_div (param $a i32) (param $b i32) (result i32)\n");
+	module.push_str("\t\tlocal.get $b\n");
+	module.push_str("\t\ti32.const -1\n");
+	module.push_str("\t\ti32.eq\n");
+	module.push_str("\t\tif (result i32)\n");
+	module.push_str("\t\t\ti32.const 0\n");
+	module.push_str("\t\t\tlocal.get $a\n");
+	module.push_str("\t\t\ti32.sub\n");
+	module.push_str("\t\telse\n");
+	module.push_str("\t\t\tlocal.get $a\n");
+	module.push_str("\t\t\tlocal.get $b\n");
+	module.push_str("\t\t\ti32.div_s\n");
+	module.push_str("\t\tend\n");
+	module.push_str("\t)\n");
+
+	module.push_str(&emitter.output);
+	module.push_str(")\n");
+
+	module
+}
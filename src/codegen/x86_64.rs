@@ -0,0 +1,593 @@
+use std::collections::HashMap;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+// System V integer argument registers, 32-bit views since every value is an `i32`
+pub const ARGUMENT_REGISTERS: [&str; 6] = ["%edi", "%esi", "%edx", "%ecx", "%r8d", "%r9d"];
+
+// `idivl` faults on division by zero and on `i32::MIN / -1`, the helper gives
+// them the same trapping and wrapping behaviour as the interpreter
+const RUNTIME: &str = "\
+	.text
+.Lfaren_div:
+	testl %esi, %esi
+	je .Lfaren_div_zero
+	cmpl $-1, %esi
+	je .Lfaren_div_negate
+	movl %edi, %eax
+	cltd
+	idivl %esi
+	ret
+.Lfaren_div_negate:
+	movl %edi, %eax
+	negl %eax
+	ret
+.Lfaren_div_zero:
+	subq $8, %rsp
+	leaq .Lfaren_division_by_zero(%rip), %rdi
+	movq stderr@GOTPCREL(%rip), %rax
+	movq (%rax), %rsi
+	call fputs@PLT
+	movl $1, %edi
+	call exit@PLT
+";
+
+const RODATA: &str = "\
+	.section .rodata
+.Lfaren_fmt_int:
+	.string \"%d\\n\"
+.Lfaren_fmt_bool:
+	.string \"%s\\n\"
+.Lfaren_fmt_void:
+	.string \"void\\n\"
+.Lfaren_true:
+	.string \"true\"
+.Lfaren_false:
+	.string \"false\"
+.Lfaren_division_by_zero:
+	.string \"division by zero\\n\"
+	.section .note.GNU-stack,\"\",@progbits
+";
+
+pub fn function_name(name: &str) -> String
+{
+	format!("faren_{}", name)
+}
+
+fn collect_locals(statements: &[Statement], locals: &mut Vec<u16>)
+{
+	for statement in statements
+	{
+		match statement.stype()
+		{
+			StatementType::Declare =>
+			{
+				locals.push(statement.as_statement::<DeclareStatement>().unwrap().identifier());
+			}
+
+			StatementType::Compound =>
+			{
+				collect_locals(statement.as_statement::<CompoundStatement>().unwrap().statements(), locals);
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				collect_locals(if_statement.then_body().statements(), locals);
+
+				if let Some(else_body) = if_statement.else_body()
+				{
+					collect_locals(std::slice::from_ref(else_body), locals);
+				}
+			}
+
+			StatementType::While =>
+			{
+				collect_locals(statement.as_statement::<WhileStatement>().unwrap().body().statements(), locals);
+			}
+
+			_ => {}
+		}
+	}
+}
+
+struct LoopLabels
+{
+	start: String,
+	end: String
+}
+
+struct Emitter
+{
+	output: String,
+	next_label: usize,
+
+	// Per function state, every variable lives in an 8 byte slot below `%rbp`
+	offsets: HashMap<u16, i32>,
+	epilogue: String,
+	loops: Vec<LoopLabels>,
+
+	// Quadwords pushed below the aligned frame, `%rsp` is 16 byte aligned when even
+	depth: usize
+}
+
+impl Emitter
+{
+	fn line(&mut self, text: &str)
+	{
+		self.output.push('\t');
+		self.output.push_str(text);
+		self.output.push('\n');
+	}
+
+	fn label(&mut self, label: &str)
+	{
+		self.output.push_str(label);
+		self.output.push_str(":\n");
+	}
+
+	fn new_label(&mut self, kind: &str) -> String
+	{
+		let label = format!(".L{}{}", kind, self.next_label);
+		self.next_label += 1;
+
+		label
+	}
+
+	fn push(&mut self)
+	{
+		self.line("pushq %rax");
+		self.depth += 1;
+	}
+
+	fn pop(&mut self, register: &str)
+	{
+		self.line(&format!("popq {}", register));
+		self.depth -= 1;
+	}
+
+	fn slot(&self, identifier: u16) -> String
+	{
+		format!("{}(%rbp)", self.offsets[&identifier])
+	}
+
+	// Calls outside of `invoke` take no stack arguments, only alignment matters
+	fn call(&mut self, symbol: &str)
+	{
+		if self.depth % 2 == 1
+		{
+			self.line("subq $8, %rsp");
+			self.line(&format!("call {}", symbol));
+			self.line("addq $8, %rsp");
+		}
+		else
+		{
+			self.line(&format!("call {}", symbol));
+		}
+	}
+
+	fn emit_statements(&mut self, statements: &[Statement])
+	{
+		for statement in statements
+		{
+			self.emit_statement(statement);
+		}
+	}
+
+	fn emit_statement(&mut self, statement: &Statement)
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				self.emit_statements(statement.as_statement::<CompoundStatement>().unwrap().statements());
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+
+				self.emit_expression(declare.expression());
+
+				let slot = self.slot(declare.identifier());
+				self.line(&format!("movl %eax, {}", slot));
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+
+				self.emit_expression(assign.expression());
+
+				let slot = self.slot(assign.identifier());
+				self.line(&format!("movl %eax, {}", slot));
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+
+				self.emit_expression(print.expression());
+
+				match print.expression().vtype()
+				{
+					VType::Integer =>
+					{
+						self.line("movl %eax, %esi");
+						self.line("leaq .Lfaren_fmt_int(%rip), %rdi");
+					}
+
+					VType::Boolean =>
+					{
+						self.line("leaq .Lfaren_true(%rip), %rsi");
+						self.line("leaq .Lfaren_false(%rip), %rdx");
+						self.line("testl %eax, %eax");
+						self.line("cmove %rdx, %rsi");
+						self.line("leaq .Lfaren_fmt_bool(%rip), %rdi");
+					}
+
+					VType::Void =>
+					{
+						self.line("leaq .Lfaren_fmt_void(%rip), %rdi");
+					}
+				}
+
+				// Variadic calls pass the number of vector registers used in `%al`
+				self.line("xorl %eax, %eax");
+				self.call("printf@PLT");
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+				self.emit_expression(express.expression());
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				if let Some(expr) = ret.expression()
+				{
+					self.emit_expression(expr);
+				}
+
+				let epilogue = self.epilogue.clone();
+				self.line(&format!("jmp {}", epilogue));
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				let else_label = self.new_label("else");
+				let end_label = self.new_label("endif");
+
+				self.emit_expression(if_statement.condition());
+				self.line("testl %eax, %eax");
+				self.line(&format!("je {}", else_label));
+
+				self.emit_statements(if_statement.then_body().statements());
+
+				match if_statement.else_body()
+				{
+					Some(else_body) =>
+					{
+						self.line(&format!("jmp {}", end_label));
+						self.label(&else_label);
+						self.emit_statement(else_body);
+						self.label(&end_label);
+					}
+
+					None => self.label(&else_label)
+				}
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				let labels = LoopLabels
+				{
+					start: self.new_label("while"),
+					end: self.new_label("endwhile")
+				};
+
+				self.label(&labels.start);
+
+				self.emit_expression(while_statement.condition());
+				self.line("testl %eax, %eax");
+				self.line(&format!("je {}", labels.end));
+
+				self.loops.push(labels);
+				self.emit_statements(while_statement.body().statements());
+				let labels = self.loops.pop().unwrap();
+
+				self.line(&format!("jmp {}", labels.start));
+				self.label(&labels.end);
+			}
+
+			StatementType::Break =>
+			{
+				let end = self.loops.last().expect("`break` outside of a loop").end.clone();
+				self.line(&format!("jmp {}", end));
+			}
+
+			StatementType::Continue =>
+			{
+				let start = self.loops.last().expect("`continue` outside of a loop").start.clone();
+				self.line(&format!("jmp {}", start));
+			}
+
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => {}
+		}
+	}
+
+	// Leaves the value of the expression in `%eax`, booleans as 0 or 1
+	fn emit_expression(&mut self, expression: &Expression)
+	{
+		match expression.etype()
+		{
+			ExpressionType::Literal =>
+			{
+				let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+
+				match literal.vtype()
+				{
+					VType::Integer => self.line(&format!("movl ${}, %eax", literal.as_literal::<IntegerLiteral>().unwrap().value)),
+					VType::Boolean => self.line(&format!("movl ${}, %eax", literal.as_literal::<BooleanLiteral>().unwrap().value as i32)),
+					VType::Void => {}
+				}
+			}
+
+			ExpressionType::Variable =>
+			{
+				let variable = expression.as_expression::<VariableExpression>().unwrap();
+
+				let slot = self.slot(variable.identifier());
+				self.line(&format!("movl {}, %eax", slot));
+			}
+
+			ExpressionType::FunctionCall =>
+			{
+				let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+				let arguments = call.passed_arguments();
+
+				// Arguments are evaluated left to right onto the stack, argument `i` ends up
+				// at `8 * (count - 1 - i)(%rsp)` and is moved into place from there
+				for argument in arguments.iter()
+				{
+					self.emit_expression(argument);
+					self.push();
+				}
+
+				let count = arguments.len();
+				let on_stack = count.saturating_sub(ARGUMENT_REGISTERS.len());
+				let padding = (self.depth + on_stack) % 2;
+				let reserved = on_stack + padding;
+
+				if reserved > 0
+				{
+					self.line(&format!("subq ${}, %rsp", 8 * reserved));
+				}
+
+				for index in ARGUMENT_REGISTERS.len()..count
+				{
+					self.line(&format!("movq {}(%rsp), %rax", 8 * (count - 1 - index + reserved)));
+					self.line(&format!("movq %rax, {}(%rsp)", 8 * (index - ARGUMENT_REGISTERS.len())));
+				}
+
+				for (index, register) in ARGUMENT_REGISTERS.iter().enumerate().take(count)
+				{
+					self.line(&format!("movl {}(%rsp), {}", 8 * (count - 1 - index + reserved), register));
+				}
+
+				self.line(&format!("call {}", function_name(&call.name())));
+
+				if count + reserved > 0
+				{
+					self.line(&format!("addq ${}, %rsp", 8 * (count + reserved)));
+				}
+
+				self.depth -= count;
+			}
+
+			ExpressionType::Arithmetic =>
+			{
+				let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+				self.emit_expression(arith.left());
+				self.push();
+				self.emit_expression(arith.right());
+				self.line("movl %eax, %ecx");
+				self.pop("%rax");
+
+				match arith.op()
+				{
+					ArithmeticOperation::Add => self.line("addl %ecx, %eax"),
+					ArithmeticOperation::Subtract => self.line("subl %ecx, %eax"),
+					ArithmeticOperation::Multiply => self.line("imull %ecx, %eax"),
+
+					ArithmeticOperation::Divide =>
+					{
+						self.line("movl %eax, %edi");
+						self.line("movl %ecx, %esi");
+						self.call(".Lfaren_div");
+					}
+				}
+			}
+
+			ExpressionType::Comparison =>
+			{
+				let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+				self.emit_expression(cmp.left());
+				self.push();
+				self.emit_expression(cmp.right());
+				self.line("movl %eax, %ecx");
+				self.pop("%rax");
+
+				self.line("cmpl %ecx, %eax");
+				self.line(match cmp.op()
+				{
+					ComparisonOperation::IsEqual => "sete %al",
+					ComparisonOperation::IsNotEqual => "setne %al",
+					ComparisonOperation::IsGreater => "setg %al",
+					ComparisonOperation::IsGreaterOrEqual => "setge %al",
+					ComparisonOperation::IsLess => "setl %al",
+					ComparisonOperation::IsLessOrEqual => "setle %al"
+				});
+				self.line("movzbl %al, %eax");
+			}
+
+			ExpressionType::Boolean =>
+			{
+				let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+				let short_label = self.new_label("short");
+				let end_label = self.new_label("endbool");
+
+				self.emit_expression(boolean.left());
+				self.line("testl %eax, %eax");
+
+				// and: a false left skips the right, or: a true left does
+				let (jump, short_value) = match boolean.op()
+				{
+					BooleanOperation::And => ("je", 0),
+					BooleanOperation::Or => ("jne", 1)
+				};
+
+				self.line(&format!("{} {}", jump, short_label));
+				self.emit_expression(boolean.right());
+				self.line(&format!("jmp {}", end_label));
+				self.label(&short_label);
+				self.line(&format!("movl ${}, %eax", short_value));
+				self.label(&end_label);
+			}
+		}
+	}
+
+	fn emit_function(&mut self, func_define: &FunctionDefineStatement)
+	{
+		let signature = func_define.signature();
+		let name = function_name(&signature.name());
+
+		let mut variables: Vec<u16> = signature.parameters().iter().map(|param| param.id()).collect();
+		collect_locals(func_define.body().statements(), &mut variables);
+
+		self.offsets = variables
+			.iter()
+			.enumerate()
+			.map(|(index, identifier)| (*identifier, -8 * (index as i32 + 1)))
+			.collect();
+
+		self.epilogue = self.new_label("return");
+		self.depth = 0;
+
+		let frame = (8 * variables.len()).next_multiple_of(16);
+
+		self.output.push('\n');
+		self.line(&format!(".globl {}", name));
+		self.line(&format!(".type {}, @function", name));
+		self.label(&name);
+		self.line("pushq %rbp");
+		self.line("movq %rsp, %rbp");
+
+		if frame > 0
+		{
+			self.line(&format!("subq ${}, %rsp", frame));
+		}
+
+		for (index, param) in signature.parameters().iter().enumerate()
+		{
+			let slot = self.slot(param.id());
+
+			match ARGUMENT_REGISTERS.get(index)
+			{
+				Some(register) => self.line(&format!("movl {}, {}", register, slot)),
+
+				// Past the return address and the saved `%rbp`
+				None =>
+				{
+					self.line(&format!("movl {}(%rbp), %eax", 16 + 8 * (index - ARGUMENT_REGISTERS.len())));
+					self.line(&format!("movl %eax, {}", slot));
+				}
+			}
+		}
+
+		self.emit_statements(func_define.body().statements());
+
+		let epilogue = self.epilogue.clone();
+
+		self.label(&epilogue);
+		self.line("leave");
+		self.line("ret");
+		self.line(&format!(".size {}, .-{}", name, name));
+	}
+}
+
+pub fn generate(root: &Root) -> String
+{
+	let mut emitter = Emitter
+	{
+		output: String::from(RUNTIME),
+		next_label: 0,
+		offsets: HashMap::new(),
+		epilogue: String::new(),
+		loops: Vec::new(),
+		depth: 0
+	};
+
+	let mut entry = None;
+
+	for statement in root.statements.iter()
+	{
+		let Some(func_define) = statement.as_statement::<FunctionDefineStatement>() else
+		{
+			continue;
+		};
+
+		if func_define.signature().name() == "main"
+		{
+			entry = Some(func_define.signature().return_type());
+		}
+
+		emitter.emit_function(func_define);
+	}
+
+	// The C entry point calls into the Faren one, just like the C backend
+	if let Some(return_type) = entry
+	{
+		emitter.output.push('\n');
+		emitter.line(".globl main");
+		emitter.line(".type main, @function");
+		emitter.label("main");
+		emitter.line("subq $8, %rsp");
+		emitter.line(&format!("call {}", function_name("main")));
+
+		if return_type != VType::Integer
+		{
+			emitter.line("xorl %eax, %eax");
+		}
+
+		emitter.line("addq $8, %rsp");
+		emitter.line("ret");
+		emitter.line(".size main, .-main");
+	}
+
+	emitter.output.push('\n');
+	emitter.output.push_str(RODATA);
+
+	emitter.output
+}
@@ -0,0 +1,584 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::data::{
+	ops::*,
+	syms::*
+};
+
+use crate::parser::{
+	precedence_of,
+	Operator,
+	lexer::*,
+	token::*
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind
+{
+	Root,
+
+	FunctionDeclare,
+	FunctionDefine,
+	Parameters,
+	Parameter,
+	Block,
+
+	Let,
+	Set,
+	Print,
+	Express,
+	Return,
+	If,
+	Else,
+	While,
+	Break,
+	Continue,
+
+	Binary,
+	Parenthesised,
+	Literal,
+	Name,
+	Call,
+	Arguments,
+
+	// Tokens that do not fit the grammar where they appear
+	Error
+}
+
+#[derive(Clone)]
+pub enum Element
+{
+	Node(Node),
+	Token(LosslessToken)
+}
+
+impl fmt::Display for Element
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Element::Node(node) => node.fmt(f),
+			Element::Token(token) => token.fmt(f)
+		}
+	}
+}
+
+// Unlike the AST this keeps every token, expected tokens that are missing are simply
+// left out and stray ones end up in `Error` nodes, so any input has a tree
+#[derive(Clone)]
+pub struct Node
+{
+	pub kind: NodeKind,
+	pub children: Vec<Element>
+}
+
+impl Node
+{
+	fn new(kind: NodeKind) -> Self
+	{
+		Self { kind, children: Vec::new() }
+	}
+
+	pub fn nodes(&self) -> impl Iterator<Item = &Node>
+	{
+		self.children.iter().filter_map(|element| match element
+		{
+			Element::Node(node) => Some(node),
+			Element::Token(_) => None
+		})
+	}
+
+	// All tokens below this node in source order
+	pub fn tokens(&self) -> Vec<&LosslessToken>
+	{
+		let mut tokens = Vec::new();
+
+		for element in self.children.iter()
+		{
+			match element
+			{
+				Element::Node(node) => tokens.extend(node.tokens()),
+				Element::Token(token) => tokens.push(token)
+			}
+		}
+
+		tokens
+	}
+
+	pub fn has_errors(&self) -> bool
+	{
+		self.kind == NodeKind::Error || self.nodes().any(|node| node.has_errors())
+	}
+}
+
+impl fmt::Display for Node
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		for element in self.children.iter()
+		{
+			element.fmt(f)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Clone)]
+pub struct SyntaxTree
+{
+	pub root: Node,
+
+	// Trivia after the last token, or all of it for a source without tokens
+	pub trailing: Vec<Trivia>
+}
+
+// Prints the source the tree was parsed from, byte for byte
+impl fmt::Display for SyntaxTree
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		self.root.fmt(f)?;
+
+		for trivia in self.trailing.iter()
+		{
+			f.write_str(&trivia.text)?;
+		}
+
+		Ok(())
+	}
+}
+
+pub(crate) const STATEMENT_KEYWORDS: [&str; 11] = [
+	"function", "let", "set", "print", "express", "return", "if", "else", "while", "break", "continue"
+];
+
+struct CstParser
+{
+	tokens: VecDeque<LosslessToken>
+}
+
+impl CstParser
+{
+	fn peek(&self) -> Option<&Token>
+	{
+		self.tokens.front().map(|lossless| &lossless.token)
+	}
+
+	fn at_keyword(&self, keyword: &str) -> bool
+	{
+		self.peek()
+			.and_then(|token| token.as_token::<IdentifierToken>())
+			.is_some_and(|ident_token| ident_token.name() == keyword)
+	}
+
+	fn at_statement_keyword(&self) -> bool
+	{
+		STATEMENT_KEYWORDS.iter().any(|keyword| self.at_keyword(keyword))
+	}
+
+	fn at_symbol(&self, sym: Symbol) -> bool
+	{
+		self.peek()
+			.and_then(|token| token.as_token::<SymbolToken>())
+			.is_some_and(|sym_token| sym_token.sym() == sym)
+	}
+
+	fn at_type(&self, token_type: &TokenType) -> bool
+	{
+		self.peek().is_some_and(|token| token.get_type() == *token_type)
+	}
+
+	fn bump(&mut self, node: &mut Node)
+	{
+		if let Some(token) = self.tokens.pop_front()
+		{
+			node.children.push(Element::Token(token));
+		}
+	}
+
+	fn eat_symbol(&mut self, node: &mut Node, sym: Symbol) -> bool
+	{
+		let found = self.at_symbol(sym);
+
+		if found
+		{
+			self.bump(node);
+		}
+
+		found
+	}
+
+	fn eat_type(&mut self, node: &mut Node, token_type: TokenType) -> bool
+	{
+		// Keywords lex as identifiers but are never names
+		let found = self.at_type(&token_type) && !(token_type == TokenType::Identifier && self.at_statement_keyword());
+
+		if found
+		{
+			self.bump(node);
+		}
+
+		found
+	}
+
+	fn root(&mut self) -> Node
+	{
+		let mut root = Node::new(NodeKind::Root);
+
+		while !self.tokens.is_empty()
+		{
+			let statement = self.statement();
+			root.children.push(Element::Node(statement));
+		}
+
+		root
+	}
+
+	fn block(&mut self) -> Node
+	{
+		let mut block = Node::new(NodeKind::Block);
+		self.bump(&mut block);
+
+		while !self.tokens.is_empty() && !self.at_symbol(Symbol::RightBrace)
+		{
+			let statement = self.statement();
+			block.children.push(Element::Node(statement));
+		}
+
+		self.eat_symbol(&mut block, Symbol::RightBrace);
+
+		block
+	}
+
+	fn push_block(&mut self, node: &mut Node)
+	{
+		if self.at_symbol(Symbol::LeftBrace)
+		{
+			let block = self.block();
+			node.children.push(Element::Node(block));
+		}
+	}
+
+	fn push_expression(&mut self, node: &mut Node)
+	{
+		if let Some(expression) = self.expression(0)
+		{
+			node.children.push(Element::Node(expression));
+		}
+	}
+
+	// Always consumes at least one token
+	fn statement(&mut self) -> Node
+	{
+		if self.at_symbol(Symbol::LeftBrace)
+		{
+			return self.block();
+		}
+
+		let keyword = self.peek()
+			.and_then(|token| token.as_token::<IdentifierToken>())
+			.map(|ident_token| ident_token.name())
+			.unwrap_or_default();
+
+		match keyword.as_str()
+		{
+			"function" => self.function(),
+
+			"let" | "set" =>
+			{
+				let mut node = Node::new(if keyword == "let" { NodeKind::Let } else { NodeKind::Set });
+				self.bump(&mut node);
+				self.eat_type(&mut node, TokenType::Identifier);
+
+				if keyword == "let"
+				{
+					self.eat_type(&mut node, TokenType::Type);
+				}
+
+				self.eat_symbol(&mut node, Symbol::Equal);
+				self.push_expression(&mut node);
+				self.eat_symbol(&mut node, Symbol::Semicolon);
+
+				node
+			}
+
+			"print" | "express" | "return" =>
+			{
+				let mut node = Node::new(match keyword.as_str()
+				{
+					"print" => NodeKind::Print,
+					"express" => NodeKind::Express,
+					_ => NodeKind::Return
+				});
+
+				self.bump(&mut node);
+				self.push_expression(&mut node);
+				self.eat_symbol(&mut node, Symbol::Semicolon);
+
+				node
+			}
+
+			"if" => self.if_statement(),
+
+			"while" =>
+			{
+				let mut node = Node::new(NodeKind::While);
+				self.bump(&mut node);
+				self.push_expression(&mut node);
+				self.push_block(&mut node);
+
+				node
+			}
+
+			"break" | "continue" =>
+			{
+				let mut node = Node::new(if keyword == "break" { NodeKind::Break } else { NodeKind::Continue });
+				self.bump(&mut node);
+				self.eat_symbol(&mut node, Symbol::Semicolon);
+
+				node
+			}
+
+			_ => self.error()
+		}
+	}
+
+	// Skips to the end of the statement, or to where a new one begins
+	fn error(&mut self) -> Node
+	{
+		let mut node = Node::new(NodeKind::Error);
+		self.bump(&mut node);
+
+		while !self.tokens.is_empty()
+		{
+			if self.eat_symbol(&mut node, Symbol::Semicolon)
+			{
+				break;
+			}
+
+			if self.at_symbol(Symbol::LeftBrace) || self.at_symbol(Symbol::RightBrace) || self.at_statement_keyword()
+			{
+				break;
+			}
+
+			self.bump(&mut node);
+		}
+
+		node
+	}
+
+	fn function(&mut self) -> Node
+	{
+		let mut node = Node::new(NodeKind::FunctionDeclare);
+		self.bump(&mut node);
+		self.eat_type(&mut node, TokenType::Identifier);
+
+		if self.at_symbol(Symbol::LeftParen)
+		{
+			let mut parameters = Node::new(NodeKind::Parameters);
+			self.bump(&mut parameters);
+
+			loop
+			{
+				let mut parameter = Node::new(NodeKind::Parameter);
+				self.eat_type(&mut parameter, TokenType::Identifier);
+				self.eat_type(&mut parameter, TokenType::Type);
+
+				let is_empty = parameter.children.is_empty();
+
+				if !is_empty
+				{
+					parameters.children.push(Element::Node(parameter));
+				}
+
+				if is_empty || !self.eat_symbol(&mut parameters, Symbol::Comma)
+				{
+					break;
+				}
+			}
+
+			self.eat_symbol(&mut parameters, Symbol::RightParen);
+			node.children.push(Element::Node(parameters));
+		}
+
+		self.eat_type(&mut node, TokenType::Type);
+
+		if self.at_symbol(Symbol::LeftBrace)
+		{
+			node.kind = NodeKind::FunctionDefine;
+			self.push_block(&mut node);
+		}
+		else
+		{
+			self.eat_symbol(&mut node, Symbol::Semicolon);
+		}
+
+		node
+	}
+
+	fn if_statement(&mut self) -> Node
+	{
+		let mut node = Node::new(NodeKind::If);
+		self.bump(&mut node);
+		self.push_expression(&mut node);
+		self.push_block(&mut node);
+
+		if self.at_keyword("else")
+		{
+			let mut else_node = Node::new(NodeKind::Else);
+			self.bump(&mut else_node);
+
+			if self.at_keyword("if")
+			{
+				let else_if = self.if_statement();
+				else_node.children.push(Element::Node(else_if));
+			}
+			else
+			{
+				self.push_block(&mut else_node);
+			}
+
+			node.children.push(Element::Node(else_node));
+		}
+
+		node
+	}
+
+	fn operator(&self) -> Option<Operator>
+	{
+		let token = self.peek()?;
+
+		if let Some(arith_token) = token.as_token::<ArithmeticToken>()
+		{
+			return Some(match arith_token.op()
+			{
+				ArithmeticOperation::Add      => Operator::Add,
+				ArithmeticOperation::Subtract => Operator::Sub,
+				ArithmeticOperation::Multiply => Operator::Mul,
+				ArithmeticOperation::Divide   => Operator::Div
+			});
+		}
+
+		if let Some(cmp_token) = token.as_token::<ComparisonToken>()
+		{
+			return Some(match cmp_token.op()
+			{
+				ComparisonOperation::IsEqual          => Operator::Eq,
+				ComparisonOperation::IsNotEqual       => Operator::Neq,
+				ComparisonOperation::IsGreater        => Operator::Gt,
+				ComparisonOperation::IsGreaterOrEqual => Operator::Gte,
+				ComparisonOperation::IsLess           => Operator::Lt,
+				ComparisonOperation::IsLessOrEqual    => Operator::Lte
+			});
+		}
+
+		token.as_token::<BooleanToken>().map(|bool_token| match bool_token.op()
+		{
+			BooleanOperation::And => Operator::And,
+			BooleanOperation::Or  => Operator::Or
+		})
+	}
+
+	// Precedence climbing, every operator is left associative
+	fn expression(&mut self, min_prec: u8) -> Option<Node>
+	{
+		let mut left = self.operand();
+
+		while let Some(op) = self.operator()
+		{
+			let (prec, _) = precedence_of(op);
+
+			if prec < min_prec
+			{
+				break;
+			}
+
+			let mut binary = Node::new(NodeKind::Binary);
+			binary.children.extend(left.take().map(Element::Node));
+			self.bump(&mut binary);
+
+			if let Some(right) = self.expression(prec + 1)
+			{
+				binary.children.push(Element::Node(right));
+			}
+
+			left = Some(binary);
+		}
+
+		left
+	}
+
+	fn operand(&mut self) -> Option<Node>
+	{
+		if self.at_type(&TokenType::IntegerLiteral) || self.at_type(&TokenType::BooleanLiteral)
+		{
+			let mut literal = Node::new(NodeKind::Literal);
+			self.bump(&mut literal);
+			return Some(literal);
+		}
+
+		if self.at_symbol(Symbol::LeftParen)
+		{
+			let mut paren = Node::new(NodeKind::Parenthesised);
+			self.bump(&mut paren);
+			self.push_expression(&mut paren);
+			self.eat_symbol(&mut paren, Symbol::RightParen);
+			return Some(paren);
+		}
+
+		if self.at_keyword("invoke")
+		{
+			let mut call = Node::new(NodeKind::Call);
+			self.bump(&mut call);
+			self.eat_type(&mut call, TokenType::Identifier);
+
+			if self.at_symbol(Symbol::LeftParen)
+			{
+				let mut arguments = Node::new(NodeKind::Arguments);
+				self.bump(&mut arguments);
+
+				if !self.at_symbol(Symbol::RightParen)
+				{
+					loop
+					{
+						self.push_expression(&mut arguments);
+
+						if !self.eat_symbol(&mut arguments, Symbol::Comma)
+						{
+							break;
+						}
+					}
+				}
+
+				self.eat_symbol(&mut arguments, Symbol::RightParen);
+				call.children.push(Element::Node(arguments));
+			}
+
+			return Some(call);
+		}
+
+		let mut name = Node::new(NodeKind::Name);
+
+		if self.eat_type(&mut name, TokenType::Identifier)
+		{
+			return Some(name);
+		}
+
+		None
+	}
+}
+
+// Parses any source into a tree that prints back to it exactly. Errors in the syntax
+// are not reported here, `parse_root` does that, only what the lexer found.
+pub fn parse(source: &str) -> (SyntaxTree, Vec<LexerError>)
+{
+	let (tokens, trailing, errors) = lex_lossless(source);
+
+	let mut parser = CstParser { tokens: tokens.into() };
+	let root = parser.root();
+
+	(SyntaxTree { root, trailing }, errors)
+}
@@ -0,0 +1,561 @@
+use std::collections::VecDeque;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	syms::*,
+	vtype::*
+};
+
+use crate::parser::{
+	precedence_of,
+	Assoc,
+	Operator,
+	lexer::*,
+	token::*
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent
+{
+	Tabs,
+	Spaces(usize)
+}
+
+#[derive(Debug, Clone)]
+pub struct FormatOptions
+{
+	pub indent: Indent
+}
+
+impl Default for FormatOptions
+{
+	fn default() -> Self
+	{
+		Self { indent: Indent::Tabs }
+	}
+}
+
+pub fn type_name(vtype: &VType) -> &'static str
+{
+	match vtype
+	{
+		VType::Void    => "void",
+		VType::Integer => "int",
+		VType::Boolean => "bool"
+	}
+}
+
+fn operator_text(op: Operator) -> &'static str
+{
+	match op
+	{
+		Operator::Add => "+",
+		Operator::Sub => "-",
+		Operator::Mul => "*",
+		Operator::Div => "/",
+		Operator::Eq  => "==",
+		Operator::Neq => "!=",
+		Operator::Gt  => ">",
+		Operator::Gte => ">=",
+		Operator::Lt  => "<",
+		Operator::Lte => "<=",
+		Operator::And => "and",
+		Operator::Or  => "or"
+	}
+}
+
+// The operator at the top of an expression as it is printed, a folded literal prints
+// the expression it replaced and a negative one has to be written as a subtraction
+fn operator_of(expression: &Expression) -> Option<Operator>
+{
+	match expression.kind()
+	{
+		ExpressionKind::Literal(literal) => match literal.folded_from()
+		{
+			Some(folded_from) => operator_of(folded_from),
+
+			None => literal.literal()
+				.as_literal::<IntegerLiteral>()
+				.filter(|integer| integer.value < 0)
+				.map(|_| Operator::Sub)
+		},
+
+		ExpressionKind::Arithmetic(arith_expr) => Some(match arith_expr.op()
+		{
+			ArithmeticOperation::Add      => Operator::Add,
+			ArithmeticOperation::Subtract => Operator::Sub,
+			ArithmeticOperation::Multiply => Operator::Mul,
+			ArithmeticOperation::Divide   => Operator::Div
+		}),
+
+		ExpressionKind::Comparison(cmp_expr) => Some(match cmp_expr.op()
+		{
+			ComparisonOperation::IsEqual          => Operator::Eq,
+			ComparisonOperation::IsNotEqual       => Operator::Neq,
+			ComparisonOperation::IsGreater        => Operator::Gt,
+			ComparisonOperation::IsGreaterOrEqual => Operator::Gte,
+			ComparisonOperation::IsLess           => Operator::Lt,
+			ComparisonOperation::IsLessOrEqual    => Operator::Lte
+		}),
+
+		ExpressionKind::Boolean(bool_expr) => Some(match bool_expr.op()
+		{
+			BooleanOperation::And => Operator::And,
+			BooleanOperation::Or  => Operator::Or
+		}),
+
+		ExpressionKind::FunctionCall(_) | ExpressionKind::Variable(_) => None
+	}
+}
+
+// Names only survive in the tokens, nodes built without them get one made up from their id
+fn identifier_at(tokens: &VecDeque<Token>, index: usize) -> Option<String>
+{
+	tokens.get(index)?
+		.as_token::<IdentifierToken>()
+		.map(|ident_token| ident_token.name())
+}
+
+fn variable_name(tokens: &VecDeque<Token>, index: usize, id: u16) -> String
+{
+	identifier_at(tokens, index).unwrap_or_else(|| format!("v{}", id))
+}
+
+fn is_symbol(token: &Token, sym: Symbol) -> bool
+{
+	token.as_token::<SymbolToken>().is_some_and(|sym_token| sym_token.sym() == sym)
+}
+
+// The identifiers between the parentheses of a signature, the types are separate tokens
+fn parameter_names(tokens: &VecDeque<Token>, signature: &FunctionSignature) -> Vec<String>
+{
+	let names: Vec<String> = tokens
+		.iter()
+		.skip_while(|token| !is_symbol(token, Symbol::LeftParen))
+		.take_while(|token| !is_symbol(token, Symbol::RightParen))
+		.filter_map(|token| token.as_token::<IdentifierToken>().map(|ident_token| ident_token.name()))
+		.collect();
+
+	let parameters = signature.parameters();
+
+	if names.len() == parameters.len()
+	{
+		return names;
+	}
+
+	parameters.iter().map(|param| format!("v{}", param.id())).collect()
+}
+
+// Parameter names are taken from the tokens of the function statement
+pub fn format_signature(tokens: &VecDeque<Token>, signature: &FunctionSignature) -> String
+{
+	let parameters: Vec<String> = parameter_names(tokens, signature)
+		.into_iter()
+		.zip(signature.parameters())
+		.map(|(name, param)| format!("{} {}", name, type_name(&param.vtype())))
+		.collect();
+
+	format!("function {}({}) {}", signature.name(), parameters.join(", "), type_name(&signature.return_type()))
+}
+
+pub fn format_expression(expression: &Expression) -> String
+{
+	match expression.kind()
+	{
+		ExpressionKind::Literal(literal) =>
+		{
+			if let Some(folded_from) = literal.folded_from()
+			{
+				return format_expression(folded_from);
+			}
+
+			if let Some(integer) = literal.literal().as_literal::<IntegerLiteral>()
+			{
+				// There are no negative literals in the language
+				return match integer.value
+				{
+					i32::MIN => format!("0 - {} - 1", i32::MAX),
+					value if value < 0 => format!("0 - {}", value.unsigned_abs()),
+					value => value.to_string()
+				};
+			}
+
+			let boolean = literal.literal().as_literal::<BooleanLiteral>().unwrap();
+			boolean.value.to_string()
+		}
+
+		ExpressionKind::FunctionCall(call) =>
+		{
+			let arguments: Vec<String> = call.passed_arguments().iter().map(format_expression).collect();
+			format!("invoke {}({})", call.name(), arguments.join(", "))
+		}
+
+		ExpressionKind::Variable(variable) => variable_name(&variable.unparse(), 0, variable.identifier()),
+
+		ExpressionKind::Arithmetic(arith_expr) => binary(expression, arith_expr.left(), arith_expr.right()),
+		ExpressionKind::Comparison(cmp_expr) => binary(expression, cmp_expr.left(), cmp_expr.right()),
+		ExpressionKind::Boolean(bool_expr) => binary(expression, bool_expr.left(), bool_expr.right())
+	}
+}
+
+fn binary(expression: &Expression, left: &Expression, right: &Expression) -> String
+{
+	let op = operator_of(expression).unwrap();
+
+	format!("{} {} {}", operand(op, left, false), operator_text(op), operand(op, right, true))
+}
+
+// Parenthesised only where the parser would otherwise group it differently
+fn operand(parent: Operator, expression: &Expression, is_right: bool) -> String
+{
+	let text = format_expression(expression);
+
+	let Some(op) = operator_of(expression) else
+	{
+		return text;
+	};
+
+	let (parent_prec, assoc) = precedence_of(parent);
+	let (prec, _) = precedence_of(op);
+
+	let grouped = match assoc
+	{
+		Assoc::Left => prec < parent_prec || (is_right && prec == parent_prec)
+	};
+
+	if grouped { format!("({})", text) } else { text }
+}
+
+struct Formatter<'a>
+{
+	options: &'a FormatOptions,
+	output: String,
+	depth: usize,
+
+	// The source the tree was parsed from and its comments not printed yet, in order
+	source: &'a str,
+	comments: VecDeque<Trivia>,
+
+	// Where the last statement or comment printed ends in the source
+	last_end: usize,
+	block_start: bool,
+
+	// Where the line comment ending the line printed last begins in the output
+	line_comment: Option<usize>
+}
+
+impl Formatter<'_>
+{
+	fn line(&mut self, text: &str)
+	{
+		self.line_comment = None;
+
+		for _ in 0..self.depth
+		{
+			match self.options.indent
+			{
+				Indent::Tabs => self.output.push('\t'),
+				Indent::Spaces(count) => self.output.push_str(&" ".repeat(count))
+			}
+		}
+
+		self.output.push_str(text);
+		self.output.push('\n');
+	}
+
+	// A single blank line is kept wherever the source had at least one, or where
+	// `forced`, but never at the start of a block
+	fn separate(&mut self, begin: usize, forced: bool)
+	{
+		let blank = self.source
+			.get(self.last_end..begin)
+			.is_some_and(|gap| gap.matches('\n').count() > 1);
+
+		if !self.block_start && (forced || blank)
+		{
+			self.output.push('\n');
+		}
+
+		self.block_start = false;
+	}
+
+	fn comment(&mut self, comment: Trivia, append: bool)
+	{
+		let text = comment.text.trim_end();
+
+		match (append, self.line_comment)
+		{
+			// Nothing may follow a line comment on its line, a block comment goes before it
+			(true, Some(at)) if comment.kind == TriviaKind::BlockComment =>
+			{
+				self.output.insert_str(at, &format!("{} ", text));
+				self.line_comment = Some(at + text.len() + 1);
+			}
+
+			(true, _) =>
+			{
+				self.output.pop();
+				self.output.push(' ');
+
+				if comment.kind == TriviaKind::LineComment && self.line_comment.is_none()
+				{
+					self.line_comment = Some(self.output.len());
+				}
+
+				self.output.push_str(text);
+				self.output.push('\n');
+			}
+
+			(false, _) =>
+			{
+				self.line(text);
+
+				if comment.kind == TriviaKind::LineComment
+				{
+					self.line_comment = Some(self.output.len() - text.len() - 1);
+				}
+			}
+		}
+
+		self.last_end = comment.offset + comment.text.len();
+	}
+
+	fn same_line(&self, begin: usize, end: usize) -> bool
+	{
+		self.source
+			.get(begin..end)
+			.is_some_and(|gap| gap.chars().all(|c| c == ' ' || c == '\t'))
+	}
+
+	// Comments before `offset` go on lines of their own, together with those that shared
+	// theirs. Returns whether `forced` is still to be used, by the first comment or else
+	// the caller.
+	fn leading(&mut self, offset: usize, mut forced: bool) -> bool
+	{
+		let mut previous = false;
+
+		while self.comments.front().is_some_and(|comment| comment.offset < offset)
+		{
+			let comment = self.comments.pop_front().unwrap();
+
+			if previous && self.same_line(self.last_end, comment.offset)
+			{
+				self.comment(comment, true);
+				continue;
+			}
+
+			self.separate(comment.offset, forced);
+			forced = false;
+
+			self.comment(comment, false);
+			previous = true;
+		}
+
+		forced
+	}
+
+	// Comments before `end`, inside the printed line, and those right after it on the same
+	// source line are appended to the line printed last
+	fn trailing(&mut self, end: usize)
+	{
+		let mut cursor = end;
+
+		while let Some(comment) = self.comments.front()
+		{
+			let same_line = self.same_line(cursor, comment.offset);
+
+			if comment.offset >= end && !same_line
+			{
+				break;
+			}
+
+			let comment = self.comments.pop_front().unwrap();
+			cursor = cursor.max(comment.offset + comment.text.len());
+
+			self.comment(comment, true);
+		}
+	}
+
+	fn statements(&mut self, statements: &[Statement], top_level: bool)
+	{
+		for (index, statement) in statements.iter().enumerate()
+		{
+			let definition = index != 0
+				&& top_level
+				&& (statements[index - 1].stype() == StatementType::FunctionDefine || statement.stype() == StatementType::FunctionDefine);
+
+			let begin = statement.span().offset_begin;
+
+			let forced = self.leading(begin, definition);
+			self.separate(begin, forced);
+
+			self.statement(statement);
+		}
+	}
+
+	fn compound(&mut self, compound: &CompoundStatement)
+	{
+		let span = compound.span();
+
+		// Comments between a header and its brace go on the header
+		self.trailing(span.offset_begin);
+		self.line("{");
+		self.trailing(span.offset_begin + 1);
+
+		self.depth += 1;
+		self.last_end = span.offset_begin + 1;
+		self.block_start = true;
+
+		self.statements(compound.statements(), false);
+		self.leading(span.offset_end.saturating_sub(1), false);
+
+		self.depth -= 1;
+		self.line("}");
+
+		self.last_end = span.offset_end;
+		self.block_start = false;
+		self.trailing(span.offset_end);
+	}
+
+	fn statement(&mut self, statement: &Statement)
+	{
+		match statement.kind()
+		{
+			StatementKind::FunctionDefine(function) =>
+			{
+				self.line(&format_signature(&function.unparse(), function.signature()));
+				self.compound(function.body());
+			}
+
+			StatementKind::FunctionDeclare(function) =>
+			{
+				self.line(&format!("{};", format_signature(&function.unparse(), function.signature())));
+			}
+
+			StatementKind::FunctionReturn(ret) => match ret.expression()
+			{
+				Some(expression) => self.line(&format!("return {};", format_expression(expression))),
+				None => self.line("return;")
+			},
+
+			StatementKind::Expression(expr_stmt) =>
+			{
+				self.line(&format!("express {};", format_expression(expr_stmt.expression())));
+			}
+
+			StatementKind::Compound(compound) => self.compound(compound),
+
+			StatementKind::Declare(declare) =>
+			{
+				let name = variable_name(&declare.unparse(), 1, declare.identifier());
+				self.line(&format!("let {} {} = {};", name, type_name(&declare.vtype()), format_expression(declare.expression())));
+			}
+
+			StatementKind::Assign(assign) =>
+			{
+				let name = variable_name(&assign.unparse(), 1, assign.identifier());
+				self.line(&format!("set {} = {};", name, format_expression(assign.expression())));
+			}
+
+			StatementKind::Print(print) =>
+			{
+				self.line(&format!("print {};", format_expression(print.expression())));
+			}
+
+			StatementKind::If(if_stmt) => self.if_statement(if_stmt, ""),
+
+			StatementKind::While(while_stmt) =>
+			{
+				self.line(&format!("while {}", format_expression(while_stmt.condition())));
+				self.compound(while_stmt.body());
+			}
+
+			StatementKind::Break(_) => self.line("break;"),
+			StatementKind::Continue(_) => self.line("continue;")
+		}
+
+		let end = statement.span().offset_end;
+
+		self.last_end = self.last_end.max(end);
+		self.trailing(end);
+	}
+
+	fn if_statement(&mut self, if_stmt: &IfStatement, prefix: &str)
+	{
+		self.line(&format!("{}if {}", prefix, format_expression(if_stmt.condition())));
+		self.compound(if_stmt.then_body());
+
+		let Some(else_body) = if_stmt.else_body() else
+		{
+			return;
+		};
+
+		self.leading(else_body.span().offset_begin, false);
+
+		match else_body.kind()
+		{
+			StatementKind::If(else_if) => self.if_statement(else_if, "else "),
+
+			StatementKind::Compound(compound) =>
+			{
+				self.line("else");
+				self.compound(compound);
+			}
+
+			_ =>
+			{
+				self.line("else");
+				self.line("{");
+				self.depth += 1;
+				self.statement(else_body);
+				self.depth -= 1;
+				self.line("}");
+			}
+		}
+	}
+}
+
+// Prints a tree back as canonical source: Allman braces, one statement per line and
+// only the parentheses the operator precedence needs. Constants print as they were
+// written rather than folded. The comments of `source`, which `root` was parsed from,
+// are kept on a line of their own when they had one and appended to the line of the
+// statement they were in or after otherwise. A tree built without tokens has no source.
+pub fn format(root: &Root, source: &str, options: &FormatOptions) -> String
+{
+	let (tokens, trailing, _) = lex_lossless(source);
+
+	let comments = tokens
+		.into_iter()
+		.flat_map(|token| token.leading.into_iter().chain(token.trailing))
+		.chain(trailing)
+		.filter(|trivia| matches!(trivia.kind, TriviaKind::LineComment | TriviaKind::BlockComment))
+		.collect();
+
+	let mut formatter = Formatter
+	{
+		options,
+		output: String::new(),
+		depth: 0,
+
+		source,
+		comments,
+
+		last_end: 0,
+		block_start: true,
+		line_comment: None
+	};
+
+	let statements: Vec<Statement> = root.statements.iter().cloned().collect();
+
+	formatter.statements(&statements, true);
+	formatter.leading(source.len(), false);
+
+	formatter.output
+}
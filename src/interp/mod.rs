@@ -0,0 +1,541 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::panic;
+use std::thread;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	literal::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+// Calls recurse on the host stack, as deep as the bytecode VM goes on a thread
+// started by `with_stack`. Deeper recursion on a smaller stack aborts the process.
+pub const DEFAULT_MAX_DEPTH: usize = 1 << 16;
+
+// Enough for `DEFAULT_MAX_DEPTH` calls in an unoptimised build, it is only reserved
+pub const STACK_SIZE: usize = 1 << 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value
+{
+	Void,
+	Integer(i32),
+	Boolean(bool)
+}
+
+impl Value
+{
+	pub fn vtype(&self) -> VType
+	{
+		match self
+		{
+			Value::Void => VType::Void,
+			Value::Integer(_) => VType::Integer,
+			Value::Boolean(_) => VType::Boolean
+		}
+	}
+}
+
+impl fmt::Display for Value
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Value::Void => write!(f, "void"),
+			Value::Integer(value) => write!(f, "{}", value),
+			Value::Boolean(value) => write!(f, "{}", value)
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum RuntimeError
+{
+	MissingMain,
+	UndefinedFunction(String),
+	MismatchedArguments { name: String, expected: usize, got: usize },
+	UnboundVariable(u16),
+	TypeMismatch { expected: VType, got: VType },
+	DivisionByZero,
+	StackOverflow(usize),
+	Output(std::io::Error)
+}
+
+impl fmt::Display for RuntimeError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			RuntimeError::MissingMain =>
+				write!(f, "no `main` function defined"),
+			RuntimeError::UndefinedFunction(name) =>
+				write!(f, "function `{}` has no definition", name),
+			RuntimeError::MismatchedArguments { name, expected, got } =>
+				write!(f, "mismatched argument length for `{}`, expected {}, got {}", name, expected, got),
+			RuntimeError::UnboundVariable(id) =>
+				write!(f, "variable with identifier {} is not bound in the current frame", id),
+			RuntimeError::TypeMismatch { expected, got } =>
+				write!(f, "expected value of type `{:?}`, got `{:?}`", expected, got),
+			RuntimeError::DivisionByZero =>
+				write!(f, "division by zero"),
+			RuntimeError::StackOverflow(depth) =>
+				write!(f, "call stack exceeded maximum depth of {}", depth),
+			RuntimeError::Output(err) =>
+				write!(f, "failed to write output: {}", err)
+		}
+	}
+}
+
+impl std::error::Error for RuntimeError {}
+
+enum Flow
+{
+	Normal,
+	Break,
+	Continue,
+	Return(Value)
+}
+
+struct Frame
+{
+	bindings: HashMap<u16, Value>
+}
+
+pub struct Interpreter<'a, W: Write>
+{
+	functions: HashMap<String, &'a FunctionDefineStatement>,
+	frames: Vec<Frame>,
+	out: W,
+	max_depth: usize
+}
+
+impl<'a, W: Write> Interpreter<'a, W>
+{
+	pub fn new(root: &'a Root, out: W) -> Self
+	{
+		let mut functions = HashMap::new();
+
+		for statement in root.statements.iter()
+		{
+			if let Some(func_define) = statement.as_statement::<FunctionDefineStatement>()
+			{
+				functions.insert(func_define.signature().name(), func_define);
+			}
+		}
+
+		Self
+		{
+			functions,
+			frames: Vec::new(),
+			out,
+			max_depth: DEFAULT_MAX_DEPTH
+		}
+	}
+
+	pub fn set_max_depth(&mut self, max_depth: usize)
+	{
+		self.max_depth = max_depth;
+	}
+
+	pub fn output(&self) -> &W
+	{
+		&self.out
+	}
+
+	pub fn into_output(self) -> W
+	{
+		self.out
+	}
+
+	pub fn run(&mut self) -> Result<Value, RuntimeError>
+	{
+		if !self.functions.contains_key("main")
+		{
+			return Err(RuntimeError::MissingMain);
+		}
+
+		self.call("main", Vec::new())
+	}
+
+	pub fn call(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError>
+	{
+		let func_define = *self.functions
+			.get(name)
+			.ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?;
+
+		let parameters = func_define.signature().parameters();
+
+		if parameters.len() != arguments.len()
+		{
+			return Err(RuntimeError::MismatchedArguments
+			{
+				name: name.to_string(),
+				expected: parameters.len(),
+				got: arguments.len()
+			});
+		}
+
+		if self.frames.len() >= self.max_depth
+		{
+			return Err(RuntimeError::StackOverflow(self.max_depth));
+		}
+
+		let mut bindings = HashMap::new();
+
+		for (param, value) in parameters.iter().zip(arguments)
+		{
+			if param.vtype() != value.vtype()
+			{
+				return Err(RuntimeError::TypeMismatch { expected: param.vtype(), got: value.vtype() });
+			}
+
+			bindings.insert(param.id(), value);
+		}
+
+		self.frames.push(Frame { bindings });
+		let flow = self.exec_compound(func_define.body());
+		self.frames.pop();
+
+		match flow?
+		{
+			Flow::Return(value) => Ok(value),
+			// The parser rejects `break` and `continue` outside of loops
+			Flow::Normal | Flow::Break | Flow::Continue => Ok(Value::Void)
+		}
+	}
+
+	// Runs statements outside of any function, as an interactive session does, with the
+	// variables bound so far. Returns the value of a `return` if one was reached.
+	pub fn execute(&mut self, statements: &[Statement], bindings: &mut HashMap<u16, Value>) -> Result<Option<Value>, RuntimeError>
+	{
+		self.frames.push(Frame { bindings: std::mem::take(bindings) });
+
+		let mut flow = Ok(Flow::Normal);
+
+		for statement in statements
+		{
+			flow = self.exec_statement(statement);
+
+			if !matches!(flow, Ok(Flow::Normal))
+			{
+				break;
+			}
+		}
+
+		// Whatever was bound before a failure stays bound
+		*bindings = self.frames.pop().unwrap().bindings;
+
+		match flow?
+		{
+			Flow::Return(value) => Ok(Some(value)),
+			Flow::Normal | Flow::Break | Flow::Continue => Ok(None)
+		}
+	}
+
+	pub fn evaluate(&mut self, expression: &Expression, bindings: &HashMap<u16, Value>) -> Result<Value, RuntimeError>
+	{
+		self.frames.push(Frame { bindings: bindings.clone() });
+		let value = self.eval_expression(expression);
+		self.frames.pop();
+
+		value
+	}
+
+	fn frame(&mut self) -> &mut Frame
+	{
+		self.frames.last_mut().expect("no active call frame")
+	}
+
+	fn exec_compound(&mut self, compound: &CompoundStatement) -> Result<Flow, RuntimeError>
+	{
+		for statement in compound.statements()
+		{
+			match self.exec_statement(statement)?
+			{
+				Flow::Normal => continue,
+				flow => return Ok(flow)
+			}
+		}
+
+		Ok(Flow::Normal)
+	}
+
+	fn exec_statement(&mut self, statement: &Statement) -> Result<Flow, RuntimeError>
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				let compound = statement.as_statement::<CompoundStatement>().unwrap();
+				self.exec_compound(compound)
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+				let value = self.eval_expression(declare.expression())?;
+
+				self.frame().bindings.insert(declare.identifier(), value);
+				Ok(Flow::Normal)
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+				let value = self.eval_expression(assign.expression())?;
+
+				let binding = self.frame().bindings
+					.get_mut(&assign.identifier())
+					.ok_or(RuntimeError::UnboundVariable(assign.identifier()))?;
+
+				*binding = value;
+				Ok(Flow::Normal)
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+				let value = self.eval_expression(print.expression())?;
+
+				writeln!(self.out, "{}", value).map_err(RuntimeError::Output)?;
+				Ok(Flow::Normal)
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+				self.eval_expression(express.expression())?;
+
+				Ok(Flow::Normal)
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				let value = match ret.expression()
+				{
+					Some(expr) => self.eval_expression(expr)?,
+					None => Value::Void
+				};
+
+				Ok(Flow::Return(value))
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				if self.eval_boolean(if_statement.condition())?
+				{
+					self.exec_compound(if_statement.then_body())
+				}
+				else if let Some(else_body) = if_statement.else_body()
+				{
+					self.exec_statement(else_body)
+				}
+				else
+				{
+					Ok(Flow::Normal)
+				}
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				while self.eval_boolean(while_statement.condition())?
+				{
+					match self.exec_compound(while_statement.body())?
+					{
+						Flow::Normal | Flow::Continue => continue,
+						Flow::Break => break,
+						flow => return Ok(flow)
+					}
+				}
+
+				Ok(Flow::Normal)
+			}
+
+			StatementType::Break => Ok(Flow::Break),
+			StatementType::Continue => Ok(Flow::Continue),
+
+			// Functions can only live at the root, the parser rejects them elsewhere
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => Ok(Flow::Normal)
+		}
+	}
+
+	fn eval_integer(&mut self, expression: &Expression) -> Result<i32, RuntimeError>
+	{
+		match self.eval_expression(expression)?
+		{
+			Value::Integer(value) => Ok(value),
+			other => Err(RuntimeError::TypeMismatch { expected: VType::Integer, got: other.vtype() })
+		}
+	}
+
+	fn eval_boolean(&mut self, expression: &Expression) -> Result<bool, RuntimeError>
+	{
+		match self.eval_expression(expression)?
+		{
+			Value::Boolean(value) => Ok(value),
+			other => Err(RuntimeError::TypeMismatch { expected: VType::Boolean, got: other.vtype() })
+		}
+	}
+
+	fn eval_expression(&mut self, expression: &Expression) -> Result<Value, RuntimeError>
+	{
+		match expression.etype()
+		{
+			ExpressionType::Literal =>
+			{
+				let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+				Ok(literal_value(literal))
+			}
+
+			ExpressionType::Variable =>
+			{
+				let variable = expression.as_expression::<VariableExpression>().unwrap();
+
+				self.frame().bindings
+					.get(&variable.identifier())
+					.copied()
+					.ok_or(RuntimeError::UnboundVariable(variable.identifier()))
+			}
+
+			ExpressionType::FunctionCall =>
+			{
+				let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+
+				let mut arguments = Vec::new();
+
+				for argument in call.passed_arguments().iter()
+				{
+					arguments.push(self.eval_expression(argument)?);
+				}
+
+				self.call(&call.name(), arguments)
+			}
+
+			ExpressionType::Arithmetic =>
+			{
+				let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+				let lhs = self.eval_integer(arith.left())?;
+				let rhs = self.eval_integer(arith.right())?;
+
+				let value = match arith.op()
+				{
+					ArithmeticOperation::Add => lhs.wrapping_add(rhs),
+					ArithmeticOperation::Subtract => lhs.wrapping_sub(rhs),
+					ArithmeticOperation::Multiply => lhs.wrapping_mul(rhs),
+					ArithmeticOperation::Divide =>
+					{
+						if rhs == 0
+						{
+							return Err(RuntimeError::DivisionByZero);
+						}
+
+						lhs.wrapping_div(rhs)
+					}
+				};
+
+				Ok(Value::Integer(value))
+			}
+
+			ExpressionType::Comparison =>
+			{
+				let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+				let lhs = self.eval_expression(cmp.left())?;
+				let rhs = self.eval_expression(cmp.right())?;
+
+				if lhs.vtype() != rhs.vtype()
+				{
+					return Err(RuntimeError::TypeMismatch { expected: lhs.vtype(), got: rhs.vtype() });
+				}
+
+				let result = match cmp.op()
+				{
+					ComparisonOperation::IsEqual => lhs == rhs,
+					ComparisonOperation::IsNotEqual => lhs != rhs,
+
+					op =>
+					{
+						let (Value::Integer(lhs), Value::Integer(rhs)) = (lhs, rhs) else
+						{
+							return Err(RuntimeError::TypeMismatch { expected: VType::Integer, got: lhs.vtype() });
+						};
+
+						match op
+						{
+							ComparisonOperation::IsGreater => lhs > rhs,
+							ComparisonOperation::IsGreaterOrEqual => lhs >= rhs,
+							ComparisonOperation::IsLess => lhs < rhs,
+							ComparisonOperation::IsLessOrEqual => lhs <= rhs,
+							_ => unreachable!()
+						}
+					}
+				};
+
+				Ok(Value::Boolean(result))
+			}
+
+			ExpressionType::Boolean =>
+			{
+				let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+				let lhs = self.eval_boolean(boolean.left())?;
+
+				// Short-circuit like every backend is expected to
+				let result = match boolean.op()
+				{
+					BooleanOperation::And => lhs && self.eval_boolean(boolean.right())?,
+					BooleanOperation::Or => lhs || self.eval_boolean(boolean.right())?
+				};
+
+				Ok(Value::Boolean(result))
+			}
+		}
+	}
+}
+
+pub fn literal_value(literal: &Literal) -> Value
+{
+	match literal.vtype()
+	{
+		VType::Integer => Value::Integer(literal.as_literal::<IntegerLiteral>().unwrap().value),
+		VType::Boolean => Value::Boolean(literal.as_literal::<BooleanLiteral>().unwrap().value),
+		VType::Void => Value::Void
+	}
+}
+
+pub fn run<W: Write>(root: &Root, out: W) -> Result<Value, RuntimeError>
+{
+	Interpreter::new(root, out).run()
+}
+
+// Runs `f` on a thread with a stack of `STACK_SIZE`, for running programs that
+// recurse up to `DEFAULT_MAX_DEPTH`. A panic in `f` carries on in the caller.
+pub fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T
+{
+	thread::scope(|scope|
+	{
+		let handle = thread::Builder::new()
+			.stack_size(STACK_SIZE)
+			.spawn_scoped(scope, f)
+			.expect("failed to spawn the interpreter thread");
+
+		handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload))
+	})
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+
+use super::*;
+
+pub fn instruction(function: &Function, instruction: &Instruction) -> String
+{
+	let operation = match instruction
+	{
+		Instruction::Copy { source, .. } => format!("copy {}", source),
+		Instruction::Binary { op, left, right, .. } => format!("{} {}, {}", op, left, right),
+
+		Instruction::Call { function, arguments, .. } =>
+		{
+			let arguments: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+			format!("call {}({})", function, arguments.join(", "))
+		}
+
+		Instruction::Print { value } => format!("print {}", value),
+
+		Instruction::Phi { sources, .. } =>
+		{
+			let sources: Vec<String> = sources.iter().map(|(block, operand)| format!("[{}: {}]", block, operand)).collect();
+			format!("phi {}", sources.join(", "))
+		}
+	};
+
+	match instruction.dest()
+	{
+		Some(dest) => format!("{}: {} = {}", dest, type_name(&function.temp_type(dest)), operation),
+		None => operation
+	}
+}
+
+pub fn signature(function: &Function) -> String
+{
+	let parameters: Vec<String> = function.parameters
+		.iter()
+		.map(|param| format!("{} {}", param, type_name(&function.temp_type(*param))))
+		.collect();
+
+	format!("function {}({}) {}", function.name, parameters.join(", "), type_name(&function.return_type))
+}
+
+// Mirrors the source syntax for signatures, every defined temporary carries its type:
+//
+// function square(%0 int) int
+// bb0:
+//   %1: int = mul %0, %0
+//   return %1
+pub fn dump(module: &Module) -> String
+{
+	let mut output = String::new();
+
+	for (index, function) in module.functions.iter().enumerate()
+	{
+		if index != 0
+		{
+			output.push('\n');
+		}
+
+		if !function.defined()
+		{
+			writeln!(output, "{};", signature(function)).unwrap();
+			continue;
+		}
+
+		writeln!(output, "{}", signature(function)).unwrap();
+
+		for (id, block) in function.blocks.iter().enumerate()
+		{
+			writeln!(output, "{}:", BlockId(id as u32)).unwrap();
+
+			for instr in block.instructions.iter()
+			{
+				writeln!(output, "  {}", self::instruction(function, instr)).unwrap();
+			}
+
+			writeln!(output, "  {}", block.terminator).unwrap();
+		}
+	}
+
+	output
+}
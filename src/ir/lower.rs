@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use crate::ast::{
+	root::*,
+	statement::*,
+	expression::*
+};
+
+use crate::data::{
+	ops::*,
+	vtype::*
+};
+
+use crate::interp::Value;
+
+use super::*;
+
+struct LoopBlocks
+{
+	header: BlockId,
+	exit: BlockId
+}
+
+struct FunctionBuilder
+{
+	// Variables are plain temporaries that may be assigned more than once
+	variables: HashMap<u16, Temp>,
+	temps: Vec<VType>,
+	blocks: Vec<BasicBlock>,
+	current: BlockId,
+	loops: Vec<LoopBlocks>
+}
+
+impl FunctionBuilder
+{
+	fn temp(&mut self, vtype: VType) -> Temp
+	{
+		self.temps.push(vtype);
+		Temp(self.temps.len() as u32 - 1)
+	}
+
+	fn variable(&mut self, identifier: u16, vtype: VType) -> Temp
+	{
+		if let Some(temp) = self.variables.get(&identifier)
+		{
+			return *temp;
+		}
+
+		let temp = self.temp(vtype);
+		self.variables.insert(identifier, temp);
+
+		temp
+	}
+
+	// Blocks start out unreachable and get their real terminator once lowered
+	fn block(&mut self) -> BlockId
+	{
+		self.blocks.push(BasicBlock { instructions: Vec::new(), terminator: Terminator::Unreachable });
+		BlockId(self.blocks.len() as u32 - 1)
+	}
+
+	fn emit(&mut self, instruction: Instruction)
+	{
+		self.blocks[self.current.0 as usize].instructions.push(instruction);
+	}
+
+	fn terminate(&mut self, terminator: Terminator)
+	{
+		self.blocks[self.current.0 as usize].terminator = terminator;
+	}
+
+	// Code following `return`, `break` or `continue` lands in a block without predecessors
+	fn terminate_and_detach(&mut self, terminator: Terminator)
+	{
+		self.terminate(terminator);
+		self.current = self.block();
+	}
+
+	fn lower_statements(&mut self, statements: &[Statement])
+	{
+		for statement in statements
+		{
+			self.lower_statement(statement);
+		}
+	}
+
+	fn lower_statement(&mut self, statement: &Statement)
+	{
+		match statement.stype()
+		{
+			StatementType::Compound =>
+			{
+				self.lower_statements(statement.as_statement::<CompoundStatement>().unwrap().statements());
+			}
+
+			StatementType::Declare =>
+			{
+				let declare = statement.as_statement::<DeclareStatement>().unwrap();
+
+				let source = self.lower_expression(declare.expression());
+				let dest = self.variable(declare.identifier(), declare.vtype());
+
+				self.emit(Instruction::Copy { dest, source });
+			}
+
+			StatementType::Assign =>
+			{
+				let assign = statement.as_statement::<AssignStatement>().unwrap();
+
+				let source = self.lower_expression(assign.expression());
+				let dest = self.variables[&assign.identifier()];
+
+				self.emit(Instruction::Copy { dest, source });
+			}
+
+			StatementType::Print =>
+			{
+				let print = statement.as_statement::<PrintStatement>().unwrap();
+
+				let value = self.lower_expression(print.expression());
+				self.emit(Instruction::Print { value });
+			}
+
+			StatementType::Expression =>
+			{
+				let express = statement.as_statement::<ExpressionStatement>().unwrap();
+				self.lower_expression(express.expression());
+			}
+
+			StatementType::FunctionReturn =>
+			{
+				let ret = statement.as_statement::<FunctionReturnStatement>().unwrap();
+
+				let value = match ret.expression()
+				{
+					Some(expr) => self.lower_expression(expr),
+					None => Operand::Constant(Value::Void)
+				};
+
+				self.terminate_and_detach(Terminator::Return(value));
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				let condition = self.lower_expression(if_statement.condition());
+
+				let then_block = self.block();
+				let else_block = if_statement.else_body().as_ref().map(|_| self.block());
+				let end_block = self.block();
+
+				let else_block = else_block.unwrap_or(end_block);
+
+				self.terminate(Terminator::Branch { condition, then_block, else_block });
+
+				self.current = then_block;
+				self.lower_statements(if_statement.then_body().statements());
+				self.terminate(Terminator::Jump(end_block));
+
+				if let Some(else_body) = if_statement.else_body()
+				{
+					self.current = else_block;
+					self.lower_statement(else_body);
+					self.terminate(Terminator::Jump(end_block));
+				}
+
+				self.current = end_block;
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				let header = self.block();
+
+				self.terminate(Terminator::Jump(header));
+
+				self.current = header;
+				let condition = self.lower_expression(while_statement.condition());
+
+				let body = self.block();
+				let exit = self.block();
+
+				self.terminate(Terminator::Branch { condition, then_block: body, else_block: exit });
+
+				self.current = body;
+				self.loops.push(LoopBlocks { header, exit });
+				self.lower_statements(while_statement.body().statements());
+				self.loops.pop();
+				self.terminate(Terminator::Jump(header));
+
+				self.current = exit;
+			}
+
+			StatementType::Break =>
+			{
+				let exit = self.loops.last().expect("`break` outside of a loop").exit;
+				self.terminate_and_detach(Terminator::Jump(exit));
+			}
+
+			StatementType::Continue =>
+			{
+				let header = self.loops.last().expect("`continue` outside of a loop").header;
+				self.terminate_and_detach(Terminator::Jump(header));
+			}
+
+			StatementType::FunctionDefine | StatementType::FunctionDeclare => {}
+		}
+	}
+
+	fn lower_expression(&mut self, expression: &Expression) -> Operand
+	{
+		match expression.etype()
+		{
+			ExpressionType::Literal =>
+			{
+				let literal = expression.as_expression::<LiteralExpression>().unwrap().literal();
+				Operand::Constant(crate::interp::literal_value(literal))
+			}
+
+			ExpressionType::Variable =>
+			{
+				let variable = expression.as_expression::<VariableExpression>().unwrap();
+				Operand::Temp(self.variables[&variable.identifier()])
+			}
+
+			ExpressionType::FunctionCall =>
+			{
+				let call = expression.as_expression::<FunctionCallExpression>().unwrap();
+
+				let arguments: Vec<Operand> = call.passed_arguments()
+					.iter()
+					.map(|argument| self.lower_expression(argument))
+					.collect();
+
+				let dest = self.temp(expression.vtype());
+				self.emit(Instruction::Call { dest, function: call.name(), arguments });
+
+				Operand::Temp(dest)
+			}
+
+			ExpressionType::Arithmetic =>
+			{
+				let arith = expression.as_expression::<ArithmeticExpression>().unwrap();
+
+				let op = match arith.op()
+				{
+					ArithmeticOperation::Add => BinaryOperation::Add,
+					ArithmeticOperation::Subtract => BinaryOperation::Subtract,
+					ArithmeticOperation::Multiply => BinaryOperation::Multiply,
+					ArithmeticOperation::Divide => BinaryOperation::Divide
+				};
+
+				self.lower_binary(op, arith.left(), arith.right())
+			}
+
+			ExpressionType::Comparison =>
+			{
+				let cmp = expression.as_expression::<ComparisonExpression>().unwrap();
+
+				let op = match cmp.op()
+				{
+					ComparisonOperation::IsEqual => BinaryOperation::Equal,
+					ComparisonOperation::IsNotEqual => BinaryOperation::NotEqual,
+					ComparisonOperation::IsGreater => BinaryOperation::Greater,
+					ComparisonOperation::IsGreaterOrEqual => BinaryOperation::GreaterOrEqual,
+					ComparisonOperation::IsLess => BinaryOperation::Less,
+					ComparisonOperation::IsLessOrEqual => BinaryOperation::LessOrEqual
+				};
+
+				self.lower_binary(op, cmp.left(), cmp.right())
+			}
+
+			ExpressionType::Boolean =>
+			{
+				let boolean = expression.as_expression::<BooleanExpression>().unwrap();
+
+				// Short-circuit, the right operand gets its own block:
+				//   and: branch left, right, short(false)
+				//   or:  branch left, short(true), right
+				let left = self.lower_expression(boolean.left());
+				let result = self.temp(VType::Boolean);
+
+				let right_block = self.block();
+				let short_block = self.block();
+				let end_block = self.block();
+
+				let (then_block, else_block, short_value) = match boolean.op()
+				{
+					BooleanOperation::And => (right_block, short_block, false),
+					BooleanOperation::Or => (short_block, right_block, true)
+				};
+
+				self.terminate(Terminator::Branch { condition: left, then_block, else_block });
+
+				self.current = right_block;
+				let right = self.lower_expression(boolean.right());
+				self.emit(Instruction::Copy { dest: result, source: right });
+				self.terminate(Terminator::Jump(end_block));
+
+				self.current = short_block;
+				self.emit(Instruction::Copy { dest: result, source: Operand::Constant(Value::Boolean(short_value)) });
+				self.terminate(Terminator::Jump(end_block));
+
+				self.current = end_block;
+
+				Operand::Temp(result)
+			}
+		}
+	}
+
+	fn lower_binary(&mut self, op: BinaryOperation, left: &Expression, right: &Expression) -> Operand
+	{
+		let left = self.lower_expression(left);
+		let right = self.lower_expression(right);
+
+		let dest = self.temp(op.result_type());
+		self.emit(Instruction::Binary { dest, op, left, right });
+
+		Operand::Temp(dest)
+	}
+}
+
+fn declare(signature: &FunctionSignature) -> Function
+{
+	let parameters = signature.parameters();
+
+	Function
+	{
+		name: signature.name(),
+		return_type: signature.return_type(),
+		parameters: (0..parameters.len() as u32).map(Temp).collect(),
+		temps: parameters.iter().map(|param| param.vtype()).collect(),
+		blocks: Vec::new()
+	}
+}
+
+pub fn lower(root: &Root) -> Module
+{
+	let mut functions: Vec<Function> = Vec::new();
+	let mut indices: HashMap<String, usize> = HashMap::new();
+
+	for statement in root.statements.iter()
+	{
+		let signature = match statement.stype()
+		{
+			StatementType::FunctionDefine => statement.as_statement::<FunctionDefineStatement>().unwrap().signature(),
+			StatementType::FunctionDeclare => statement.as_statement::<FunctionDeclareStatement>().unwrap().signature(),
+			_ => continue
+		};
+
+		if let Entry::Vacant(entry) = indices.entry(signature.name())
+		{
+			entry.insert(functions.len());
+			functions.push(declare(signature));
+		}
+	}
+
+	for statement in root.statements.iter()
+	{
+		let Some(func_define) = statement.as_statement::<FunctionDefineStatement>() else
+		{
+			continue;
+		};
+
+		let function = &mut functions[indices[&func_define.signature().name()]];
+
+		let mut builder = FunctionBuilder
+		{
+			variables: HashMap::new(),
+			temps: function.temps.clone(),
+			blocks: Vec::new(),
+			current: BlockId(0),
+			loops: Vec::new()
+		};
+
+		for (param, temp) in func_define.signature().parameters().iter().zip(function.parameters.iter())
+		{
+			builder.variables.insert(param.id(), *temp);
+		}
+
+		builder.current = builder.block();
+		builder.lower_statements(func_define.body().statements());
+
+		// Falling off the end returns void, the parser rejects it for any other return type
+		builder.terminate(match function.return_type
+		{
+			VType::Void => Terminator::Return(Operand::Constant(Value::Void)),
+			_ => Terminator::Unreachable
+		});
+
+		function.temps = builder.temps;
+		function.blocks = builder.blocks;
+
+		function.remove_unreachable_blocks();
+	}
+
+	Module { functions }
+}
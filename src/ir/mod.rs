@@ -0,0 +1,387 @@
+pub mod lower;
+pub mod dump;
+pub mod verify;
+pub mod ssa;
+pub mod opt;
+
+use std::fmt;
+
+use crate::data::vtype::*;
+use crate::interp::Value;
+
+pub fn type_name(vtype: &VType) -> &'static str
+{
+	match vtype
+	{
+		VType::Void => "void",
+		VType::Integer => "int",
+		VType::Boolean => "bool"
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Temp(pub u32);
+
+impl fmt::Display for Temp
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "%{}", self.0)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(pub u32);
+
+impl fmt::Display for BlockId
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "bb{}", self.0)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand
+{
+	Temp(Temp),
+	Constant(Value)
+}
+
+impl fmt::Display for Operand
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Operand::Temp(temp) => write!(f, "{}", temp),
+			Operand::Constant(value) => write!(f, "{}", value)
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOperation
+{
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+
+	Equal,
+	NotEqual,
+	Greater,
+	GreaterOrEqual,
+	Less,
+	LessOrEqual
+}
+
+impl BinaryOperation
+{
+	pub fn is_arithmetic(&self) -> bool
+	{
+		matches!(self, BinaryOperation::Add | BinaryOperation::Subtract | BinaryOperation::Multiply | BinaryOperation::Divide)
+	}
+
+	pub fn result_type(&self) -> VType
+	{
+		if self.is_arithmetic() { VType::Integer } else { VType::Boolean }
+	}
+}
+
+impl fmt::Display for BinaryOperation
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			BinaryOperation::Add => write!(f, "add"),
+			BinaryOperation::Subtract => write!(f, "sub"),
+			BinaryOperation::Multiply => write!(f, "mul"),
+			BinaryOperation::Divide => write!(f, "div"),
+
+			BinaryOperation::Equal => write!(f, "eq"),
+			BinaryOperation::NotEqual => write!(f, "ne"),
+			BinaryOperation::Greater => write!(f, "gt"),
+			BinaryOperation::GreaterOrEqual => write!(f, "ge"),
+			BinaryOperation::Less => write!(f, "lt"),
+			BinaryOperation::LessOrEqual => write!(f, "le")
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instruction
+{
+	Copy { dest: Temp, source: Operand },
+	Binary { dest: Temp, op: BinaryOperation, left: Operand, right: Operand },
+	Call { dest: Temp, function: String, arguments: Vec<Operand> },
+	Print { value: Operand },
+
+	// Only in SSA form, phis lead their block with one source per predecessor
+	Phi { dest: Temp, sources: Vec<(BlockId, Operand)> }
+}
+
+impl Instruction
+{
+	pub fn dest(&self) -> Option<Temp>
+	{
+		match self
+		{
+			Instruction::Copy { dest, .. }
+				| Instruction::Binary { dest, .. }
+				| Instruction::Call { dest, .. }
+				| Instruction::Phi { dest, .. } => Some(*dest),
+
+			Instruction::Print { .. } => None
+		}
+	}
+
+	pub fn is_phi(&self) -> bool
+	{
+		matches!(self, Instruction::Phi { .. })
+	}
+
+	pub fn operands(&self) -> Vec<Operand>
+	{
+		match self
+		{
+			Instruction::Copy { source, .. } => vec![*source],
+			Instruction::Binary { left, right, .. } => vec![*left, *right],
+			Instruction::Call { arguments, .. } => arguments.clone(),
+			Instruction::Print { value } => vec![*value],
+			Instruction::Phi { sources, .. } => sources.iter().map(|(_, operand)| *operand).collect()
+		}
+	}
+
+	pub fn operands_mut(&mut self) -> Vec<&mut Operand>
+	{
+		match self
+		{
+			Instruction::Copy { source, .. } => vec![source],
+			Instruction::Binary { left, right, .. } => vec![left, right],
+			Instruction::Call { arguments, .. } => arguments.iter_mut().collect(),
+			Instruction::Print { value } => vec![value],
+			Instruction::Phi { sources, .. } => sources.iter_mut().map(|(_, operand)| operand).collect()
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Terminator
+{
+	Return(Operand),
+	Jump(BlockId),
+	Branch { condition: Operand, then_block: BlockId, else_block: BlockId },
+
+	// Falling off the end of a function that the parser proved always returns
+	Unreachable
+}
+
+impl Terminator
+{
+	pub fn successors(&self) -> Vec<BlockId>
+	{
+		match self
+		{
+			Terminator::Jump(target) => vec![*target],
+			Terminator::Branch { then_block, else_block, .. } => vec![*then_block, *else_block],
+			Terminator::Return(_) | Terminator::Unreachable => Vec::new()
+		}
+	}
+
+	pub fn operands(&self) -> Vec<Operand>
+	{
+		match self
+		{
+			Terminator::Return(value) => vec![*value],
+			Terminator::Branch { condition, .. } => vec![*condition],
+			Terminator::Jump(_) | Terminator::Unreachable => Vec::new()
+		}
+	}
+
+	pub fn operands_mut(&mut self) -> Vec<&mut Operand>
+	{
+		match self
+		{
+			Terminator::Return(value) => vec![value],
+			Terminator::Branch { condition, .. } => vec![condition],
+			Terminator::Jump(_) | Terminator::Unreachable => Vec::new()
+		}
+	}
+}
+
+impl fmt::Display for Terminator
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Terminator::Return(value) => write!(f, "return {}", value),
+			Terminator::Jump(target) => write!(f, "jump {}", target),
+			Terminator::Branch { condition, then_block, else_block } => write!(f, "branch {}, {}, {}", condition, then_block, else_block),
+			Terminator::Unreachable => write!(f, "unreachable")
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock
+{
+	pub instructions: Vec<Instruction>,
+	pub terminator: Terminator
+}
+
+#[derive(Debug, Clone)]
+pub struct Function
+{
+	pub name: String,
+	pub return_type: VType,
+
+	// Parameters are the first temporaries, `temps` holds the type of every temporary
+	pub parameters: Vec<Temp>,
+	pub temps: Vec<VType>,
+
+	// The entry block is always the first one, declared functions have none
+	pub blocks: Vec<BasicBlock>
+}
+
+impl Function
+{
+	pub fn defined(&self) -> bool
+	{
+		!self.blocks.is_empty()
+	}
+
+	pub fn temp_type(&self, temp: Temp) -> VType
+	{
+		self.temps[temp.0 as usize].clone()
+	}
+
+	pub fn operand_type(&self, operand: &Operand) -> VType
+	{
+		match operand
+		{
+			Operand::Temp(temp) => self.temp_type(*temp),
+			Operand::Constant(value) => value.vtype()
+		}
+	}
+
+	pub fn block(&self, id: BlockId) -> &BasicBlock
+	{
+		&self.blocks[id.0 as usize]
+	}
+
+	// Drops blocks that cannot be reached from the entry block and renumbers the rest in order
+	pub fn remove_unreachable_blocks(&mut self)
+	{
+		if !self.defined()
+		{
+			return;
+		}
+
+		let mut reachable = vec![false; self.blocks.len()];
+		let mut worklist = vec![BlockId(0)];
+
+		while let Some(block) = worklist.pop()
+		{
+			if reachable[block.0 as usize]
+			{
+				continue;
+			}
+
+			reachable[block.0 as usize] = true;
+			worklist.extend(self.block(block).terminator.successors());
+		}
+
+		let mut renumbered = Vec::with_capacity(self.blocks.len());
+		let mut next = 0;
+
+		for is_reachable in reachable.iter()
+		{
+			renumbered.push(BlockId(next));
+
+			if *is_reachable
+			{
+				next += 1;
+			}
+		}
+
+		let blocks = std::mem::take(&mut self.blocks);
+
+		for (block, is_reachable) in blocks.into_iter().zip(reachable.iter())
+		{
+			if !*is_reachable
+			{
+				continue;
+			}
+
+			let mut block = block;
+
+			block.terminator = match block.terminator
+			{
+				Terminator::Jump(target) => Terminator::Jump(renumbered[target.0 as usize]),
+
+				Terminator::Branch { condition, then_block, else_block } => Terminator::Branch
+				{
+					condition,
+					then_block: renumbered[then_block.0 as usize],
+					else_block: renumbered[else_block.0 as usize]
+				},
+
+				other => other
+			};
+
+			// Edges from dropped blocks disappear, so do their phi sources
+			for instruction in block.instructions.iter_mut()
+			{
+				if let Instruction::Phi { sources, .. } = instruction
+				{
+					sources.retain(|(source, _)| reachable[source.0 as usize]);
+
+					for (source, _) in sources.iter_mut()
+					{
+						*source = renumbered[source.0 as usize];
+					}
+				}
+			}
+
+			self.blocks.push(block);
+		}
+	}
+
+	pub fn predecessors(&self) -> Vec<Vec<BlockId>>
+	{
+		let mut predecessors = vec![Vec::new(); self.blocks.len()];
+
+		for (index, block) in self.blocks.iter().enumerate()
+		{
+			for successor in block.terminator.successors()
+			{
+				let entry = &mut predecessors[successor.0 as usize];
+
+				// A branch with both edges into the same block is still a single predecessor
+				if !entry.contains(&BlockId(index as u32))
+				{
+					entry.push(BlockId(index as u32));
+				}
+			}
+		}
+
+		predecessors
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Module
+{
+	pub functions: Vec<Function>
+}
+
+impl Module
+{
+	pub fn function(&self, name: &str) -> Option<&Function>
+	{
+		self.functions.iter().find(|function| function.name == name)
+	}
+}
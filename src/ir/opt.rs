@@ -0,0 +1,520 @@
+use std::collections::{
+	HashMap,
+	HashSet
+};
+
+use crate::interp::Value;
+
+use super::*;
+use super::ssa::Dominators;
+
+pub const DEFAULT_MAX_ITERATIONS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass
+{
+	ConstantPropagation,
+	CopyPropagation,
+	CommonSubexpressionElimination,
+	DeadCodeElimination
+}
+
+impl Pass
+{
+	// In the order the pass manager runs them
+	pub const ALL: [Pass; 4] = [
+		Pass::ConstantPropagation,
+		Pass::CopyPropagation,
+		Pass::CommonSubexpressionElimination,
+		Pass::DeadCodeElimination
+	];
+
+	pub fn name(&self) -> &'static str
+	{
+		match self
+		{
+			Pass::ConstantPropagation => "constant-propagation",
+			Pass::CopyPropagation => "copy-propagation",
+			Pass::CommonSubexpressionElimination => "common-subexpression-elimination",
+			Pass::DeadCodeElimination => "dead-code-elimination"
+		}
+	}
+
+	// Returns whether the function changed
+	pub fn run(&self, function: &mut Function) -> bool
+	{
+		match self
+		{
+			Pass::ConstantPropagation => constant_propagation(function),
+			Pass::CopyPropagation => copy_propagation(function),
+			Pass::CommonSubexpressionElimination => common_subexpression_elimination(function),
+			Pass::DeadCodeElimination => dead_code_elimination(function)
+		}
+	}
+}
+
+// Runs the enabled passes over SSA form until nothing changes or the iteration limit is hit
+pub struct PassManager
+{
+	disabled: HashSet<Pass>,
+	max_iterations: usize
+}
+
+impl Default for PassManager
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
+
+impl PassManager
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			disabled: HashSet::new(),
+			max_iterations: DEFAULT_MAX_ITERATIONS
+		}
+	}
+
+	pub fn set_enabled(&mut self, pass: Pass, enabled: bool)
+	{
+		if enabled
+		{
+			self.disabled.remove(&pass);
+		}
+		else
+		{
+			self.disabled.insert(pass);
+		}
+	}
+
+	pub fn is_enabled(&self, pass: Pass) -> bool
+	{
+		!self.disabled.contains(&pass)
+	}
+
+	pub fn set_max_iterations(&mut self, max_iterations: usize)
+	{
+		self.max_iterations = max_iterations;
+	}
+
+	// Functions are put into SSA form first, which is not optional
+	pub fn run(&self, module: &mut Module)
+	{
+		for function in module.functions.iter_mut()
+		{
+			self.run_function(function);
+		}
+	}
+
+	pub fn run_function(&self, function: &mut Function)
+	{
+		if !function.defined()
+		{
+			return;
+		}
+
+		ssa::construct(function);
+
+		for _ in 0..self.max_iterations
+		{
+			let mut changed = false;
+
+			for pass in Pass::ALL.iter().filter(|pass| self.is_enabled(**pass))
+			{
+				changed |= pass.run(function);
+			}
+
+			if !changed
+			{
+				break;
+			}
+		}
+	}
+}
+
+// Same semantics as the interpreter, division by zero is left in place to trap at runtime
+pub fn fold(op: BinaryOperation, left: Value, right: Value) -> Option<Value>
+{
+	match (left, right)
+	{
+		(Value::Integer(left), Value::Integer(right)) => Some(match op
+		{
+			BinaryOperation::Add => Value::Integer(left.wrapping_add(right)),
+			BinaryOperation::Subtract => Value::Integer(left.wrapping_sub(right)),
+			BinaryOperation::Multiply => Value::Integer(left.wrapping_mul(right)),
+
+			BinaryOperation::Divide if right == 0 => return None,
+			BinaryOperation::Divide => Value::Integer(left.wrapping_div(right)),
+
+			BinaryOperation::Equal => Value::Boolean(left == right),
+			BinaryOperation::NotEqual => Value::Boolean(left != right),
+			BinaryOperation::Greater => Value::Boolean(left > right),
+			BinaryOperation::GreaterOrEqual => Value::Boolean(left >= right),
+			BinaryOperation::Less => Value::Boolean(left < right),
+			BinaryOperation::LessOrEqual => Value::Boolean(left <= right)
+		}),
+
+		(Value::Boolean(left), Value::Boolean(right)) => match op
+		{
+			BinaryOperation::Equal => Some(Value::Boolean(left == right)),
+			BinaryOperation::NotEqual => Some(Value::Boolean(left != right)),
+			_ => None
+		},
+
+		_ => None
+	}
+}
+
+// Rewrites every use, chains like `%1 -> %2 -> 5` are followed to the end
+fn replace_uses(function: &mut Function, replacements: &HashMap<Temp, Operand>) -> bool
+{
+	if replacements.is_empty()
+	{
+		return false;
+	}
+
+	let resolve = |operand: &Operand| -> Operand
+	{
+		let mut operand = *operand;
+		let mut steps = 0;
+
+		while let Operand::Temp(temp) = operand
+		{
+			match replacements.get(&temp)
+			{
+				// A phi that only merges itself could cycle, the step limit breaks it
+				Some(next) if steps <= replacements.len() => operand = *next,
+				_ => break
+			}
+
+			steps += 1;
+		}
+
+		operand
+	};
+
+	let mut changed = false;
+
+	for block in function.blocks.iter_mut()
+	{
+		let operands = block.instructions
+			.iter_mut()
+			.flat_map(|instruction| instruction.operands_mut())
+			.chain(block.terminator.operands_mut());
+
+		for operand in operands
+		{
+			let resolved = resolve(operand);
+
+			if resolved != *operand
+			{
+				*operand = resolved;
+				changed = true;
+			}
+		}
+	}
+
+	changed
+}
+
+// The single value a phi merges, ignoring sources that are the phi itself
+fn phi_value(dest: Temp, sources: &[(BlockId, Operand)]) -> Option<Operand>
+{
+	let mut value = None;
+
+	for (_, operand) in sources
+	{
+		if *operand == Operand::Temp(dest)
+		{
+			continue;
+		}
+
+		match value
+		{
+			None => value = Some(*operand),
+			Some(existing) if existing == *operand => {}
+			Some(_) => return None
+		}
+	}
+
+	value
+}
+
+pub fn constant_propagation(function: &mut Function) -> bool
+{
+	let mut changed = false;
+
+	loop
+	{
+		let mut constants: HashMap<Temp, Operand> = HashMap::new();
+
+		for block in function.blocks.iter_mut()
+		{
+			for instruction in block.instructions.iter_mut()
+			{
+				match instruction
+				{
+					Instruction::Copy { dest, source: source @ Operand::Constant(_) } =>
+					{
+						constants.insert(*dest, *source);
+					}
+
+					Instruction::Binary { dest, op, left: Operand::Constant(left), right: Operand::Constant(right) } =>
+					{
+						if let Some(value) = fold(*op, *left, *right)
+						{
+							let dest = *dest;
+
+							*instruction = Instruction::Copy { dest, source: Operand::Constant(value) };
+							constants.insert(dest, Operand::Constant(value));
+							changed = true;
+						}
+					}
+
+					Instruction::Phi { dest, sources } =>
+					{
+						if let Some(value @ Operand::Constant(_)) = phi_value(*dest, sources)
+						{
+							constants.insert(*dest, value);
+						}
+					}
+
+					_ => {}
+				}
+			}
+		}
+
+		if !replace_uses(function, &constants)
+		{
+			break;
+		}
+
+		changed = true;
+	}
+
+	let mut folded = false;
+
+	for index in 0..function.blocks.len()
+	{
+		let Terminator::Branch { condition: Operand::Constant(Value::Boolean(condition)), then_block, else_block } = function.blocks[index].terminator else
+		{
+			continue;
+		};
+
+		let (taken, dropped) = if condition { (then_block, else_block) } else { (else_block, then_block) };
+
+		function.blocks[index].terminator = Terminator::Jump(taken);
+		folded = true;
+
+		if dropped != taken
+		{
+			for instruction in function.blocks[dropped.0 as usize].instructions.iter_mut()
+			{
+				if let Instruction::Phi { sources, .. } = instruction
+				{
+					sources.retain(|(source, _)| source.0 as usize != index);
+				}
+			}
+		}
+	}
+
+	if folded
+	{
+		function.remove_unreachable_blocks();
+	}
+
+	changed || folded
+}
+
+pub fn copy_propagation(function: &mut Function) -> bool
+{
+	let mut copies: HashMap<Temp, Operand> = HashMap::new();
+
+	for block in function.blocks.iter()
+	{
+		for instruction in block.instructions.iter()
+		{
+			match instruction
+			{
+				Instruction::Copy { dest, source } =>
+				{
+					copies.insert(*dest, *source);
+				}
+
+				Instruction::Phi { dest, sources } =>
+				{
+					if let Some(value) = phi_value(*dest, sources)
+					{
+						copies.insert(*dest, value);
+					}
+				}
+
+				_ => {}
+			}
+		}
+	}
+
+	replace_uses(function, &copies)
+}
+
+fn is_commutative(op: BinaryOperation) -> bool
+{
+	matches!(op, BinaryOperation::Add | BinaryOperation::Multiply | BinaryOperation::Equal | BinaryOperation::NotEqual)
+}
+
+// Binary instructions are pure, apart from a division trapping, and an identical
+// dominating division would have trapped first
+pub fn common_subexpression_elimination(function: &mut Function) -> bool
+{
+	let dominators = Dominators::compute(function);
+	let children = dominators.children();
+
+	let mut available: HashMap<String, Temp> = HashMap::new();
+	let mut changed = false;
+
+	// Depth-first over the dominator tree, an expression is available in every dominated block
+	let mut stack: Vec<(BlockId, Option<Vec<String>>)> = vec![(BlockId(0), None)];
+
+	while let Some((block, scope)) = stack.pop()
+	{
+		if let Some(keys) = scope
+		{
+			for key in keys
+			{
+				available.remove(&key);
+			}
+
+			continue;
+		}
+
+		let mut inserted = Vec::new();
+
+		for instruction in function.blocks[block.0 as usize].instructions.iter_mut()
+		{
+			let Instruction::Binary { dest, op, left, right } = instruction else
+			{
+				continue;
+			};
+
+			let (mut first, mut second) = (left.to_string(), right.to_string());
+
+			if is_commutative(*op) && first > second
+			{
+				std::mem::swap(&mut first, &mut second);
+			}
+
+			let key = format!("{} {}, {}", op, first, second);
+
+			match available.get(&key)
+			{
+				Some(existing) =>
+				{
+					*instruction = Instruction::Copy { dest: *dest, source: Operand::Temp(*existing) };
+					changed = true;
+				}
+
+				None =>
+				{
+					available.insert(key.clone(), *dest);
+					inserted.push(key);
+				}
+			}
+		}
+
+		stack.push((block, Some(inserted)));
+
+		for child in children[block.0 as usize].iter().rev()
+		{
+			stack.push((*child, None));
+		}
+	}
+
+	changed
+}
+
+// Instructions that have to stay even when their result is never used
+fn has_side_effects(instruction: &Instruction) -> bool
+{
+	match instruction
+	{
+		Instruction::Call { .. } | Instruction::Print { .. } => true,
+
+		Instruction::Binary { op: BinaryOperation::Divide, right, .. } =>
+		{
+			!matches!(right, Operand::Constant(Value::Integer(divisor)) if *divisor != 0)
+		}
+
+		_ => false
+	}
+}
+
+fn push_operands(operands: Vec<Operand>, worklist: &mut Vec<Temp>)
+{
+	for operand in operands
+	{
+		if let Operand::Temp(temp) = operand
+		{
+			worklist.push(temp);
+		}
+	}
+}
+
+// Mark and sweep, so cycles of otherwise unused phis are removed as well
+pub fn dead_code_elimination(function: &mut Function) -> bool
+{
+	let mut definitions: HashMap<Temp, &Instruction> = HashMap::new();
+	let mut worklist: Vec<Temp> = Vec::new();
+
+	for block in function.blocks.iter()
+	{
+		for instruction in block.instructions.iter()
+		{
+			if let Some(dest) = instruction.dest()
+			{
+				definitions.insert(dest, instruction);
+			}
+
+			if has_side_effects(instruction)
+			{
+				push_operands(instruction.operands(), &mut worklist);
+			}
+		}
+
+		push_operands(block.terminator.operands(), &mut worklist);
+	}
+
+	let mut live: HashSet<Temp> = HashSet::new();
+
+	while let Some(temp) = worklist.pop()
+	{
+		if !live.insert(temp)
+		{
+			continue;
+		}
+
+		if let Some(instruction) = definitions.get(&temp)
+		{
+			push_operands(instruction.operands(), &mut worklist);
+		}
+	}
+
+	let mut changed = false;
+
+	for block in function.blocks.iter_mut()
+	{
+		let before = block.instructions.len();
+
+		block.instructions.retain(|instruction|
+		{
+			has_side_effects(instruction) || instruction.dest().is_some_and(|dest| live.contains(&dest))
+		});
+
+		changed |= block.instructions.len() != before;
+	}
+
+	changed
+}
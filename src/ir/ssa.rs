@@ -0,0 +1,501 @@
+use std::collections::{
+	HashMap,
+	HashSet
+};
+
+use crate::interp::Value;
+
+use super::*;
+
+// Dominator tree after Cooper, Harvey and Kennedy, "A Simple, Fast Dominance Algorithm"
+pub struct Dominators
+{
+	idom: Vec<Option<BlockId>>,
+
+	// Reverse postorder of the blocks reachable from the entry
+	order: Vec<BlockId>
+}
+
+impl Dominators
+{
+	pub fn compute(function: &Function) -> Self
+	{
+		let count = function.blocks.len();
+
+		let mut postorder = Vec::with_capacity(count);
+		let mut visited = vec![false; count];
+
+		// Iterative depth-first search, a block is emitted once all its successors were
+		let mut stack = vec![(BlockId(0), 0)];
+		visited[0] = true;
+
+		while let Some((block, next)) = stack.pop()
+		{
+			let successors = function.block(block).terminator.successors();
+
+			match successors.get(next)
+			{
+				Some(successor) =>
+				{
+					stack.push((block, next + 1));
+
+					if !visited[successor.0 as usize]
+					{
+						visited[successor.0 as usize] = true;
+						stack.push((*successor, 0));
+					}
+				}
+
+				None => postorder.push(block)
+			}
+		}
+
+		let mut position = vec![usize::MAX; count];
+
+		for (index, block) in postorder.iter().enumerate()
+		{
+			position[block.0 as usize] = index;
+		}
+
+		let predecessors = function.predecessors();
+		let order: Vec<BlockId> = postorder.iter().rev().copied().collect();
+
+		let mut idom: Vec<Option<BlockId>> = vec![None; count];
+		idom[0] = Some(BlockId(0));
+
+		let mut changed = true;
+
+		while changed
+		{
+			changed = false;
+
+			for block in order.iter().skip(1)
+			{
+				let mut new_idom: Option<BlockId> = None;
+
+				for predecessor in predecessors[block.0 as usize].iter()
+				{
+					if idom[predecessor.0 as usize].is_none()
+					{
+						continue;
+					}
+
+					new_idom = Some(match new_idom
+					{
+						None => *predecessor,
+						Some(current) => intersect(&idom, &position, *predecessor, current)
+					});
+				}
+
+				if new_idom.is_some() && idom[block.0 as usize] != new_idom
+				{
+					idom[block.0 as usize] = new_idom;
+					changed = true;
+				}
+			}
+		}
+
+		Self { idom, order }
+	}
+
+	// The entry block has no immediate dominator, neither do unreachable blocks
+	pub fn immediate_dominator(&self, block: BlockId) -> Option<BlockId>
+	{
+		match block.0
+		{
+			0 => None,
+			_ => self.idom[block.0 as usize]
+		}
+	}
+
+	pub fn dominates(&self, dominator: BlockId, block: BlockId) -> bool
+	{
+		let mut current = Some(block);
+
+		while let Some(block) = current
+		{
+			if block == dominator
+			{
+				return true;
+			}
+
+			current = self.immediate_dominator(block);
+		}
+
+		false
+	}
+
+	pub fn reverse_postorder(&self) -> &[BlockId]
+	{
+		&self.order
+	}
+
+	pub fn children(&self) -> Vec<Vec<BlockId>>
+	{
+		let mut children = vec![Vec::new(); self.idom.len()];
+
+		for block in self.order.iter()
+		{
+			if let Some(parent) = self.immediate_dominator(*block)
+			{
+				children[parent.0 as usize].push(*block);
+			}
+		}
+
+		children
+	}
+
+	pub fn frontiers(&self, function: &Function) -> Vec<HashSet<BlockId>>
+	{
+		let mut frontiers = vec![HashSet::new(); function.blocks.len()];
+
+		for (index, predecessors) in function.predecessors().iter().enumerate()
+		{
+			if predecessors.len() < 2
+			{
+				continue;
+			}
+
+			let block = BlockId(index as u32);
+
+			let Some(idom) = self.immediate_dominator(block) else
+			{
+				continue;
+			};
+
+			for predecessor in predecessors
+			{
+				let mut runner = Some(*predecessor);
+
+				while let Some(current) = runner
+				{
+					if current == idom || self.idom[current.0 as usize].is_none()
+					{
+						break;
+					}
+
+					frontiers[current.0 as usize].insert(block);
+					runner = self.immediate_dominator(current);
+				}
+			}
+		}
+
+		frontiers
+	}
+}
+
+fn intersect(idom: &[Option<BlockId>], position: &[usize], mut left: BlockId, mut right: BlockId) -> BlockId
+{
+	while left != right
+	{
+		while position[left.0 as usize] < position[right.0 as usize]
+		{
+			left = idom[left.0 as usize].unwrap();
+		}
+
+		while position[right.0 as usize] < position[left.0 as usize]
+		{
+			right = idom[right.0 as usize].unwrap();
+		}
+	}
+
+	left
+}
+
+// Temporaries live on entry to each block, limited to `candidates`
+fn live_in(function: &Function, candidates: &HashSet<Temp>) -> Vec<HashSet<Temp>>
+{
+	let count = function.blocks.len();
+
+	let mut uses = vec![HashSet::new(); count];
+	let mut defs = vec![HashSet::new(); count];
+
+	for (index, block) in function.blocks.iter().enumerate()
+	{
+		for instruction in block.instructions.iter()
+		{
+			for operand in instruction.operands()
+			{
+				if let Operand::Temp(temp) = operand
+					&& candidates.contains(&temp) && !defs[index].contains(&temp)
+				{
+					uses[index].insert(temp);
+				}
+			}
+
+			if let Some(dest) = instruction.dest()
+			{
+				defs[index].insert(dest);
+			}
+		}
+
+		for operand in block.terminator.operands()
+		{
+			if let Operand::Temp(temp) = operand
+				&& candidates.contains(&temp) && !defs[index].contains(&temp)
+			{
+				uses[index].insert(temp);
+			}
+		}
+	}
+
+	let mut live = uses.clone();
+	let mut changed = true;
+
+	while changed
+	{
+		changed = false;
+
+		for index in (0..count).rev()
+		{
+			for successor in function.blocks[index].terminator.successors()
+			{
+				let incoming: Vec<Temp> = live[successor.0 as usize]
+					.iter()
+					.filter(|temp| !defs[index].contains(temp))
+					.copied()
+					.collect();
+
+				for temp in incoming
+				{
+					changed |= live[index].insert(temp);
+				}
+			}
+		}
+	}
+
+	live
+}
+
+fn undefined(vtype: &VType) -> Operand
+{
+	Operand::Constant(match vtype
+	{
+		VType::Void => Value::Void,
+		VType::Integer => Value::Integer(0),
+		VType::Boolean => Value::Boolean(false)
+	})
+}
+
+struct Renamer<'a>
+{
+	function: &'a mut Function,
+	children: Vec<Vec<BlockId>>,
+
+	// Original temporary of every inserted phi, in block order
+	phi_variables: Vec<Vec<Temp>>,
+	variables: HashSet<Temp>,
+	stacks: HashMap<Temp, Vec<Temp>>
+}
+
+impl Renamer<'_>
+{
+	fn current(&self, temp: Temp) -> Operand
+	{
+		match self.stacks.get(&temp).and_then(|stack| stack.last())
+		{
+			Some(renamed) => Operand::Temp(*renamed),
+
+			// Only reachable through paths the front end never lets read the variable
+			None => undefined(&self.function.temp_type(temp))
+		}
+	}
+
+	fn rename_operand(&self, operand: &mut Operand)
+	{
+		if let Operand::Temp(temp) = operand
+			&& self.variables.contains(temp)
+		{
+			*operand = self.current(*temp);
+		}
+	}
+
+	fn rename(&mut self, block: BlockId)
+	{
+		let mut pushed = Vec::new();
+		let mut instructions = std::mem::take(&mut self.function.blocks[block.0 as usize].instructions);
+
+		for instruction in instructions.iter_mut()
+		{
+			if !instruction.is_phi()
+			{
+				for operand in instruction.operands_mut()
+				{
+					self.rename_operand(operand);
+				}
+			}
+
+			let dest = match instruction
+			{
+				Instruction::Copy { dest, .. }
+					| Instruction::Binary { dest, .. }
+					| Instruction::Call { dest, .. }
+					| Instruction::Phi { dest, .. } => dest,
+
+				Instruction::Print { .. } => continue
+			};
+
+			if self.variables.contains(dest)
+			{
+				let variable = *dest;
+				let vtype = self.function.temp_type(variable);
+
+				self.function.temps.push(vtype);
+				*dest = Temp(self.function.temps.len() as u32 - 1);
+
+				self.stacks.entry(variable).or_default().push(*dest);
+				pushed.push(variable);
+			}
+		}
+
+		self.function.blocks[block.0 as usize].instructions = instructions;
+
+		let mut terminator = self.function.blocks[block.0 as usize].terminator.clone();
+
+		for operand in terminator.operands_mut()
+		{
+			self.rename_operand(operand);
+		}
+
+		self.function.blocks[block.0 as usize].terminator = terminator.clone();
+
+		let mut successors = terminator.successors();
+		successors.dedup();
+
+		for successor in successors
+		{
+			let variables = self.phi_variables[successor.0 as usize].clone();
+
+			for (index, variable) in variables.into_iter().enumerate()
+			{
+				let incoming = self.current(variable);
+
+				if let Instruction::Phi { sources, .. } = &mut self.function.blocks[successor.0 as usize].instructions[index]
+				{
+					for (source, operand) in sources.iter_mut()
+					{
+						if *source == block
+						{
+							*operand = incoming;
+						}
+					}
+				}
+			}
+		}
+
+		for child in self.children[block.0 as usize].clone()
+		{
+			self.rename(child);
+		}
+
+		for variable in pushed
+		{
+			self.stacks.get_mut(&variable).unwrap().pop();
+		}
+	}
+}
+
+// Rewrites a lowered function into SSA form. Only temporaries with more than one
+// definition, the variables, get renamed. Phis are pruned to where the variable is live.
+pub fn construct(function: &mut Function)
+{
+	if !function.defined()
+	{
+		return;
+	}
+
+	function.remove_unreachable_blocks();
+
+	let mut definitions: HashMap<Temp, Vec<BlockId>> = HashMap::new();
+
+	for param in function.parameters.iter()
+	{
+		definitions.entry(*param).or_default().push(BlockId(0));
+	}
+
+	for (index, block) in function.blocks.iter().enumerate()
+	{
+		for instruction in block.instructions.iter()
+		{
+			if let Some(dest) = instruction.dest()
+			{
+				definitions.entry(dest).or_default().push(BlockId(index as u32));
+			}
+		}
+	}
+
+	let variables: HashSet<Temp> = definitions
+		.iter()
+		.filter(|(_, blocks)| blocks.len() > 1)
+		.map(|(temp, _)| *temp)
+		.collect();
+
+	let dominators = Dominators::compute(function);
+	let frontiers = dominators.frontiers(function);
+	let live = live_in(function, &variables);
+	let predecessors = function.predecessors();
+
+	let mut phi_variables = vec![Vec::new(); function.blocks.len()];
+
+	// Sorted so the phi order, and with it the dump, is deterministic
+	let mut sorted: Vec<Temp> = variables.iter().copied().collect();
+	sorted.sort();
+
+	for variable in sorted
+	{
+		let mut worklist: Vec<BlockId> = definitions[&variable].clone();
+		let mut queued: HashSet<BlockId> = worklist.iter().copied().collect();
+		let mut placed: HashSet<BlockId> = HashSet::new();
+
+		while let Some(block) = worklist.pop()
+		{
+			let mut frontier: Vec<BlockId> = frontiers[block.0 as usize].iter().copied().collect();
+			frontier.sort();
+
+			for target in frontier
+			{
+				if placed.contains(&target) || !live[target.0 as usize].contains(&variable)
+				{
+					continue;
+				}
+
+				placed.insert(target);
+
+				let sources = predecessors[target.0 as usize]
+					.iter()
+					.map(|predecessor| (*predecessor, Operand::Temp(variable)))
+					.collect();
+
+				let instructions = &mut function.blocks[target.0 as usize].instructions;
+				let at = phi_variables[target.0 as usize].len();
+
+				instructions.insert(at, Instruction::Phi { dest: variable, sources });
+				phi_variables[target.0 as usize].push(variable);
+
+				if queued.insert(target)
+				{
+					worklist.push(target);
+				}
+			}
+		}
+	}
+
+	let mut stacks: HashMap<Temp, Vec<Temp>> = HashMap::new();
+
+	// Parameters keep their temporary for the first definition
+	for param in function.parameters.iter()
+	{
+		stacks.insert(*param, vec![*param]);
+	}
+
+	let mut renamer = Renamer
+	{
+		children: dominators.children(),
+		phi_variables,
+		variables,
+		stacks,
+		function
+	};
+
+	renamer.rename(BlockId(0));
+}
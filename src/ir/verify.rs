@@ -0,0 +1,407 @@
+use std::collections::{
+	HashMap,
+	HashSet
+};
+use std::fmt;
+
+use super::*;
+use super::ssa::Dominators;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError
+{
+	pub function: String,
+	pub block: Option<BlockId>,
+	pub message: String
+}
+
+impl fmt::Display for VerifyError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self.block
+		{
+			Some(block) => write!(f, "function `{}`, {}: {}", self.function, block, self.message),
+			None => write!(f, "function `{}`: {}", self.function, self.message)
+		}
+	}
+}
+
+impl std::error::Error for VerifyError {}
+
+struct Verifier<'a>
+{
+	module: &'a Module,
+	function: &'a Function,
+	block: Option<BlockId>,
+	errors: Vec<VerifyError>,
+
+	// Only known once every jump target is in range
+	predecessors: Option<Vec<Vec<BlockId>>>
+}
+
+impl Verifier<'_>
+{
+	fn error(&mut self, message: String)
+	{
+		self.errors.push(VerifyError
+		{
+			function: self.function.name.clone(),
+			block: self.block,
+			message
+		});
+	}
+
+	fn temp_type(&mut self, temp: Temp) -> Option<VType>
+	{
+		match self.function.temps.get(temp.0 as usize)
+		{
+			Some(vtype) => Some(vtype.clone()),
+
+			None =>
+			{
+				self.error(format!("temporary `{}` is out of range, the function has {}", temp, self.function.temps.len()));
+				None
+			}
+		}
+	}
+
+	fn operand_type(&mut self, operand: &Operand) -> Option<VType>
+	{
+		match operand
+		{
+			Operand::Temp(temp) => self.temp_type(*temp),
+			Operand::Constant(value) => Some(value.vtype())
+		}
+	}
+
+	fn expect_type(&mut self, what: &str, operand: &Operand, expected: &VType)
+	{
+		if let Some(got) = self.operand_type(operand)
+			&& got != *expected
+		{
+			self.error(format!("mismatched type for {} `{}`, expected `{:?}`, got `{:?}`", what, operand, expected, got));
+		}
+	}
+
+	fn expect_dest(&mut self, dest: Temp, expected: &VType)
+	{
+		if let Some(got) = self.temp_type(dest)
+			&& got != *expected
+		{
+			self.error(format!("mismatched type for destination `{}`, expected `{:?}`, got `{:?}`", dest, expected, got));
+		}
+	}
+
+	fn expect_block(&mut self, target: BlockId)
+	{
+		if target.0 as usize >= self.function.blocks.len()
+		{
+			self.error(format!("jump to non-existent block `{}`", target));
+		}
+	}
+
+	fn verify_instruction(&mut self, instruction: &Instruction)
+	{
+		match instruction
+		{
+			Instruction::Copy { dest, source } =>
+			{
+				if let Some(source_type) = self.operand_type(source)
+				{
+					self.expect_dest(*dest, &source_type);
+				}
+			}
+
+			Instruction::Binary { dest, op, left, right } =>
+			{
+				match op
+				{
+					BinaryOperation::Equal | BinaryOperation::NotEqual =>
+					{
+						let left_type = self.operand_type(left);
+						let right_type = self.operand_type(right);
+
+						if let (Some(left_type), Some(right_type)) = (left_type, right_type)
+							&& (left_type != right_type || left_type == VType::Void)
+						{
+							self.error(format!("mismatched operand types for `{}`, got `{:?}` and `{:?}`", op, left_type, right_type));
+						}
+					}
+
+					_ =>
+					{
+						self.expect_type("left operand", left, &VType::Integer);
+						self.expect_type("right operand", right, &VType::Integer);
+					}
+				}
+
+				self.expect_dest(*dest, &op.result_type());
+			}
+
+			Instruction::Call { dest, function, arguments } =>
+			{
+				let module = self.module;
+
+				let Some(callee) = module.function(function) else
+				{
+					self.error(format!("call to undeclared function `{}`", function));
+					return;
+				};
+
+				if callee.parameters.len() != arguments.len()
+				{
+					self.error(format!("mismatched argument length for `{}`, expected {}, got {}", function, callee.parameters.len(), arguments.len()));
+					return;
+				}
+
+				for (argument, param) in arguments.iter().zip(callee.parameters.iter())
+				{
+					self.expect_type("argument", argument, &callee.temp_type(*param));
+				}
+
+				self.expect_dest(*dest, &callee.return_type);
+			}
+
+			Instruction::Print { value } =>
+			{
+				self.operand_type(value);
+			}
+
+			Instruction::Phi { dest, sources } =>
+			{
+				let block = self.block.unwrap();
+
+				if let Some(predecessors) = &self.predecessors
+				{
+					let mut covered: Vec<BlockId> = sources.iter().map(|(source, _)| *source).collect();
+					covered.sort();
+
+					let mut expected = predecessors[block.0 as usize].clone();
+					expected.sort();
+
+					if covered != expected
+					{
+						self.error(format!("phi `{}` sources do not match the predecessors of {}", dest, block));
+					}
+				}
+
+				if let Some(dest_type) = self.temp_type(*dest)
+				{
+					for (_, operand) in sources.iter()
+					{
+						self.expect_type("phi source", operand, &dest_type);
+					}
+				}
+			}
+		}
+	}
+
+	fn verify_terminator(&mut self, terminator: &Terminator)
+	{
+		match terminator
+		{
+			Terminator::Return(value) =>
+			{
+				let return_type = self.function.return_type.clone();
+				self.expect_type("returned value", value, &return_type);
+			}
+
+			Terminator::Jump(target) => self.expect_block(*target),
+
+			Terminator::Branch { condition, then_block, else_block } =>
+			{
+				self.expect_type("branch condition", condition, &VType::Boolean);
+				self.expect_block(*then_block);
+				self.expect_block(*else_block);
+			}
+
+			Terminator::Unreachable => {}
+		}
+	}
+
+	fn verify_function(&mut self)
+	{
+		let function = self.function;
+
+		for (index, param) in function.parameters.iter().enumerate()
+		{
+			if param.0 as usize != index
+			{
+				self.error(format!("parameter {} must be temporary `{}`, got `{}`", index, Temp(index as u32), param));
+			}
+		}
+
+		if function.parameters.len() > function.temps.len()
+		{
+			self.error(format!("{} parameters but only {} temporaries", function.parameters.len(), function.temps.len()));
+			return;
+		}
+
+		if !function.defined()
+		{
+			return;
+		}
+
+		let in_range = function.blocks
+			.iter()
+			.flat_map(|block| block.terminator.successors())
+			.all(|target| (target.0 as usize) < function.blocks.len());
+
+		if in_range
+		{
+			self.predecessors = Some(function.predecessors());
+		}
+
+		for (index, block) in function.blocks.iter().enumerate()
+		{
+			self.block = Some(BlockId(index as u32));
+
+			let mut leading = true;
+
+			for instruction in block.instructions.iter()
+			{
+				if instruction.is_phi() && !leading
+				{
+					self.error("phi after a non-phi instruction".to_string());
+				}
+
+				leading &= instruction.is_phi();
+				self.verify_instruction(instruction);
+			}
+
+			self.verify_terminator(&block.terminator);
+		}
+
+		self.block = None;
+
+		if self.predecessors.as_ref().is_some_and(|predecessors| !predecessors[0].is_empty())
+		{
+			self.error("the entry block must not have predecessors".to_string());
+		}
+	}
+}
+
+pub fn verify(module: &Module) -> Result<(), Vec<VerifyError>>
+{
+	let mut errors = Vec::new();
+	let mut names = HashSet::new();
+
+	for function in module.functions.iter()
+	{
+		let mut verifier = Verifier
+		{
+			module,
+			function,
+			block: None,
+			errors: Vec::new(),
+			predecessors: None
+		};
+
+		if !names.insert(function.name.clone())
+		{
+			verifier.error("function is defined more than once".to_string());
+		}
+
+		verifier.verify_function();
+		errors.extend(verifier.errors);
+	}
+
+	if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+// On top of `verify`, checks that every temporary is defined once and that every
+// use is dominated by its definition. Phi sources are used at the end of their block.
+pub fn verify_ssa(module: &Module) -> Result<(), Vec<VerifyError>>
+{
+	verify(module)?;
+
+	let mut errors = Vec::new();
+
+	for function in module.functions.iter().filter(|function| function.defined())
+	{
+		let mut verifier = Verifier
+		{
+			module,
+			function,
+			block: None,
+			errors: Vec::new(),
+			predecessors: None
+		};
+
+		let dominators = Dominators::compute(function);
+
+		// Definition site of every temporary, parameters are defined before the entry block
+		let mut definitions: HashMap<Temp, (BlockId, usize)> = HashMap::new();
+
+		for param in function.parameters.iter()
+		{
+			definitions.insert(*param, (BlockId(0), 0));
+		}
+
+		for (index, block) in function.blocks.iter().enumerate()
+		{
+			verifier.block = Some(BlockId(index as u32));
+
+			for (position, instruction) in block.instructions.iter().enumerate()
+			{
+				if let Some(dest) = instruction.dest()
+					&& definitions.insert(dest, (BlockId(index as u32), position + 1)).is_some()
+				{
+					verifier.error(format!("temporary `{}` is defined more than once", dest));
+				}
+			}
+		}
+
+		let dominated = |temp: &Temp, block: BlockId, position: usize| -> bool
+		{
+			match definitions.get(temp)
+			{
+				Some((def_block, def_position)) if *def_block == block => *def_position <= position,
+				Some((def_block, _)) => dominators.dominates(*def_block, block),
+				None => false
+			}
+		};
+
+		for (index, block) in function.blocks.iter().enumerate()
+		{
+			let id = BlockId(index as u32);
+			verifier.block = Some(id);
+
+			for (position, instruction) in block.instructions.iter().enumerate()
+			{
+				let uses: Vec<(Operand, BlockId, usize)> = match instruction
+				{
+					Instruction::Phi { sources, .. } => sources
+						.iter()
+						.map(|(source, operand)| (*operand, *source, usize::MAX))
+						.collect(),
+
+					_ => instruction.operands().into_iter().map(|operand| (operand, id, position)).collect()
+				};
+
+				for (operand, block, position) in uses
+				{
+					if let Operand::Temp(temp) = operand
+						&& !dominated(&temp, block, position)
+					{
+						verifier.error(format!("use of `{}` is not dominated by its definition", temp));
+					}
+				}
+			}
+
+			for operand in block.terminator.operands()
+			{
+				if let Operand::Temp(temp) = operand
+					&& !dominated(&temp, id, usize::MAX)
+				{
+					verifier.error(format!("use of `{}` is not dominated by its definition", temp));
+				}
+			}
+		}
+
+		errors.extend(verifier.errors);
+	}
+
+	if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
@@ -1,6 +1,14 @@
 pub mod data;
 pub mod ast;
 pub mod parser;
+pub mod interp;
+pub mod bytecode;
+pub mod codegen;
+pub mod ir;
+pub mod fmt;
+pub mod cst;
+pub mod repl;
+pub mod lsp;
 
 #[cfg(test)]
 mod tests
@@ -10,6 +18,15 @@ mod tests
 	use crate::data::vtype::*;
 
 	use crate::parser::*;
+	use crate::parser::render::*;
+
+	use crate::interp::*;
+
+	use crate::bytecode::{
+		compiler::compile,
+		disasm::disassemble,
+		vm::Vm
+	};
 
 	#[test]
 	fn function_define()
@@ -71,4 +88,2555 @@ mod tests
 
 		assert_ne!(errors.len(), 0);
 	}
+
+	fn run_source(source: &str) -> (Result<Value, RuntimeError>, String)
+	{
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let mut out = Vec::new();
+		let result = run(&root, &mut out);
+
+		(result, String::from_utf8(out).unwrap())
+	}
+
+	#[test]
+	fn interp_call_frames()
+	{
+		let source = "
+			function square(x int) int
+			{
+				let y int = x * x;
+				return y;
+			}
+
+			function main() int
+			{
+				let x int = 3;
+				print invoke square(x + 1);
+				print x;
+				set x = invoke square(x) - 2;
+				return x;
+			}
+		";
+
+		let (result, out) = run_source(source);
+
+		assert_eq!(result.unwrap(), Value::Integer(7));
+		assert_eq!(out, "16\n3\n");
+	}
+
+	#[test]
+	fn interp_division_by_zero()
+	{
+		let source = "
+			function main() void
+			{
+				let zero int = 0;
+				print 1 / zero;
+			}
+		";
+
+		let (result, _) = run_source(source);
+
+		assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+	}
+
+	#[test]
+	fn interp_missing_main()
+	{
+		let source = "
+			function helper() void
+			{
+				print true;
+			}
+		";
+
+		let (result, out) = run_source(source);
+
+		assert!(matches!(result, Err(RuntimeError::MissingMain)));
+		assert_eq!(out, "");
+	}
+
+	#[test]
+	fn interp_stack_overflow()
+	{
+		let source = "
+			function forever(x int) int
+			{
+				return invoke forever(x + 1);
+			}
+
+			function main() int
+			{
+				return invoke forever(0);
+			}
+		";
+
+		let (result, _) = with_stack(|| run_source(source));
+
+		assert!(matches!(result, Err(RuntimeError::StackOverflow(DEFAULT_MAX_DEPTH))));
+
+		// As deep as the other backends go without running out of host stack
+		let source = "
+			function sum(n int) int
+			{
+				if n == 0
+				{
+					return 0;
+				}
+
+				return n + invoke sum(n - 1);
+			}
+
+			function main() int
+			{
+				return invoke sum(300);
+			}
+		";
+
+		let (result, _) = with_stack(|| run_source(source));
+
+		assert_eq!(result.unwrap(), Value::Integer(45150));
+	}
+
+	#[test]
+	fn if_else_chain()
+	{
+		let source = "
+			function sign(x int) int
+			{
+				if x > 0
+				{
+					return 1;
+				}
+				else if x == 0
+				{
+					return 0;
+				}
+				else
+				{
+					return 0 - 1;
+				}
+			}
+
+			function main() void
+			{
+				print invoke sign(5);
+				print invoke sign(0);
+				print invoke sign(0 - 5);
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let func_define_statement = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let if_statement = &func_define_statement.body().statements()[0];
+
+		assert_eq!(if_statement.stype(), StatementType::If);
+
+		let if_statement = if_statement.as_statement::<IfStatement>().unwrap();
+		let else_body = if_statement.else_body().as_ref().unwrap();
+
+		assert_eq!(else_body.stype(), StatementType::If);
+
+		let (result, out) = run_source(source);
+
+		assert!(result.is_ok());
+		assert_eq!(out, "1\n0\n-1\n");
+	}
+
+	#[test]
+	fn if_condition_type_err()
+	{
+		let source = "
+			function main() void
+			{
+				if 1 + 2
+				{
+					print 3;
+				}
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 1);
+		assert!(errors[0].message.contains("`if` statement"));
+	}
+
+	#[test]
+	fn while_break_continue()
+	{
+		let source = "
+			function main() int
+			{
+				let i int = 0;
+				let sum int = 0;
+
+				while true
+				{
+					set i = i + 1;
+
+					if i > 10
+					{
+						break;
+					}
+
+					if i == 5
+					{
+						continue;
+					}
+
+					set sum = sum + i;
+				}
+
+				return sum;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let func_define_statement = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+
+		assert_eq!(func_define_statement.body().statements()[2].stype(), StatementType::While);
+
+		let (result, _) = run_source(source);
+
+		assert_eq!(result.unwrap(), Value::Integer(50));
+	}
+
+	#[test]
+	fn break_outside_loop_err()
+	{
+		let source = "
+			function main() void
+			{
+				if true
+				{
+					break;
+				}
+
+				continue;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 2);
+		assert_eq!(errors[0].message, "`break` statement is not allowed here");
+		assert_eq!(errors[1].message, "`continue` statement is not allowed here");
+	}
+
+	#[test]
+	fn operator_type_err()
+	{
+		let source = "
+			function main() void
+			{
+				print true + 1;
+				print 1 and 2;
+				print 1 == false;
+				print 1 < 2 or 2 == 2;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 3);
+
+		assert_eq!(errors[0].line, 4);
+		assert_eq!(errors[0].column_begin, 16);
+		assert_eq!(errors[0].message, "mismatched operand types for operator `Add`, expected `Integer`, got `Boolean` and `Integer`");
+
+		assert_eq!(errors[1].line, 5);
+		assert_eq!(errors[1].column_begin, 13);
+
+		assert_eq!(errors[2].line, 6);
+		assert_eq!(errors[2].column_begin, 13);
+	}
+
+	#[test]
+	fn missing_operand_err()
+	{
+		let source = "
+			function main() void
+			{
+				print - 5;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_ne!(errors.len(), 0);
+		assert_eq!(errors[0].message, "expected an operand on both sides of operator `Sub`");
+	}
+
+	#[test]
+	fn return_type_err()
+	{
+		let source = "
+			function flag() bool
+			{
+				return 5;
+			}
+
+			function nothing() void
+			{
+				return 5;
+			}
+
+			function bare() int
+			{
+				return;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 3);
+		assert_eq!(errors[0].message, "mismatched return type in function `flag`, expected `Boolean`, got `Integer`");
+		assert_eq!(errors[0].line, 4);
+		assert_eq!(errors[1].message, "`return` with a value in function `nothing` with return type `Void`");
+		assert_eq!(errors[2].message, "`return` without a value in function `bare` with return type `Integer`");
+	}
+
+	#[test]
+	fn missing_return_err()
+	{
+		let source = "
+			function partial(x int) int
+			{
+				if x > 0
+				{
+					return 1;
+				}
+			}
+
+			function total(x int) int
+			{
+				if x > 0
+				{
+					return 1;
+				}
+				else
+				{
+					return 0;
+				}
+			}
+
+			function forever(x int) int
+			{
+				while true
+				{
+					if x > 10
+					{
+						return x;
+					}
+
+					set x = x + 1;
+				}
+			}
+
+			function escapes(x int) int
+			{
+				while true
+				{
+					break;
+				}
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 2);
+		assert_eq!(errors[0].message, "function `partial` with return type `Integer` may end without returning a value");
+		assert_eq!(errors[0].line, 2);
+		assert_eq!(errors[1].message, "function `escapes` with return type `Integer` may end without returning a value");
+	}
+
+	#[test]
+	fn argument_type_err()
+	{
+		let source = "
+			function square(x int) int
+			{
+				return x * x;
+			}
+
+			function log(x int) void
+			{
+				print x;
+			}
+
+			function main() void
+			{
+				print invoke square(true);
+				print invoke square(2) + invoke log(3);
+				print invoke log(1) == invoke log(2);
+				express invoke log(4);
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 3);
+
+		assert_eq!(errors[0].message, "mismatched type for argument 1 of `square`, expected `Integer`, got `Boolean`");
+		assert_eq!(errors[0].line, 14);
+		assert_eq!(errors[0].column_begin, 25);
+
+		assert_eq!(errors[1].message, "value of type `Void` cannot be used as an operand of operator `Add`");
+		assert_eq!(errors[2].message, "value of type `Void` cannot be used as an operand of operator `Eq`");
+	}
+
+	#[test]
+	fn multi_parameter_functions()
+	{
+		let source = "
+			function add(a int, b int) int;
+
+			function pick(flag bool, a int, b int) int
+			{
+				if flag
+				{
+					return a;
+				}
+
+				return b;
+			}
+
+			function main() void
+			{
+				print invoke add(1, invoke pick(false, 10, invoke add(2, 3)));
+			}
+
+			function add(a int, b int) int
+			{
+				return a + b;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let func_declare_statement = root.statements[0].as_statement::<FunctionDeclareStatement>().unwrap();
+		let params = func_declare_statement.signature().parameters();
+
+		assert_eq!(params.len(), 2);
+		assert_eq!(params[1].vtype(), VType::Integer);
+
+		let func_define_statement = root.statements[1].as_statement::<FunctionDefineStatement>().unwrap();
+		let params = func_define_statement.signature().parameters();
+
+		assert_eq!(params.len(), 3);
+		assert_eq!(params[0].vtype(), VType::Boolean);
+
+		let (result, out) = run_source(source);
+
+		assert!(result.is_ok());
+		assert_eq!(out, "6\n");
+	}
+
+	#[test]
+	fn multi_parameter_err()
+	{
+		let source = "
+			function add(a int, b int) int;
+			function add(a int, b bool) int;
+
+			function main() void
+			{
+				print invoke add(1, true);
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 2);
+		assert_eq!(errors[0].message, "conflicting signature for function `add`, previously declared with a different signature");
+		assert_eq!(errors[1].message, "mismatched type for argument 2 of `add`, expected `Integer`, got `Boolean`");
+
+		let source = "
+			function add(a int, b int) int;
+
+			function main() void
+			{
+				print invoke add(, 1);
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors[0].message, "expected an argument expression before `Comma`");
+		assert_eq!(errors[0].column_begin, 22);
+	}
+
+	#[test]
+	fn function_redefine_err()
+	{
+		// Declarations may repeat around the one definition
+		let source = "
+			function f() int;
+			function f() int { return 1; }
+			function f() int;
+
+			function main() void
+			{
+				print invoke f();
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let source = "
+			function f() int { return 1; }
+			function f() int { return 2; }
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].message, "function `f` is already defined");
+		assert_eq!(errors[0].line, 3);
+		assert_eq!(errors[0].column_begin, 13);
+	}
+
+	#[test]
+	fn comments()
+	{
+		let source = "
+			// Licensed under the terms in LICENSE
+			function main() void // entry point
+			{
+				/* a block comment
+				   /* that nests */
+				   across lines */ print 1 / 2; /* trailing */
+				print true + 1;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(root.statements.len(), 1);
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].line, 8);
+		assert_eq!(errors[0].column_begin, 16);
+
+		let func_define_statement = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let print_statement = &func_define_statement.body().statements()[0];
+
+		let first_token = print_statement.unparse()[0].info();
+
+		assert_eq!(first_token.line, 7);
+		assert_eq!(first_token.column_begin, 24);
+	}
+
+	#[test]
+	fn unterminated_comment_err()
+	{
+		let source = "
+			function main() void
+			{
+				/* /* */
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_ne!(errors.len(), 0);
+		assert_eq!(errors[0].message, "unterminated block comment");
+		assert_eq!(errors[0].line, 4);
+		assert_eq!(errors[0].column_begin, 5);
+		assert_eq!(errors[0].column_end, 6);
+	}
+
+	#[test]
+	fn lexer_diagnostics()
+	{
+		let source = "
+			function main() void
+			{
+				let x int = 1;
+				let y int = 2;
+				print x $ y;
+				print 99999999999;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors[0].message, "unknown character `$`");
+		assert_eq!(errors[0].line, 6);
+		assert_eq!(errors[0].column_begin, 13);
+		assert_eq!(errors[0].column_end, 13);
+		assert_eq!(errors[0].context_line, "\t\t\t\tprint x $ y;");
+
+		assert_eq!(errors[1].message, "integer literal `99999999999` does not fit in type `Integer`");
+		assert_eq!(errors[1].line, 7);
+		assert_eq!(errors[1].column_begin, 11);
+		assert_eq!(errors[1].column_end, 21);
+	}
+
+	#[test]
+	fn render_parser_error()
+	{
+		let source = "function main() void\n{\n\tprint true + 1;\n}\n";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 1);
+		assert_eq!(errors[0].to_string(), format!("3:13: {}", errors[0].message));
+
+		let rendered = render_error("main.faren", &errors[0], false);
+
+		let expected = format!(
+			"error: {}\n --> main.faren:3:13\n  |\n3 |     print true + 1;\n  |                ^\n",
+			errors[0].message
+		);
+
+		assert_eq!(rendered, expected);
+
+		let coloured = render_error("main.faren", &errors[0], true);
+
+		assert!(coloured.contains("\x1b[1;31merror\x1b[0m"));
+		assert!(coloured.contains("\x1b[1;31m^\x1b[0m"));
+	}
+
+	#[test]
+	fn bytecode_matches_interp()
+	{
+		let source = "
+			function fib(n int) int
+			{
+				if n < 2
+				{
+					return n;
+				}
+
+				return invoke fib(n - 1) + invoke fib(n - 2);
+			}
+
+			function check(x int) bool
+			{
+				print x;
+				return x > 0;
+			}
+
+			function main() int
+			{
+				let i int = 0;
+
+				while i < 10
+				{
+					set i = i + 1;
+
+					if i == 3 or i == 5
+					{
+						continue;
+					}
+
+					if invoke check(0) and invoke check(i)
+					{
+						break;
+					}
+
+					print invoke fib(i);
+				}
+
+				return i * 100 / 7;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let (interp_result, interp_out) = run_source(source);
+
+		let program = compile(&root);
+
+		let mut vm_out = Vec::new();
+		let vm_result = crate::bytecode::vm::run(&program, &mut vm_out);
+
+		assert_eq!(vm_result.unwrap(), interp_result.unwrap());
+		assert_eq!(String::from_utf8(vm_out).unwrap(), interp_out);
+	}
+
+	#[test]
+	fn bytecode_deep_recursion_and_traps()
+	{
+		let source = "
+			function depth(n int) int
+			{
+				if n == 0
+				{
+					return 0;
+				}
+
+				return 1 + invoke depth(n - 1);
+			}
+
+			function divide(a int, b int) int
+			{
+				return a / b;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let program = compile(&root);
+		let mut vm = Vm::new(&program, Vec::new());
+
+		assert_eq!(vm.call("depth", vec![Value::Integer(20000)]).unwrap(), Value::Integer(20000));
+		assert!(matches!(vm.call("divide", vec![Value::Integer(1), Value::Integer(0)]), Err(RuntimeError::DivisionByZero)));
+		assert!(matches!(vm.run(), Err(RuntimeError::MissingMain)));
+
+		vm.set_max_frames(100);
+
+		assert!(matches!(vm.call("depth", vec![Value::Integer(200)]), Err(RuntimeError::StackOverflow(100))));
+		assert_eq!(vm.call("divide", vec![Value::Integer(9), Value::Integer(2)]).unwrap(), Value::Integer(4));
+	}
+
+	#[test]
+	fn bytecode_disassemble()
+	{
+		let source = "
+			function square(x int) int;
+
+			function main() void
+			{
+				print invoke square(3);
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let listing = disassemble(&compile(&root));
+
+		let expected = "\
+function square (arity 1, declared only)
+
+function main (arity 0, slots 0)
+  0000  push.i 3
+  0001  call 0          ; square
+  0002  print
+  0003  push.v
+  0004  ret
+";
+
+		assert_eq!(listing, expected);
+	}
+
+	#[test]
+	fn c_backend()
+	{
+		let source = "
+			function gcd(a int, b int) int;
+
+			function main() int
+			{
+				let i int = 1;
+
+				while i <= 5
+				{
+					print invoke gcd(i * 12, 18);
+					set i = i + 1;
+				}
+
+				print i > 5 and true;
+				print 7 / (0 - 2);
+
+				return invoke gcd(0 - 7, 0) + 2147483647 + 10;
+			}
+
+			function gcd(a int, b int) int
+			{
+				if b == 0
+				{
+					return a;
+				}
+				else
+				{
+					return invoke gcd(b, a - a / b * b);
+				}
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let c_source = crate::codegen::c::generate(&root);
+
+		assert!(c_source.contains("int32_t faren_gcd(int32_t v0, int32_t v1);"));
+		assert!(c_source.contains("int32_t faren_main(void)"));
+		assert!(c_source.contains("\t\tprintf(\"%\" PRId32 \"\\n\", faren_gcd(mul_i32(v2, 12), 18));"));
+		assert!(c_source.contains("\tputs(((v2 > 5) && true) ? \"true\" : \"false\");"));
+
+		// Compiling needs a host toolchain, skip quietly where there is none
+		let dir = std::env::temp_dir().join(format!("faren-c-backend-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+
+		std::fs::write(dir.join("main.c"), &c_source).unwrap();
+
+		let Ok(status) = std::process::Command::new("cc")
+			.args(["-std=c99", "-Wall", "-Werror", "-o"])
+			.arg(dir.join("main"))
+			.arg(dir.join("main.c"))
+			.status() else
+		{
+			return;
+		};
+
+		assert!(status.success());
+
+		let output = std::process::Command::new(dir.join("main")).output().unwrap();
+		let (result, interp_out) = run_source(source);
+
+		assert_eq!(String::from_utf8(output.stdout).unwrap(), interp_out);
+		let Value::Integer(exit_value) = result.unwrap() else
+		{
+			panic!("`main` should return an integer");
+		};
+
+		assert_eq!(exit_value, -2147483646);
+		assert_eq!(output.status.code().unwrap(), exit_value as u8 as i32);
+
+		// A program without arithmetic leaves every helper unused, which must not warn
+		let (root, errors) = parse_root("function main() int { return 1 + 2; }".into());
+
+		assert_eq!(errors.len(), 0);
+
+		std::fs::write(dir.join("unused.c"), crate::codegen::c::generate(&root)).unwrap();
+
+		let status = std::process::Command::new("cc")
+			.args(["-std=c99", "-Wall", "-Werror", "-o"])
+			.arg(dir.join("unused"))
+			.arg(dir.join("unused.c"))
+			.status()
+			.unwrap();
+
+		assert!(status.success());
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn wat_backend()
+	{
+		let source = "
+			function clock() int;
+
+			function square(x int) int
+			{
+				return x * x;
+			}
+
+			function main() void
+			{
+				let i int = 0;
+
+				while true
+				{
+					set i = i + 1;
+
+					if i == 2
+					{
+						continue;
+					}
+
+					if i > 3 or invoke clock() < 0
+					{
+						break;
+					}
+
+					let s int = invoke square(i);
+					print s / 2;
+				}
+
+				print i > 3 and true;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let wat = crate::codegen::wat::generate(&root);
+
+		// Imports have to come before any function definition
+		let imports = [
+			"\t(import \"env\" \"print_bool\" (func $print_bool (param i32)))\n",
+			"\t(import \"env\" \"print_int\" (func $print_int (param i32)))\n",
+			"\t(import \"env\" \"clock\" (func $fn_clock (result i32)))\n"
+		];
+
+		assert!(wat.starts_with(&format!("(module\n{}\t(func $faren_div", imports.concat())));
+		assert!(!wat.contains("print_void"));
+
+		assert!(wat.contains("\t(func $fn_square (export \"square\") (param $v0 i32) (result i32)\n\t\tlocal.get $v0\n\t\tlocal.get $v0\n\t\ti32.mul\n\t\treturn\n\t\tunreachable\n\t)\n"));
+		assert!(wat.contains("\t(func $fn_main (export \"main\")\n\t\t(local $v1 i32)\n\t\t(local $v2 i32)\n"));
+
+		assert!(wat.contains("\t\tblock $break0\n\t\t\tloop $continue0\n\t\t\t\ti32.const 1\n\t\t\t\ti32.eqz\n\t\t\t\tbr_if $break0\n"));
+		assert!(wat.contains("\t\t\t\t\tbr $continue0\n"));
+		assert!(wat.contains("\t\t\t\t\tbr $break0\n"));
+		assert!(wat.contains("\t\t\t\tcall $faren_div\n\t\t\t\tcall $print_int\n"));
+		assert!(wat.contains("\t\tif (result i32)\n\t\t\ti32.const 1\n\t\telse\n\t\t\ti32.const 0\n\t\tend\n\t\tcall $print_bool\n"));
+		assert!(wat.ends_with("\t)\n)\n"));
+	}
+
+	#[test]
+	fn x86_64_backend()
+	{
+		let source = "
+			function sum(a int, b int, c int, d int, e int, f int, g int, h int) int
+			{
+				return a + b + c + d + e + f + g * 10 + h * 100;
+			}
+
+			function gcd(a int, b int) int
+			{
+				if b == 0
+				{
+					return a;
+				}
+
+				return invoke gcd(b, a - a / b * b);
+			}
+
+			function main() int
+			{
+				let i int = 0;
+
+				while true
+				{
+					set i = i + 1;
+
+					if i == 2
+					{
+						continue;
+					}
+
+					if i > 4 or i < 0
+					{
+						break;
+					}
+
+					print 1 + invoke sum(i, 2, 3, 4, 5, 6, invoke gcd(i * 12, 18), i);
+				}
+
+				print i > 3 and i != 4;
+				let min int = 0 - 2147483647 - 1;
+				let big int = 2000000000;
+
+				print min / (0 - 1);
+				print big * 3;
+
+				return i * 60;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let assembly = crate::codegen::x86_64::generate(&root);
+
+		assert!(assembly.contains("\t.globl faren_sum\n\t.type faren_sum, @function\nfaren_sum:\n\tpushq %rbp\n\tmovq %rsp, %rbp\n\tsubq $64, %rsp\n"));
+		assert!(assembly.contains("\tmovl %r9d, -48(%rbp)\n\tmovl 16(%rbp), %eax\n\tmovl %eax, -56(%rbp)\n\tmovl 24(%rbp), %eax\n\tmovl %eax, -64(%rbp)\n"));
+		assert!(assembly.contains("\tcall faren_sum\n"));
+		assert!(assembly.contains("\tcall printf@PLT\n"));
+		assert!(assembly.contains("\nmain:\n\tsubq $8, %rsp\n\tcall faren_main\n\taddq $8, %rsp\n\tret\n"));
+
+		// Assembling needs a host toolchain, skip quietly where there is none
+		let dir = std::env::temp_dir().join(format!("faren-x86-64-backend-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+
+		std::fs::write(dir.join("main.s"), &assembly).unwrap();
+
+		let Ok(status) = std::process::Command::new("cc")
+			.arg("-o")
+			.arg(dir.join("main"))
+			.arg(dir.join("main.s"))
+			.status() else
+		{
+			return;
+		};
+
+		assert!(status.success());
+
+		let output = std::process::Command::new(dir.join("main")).output().unwrap();
+		let (result, interp_out) = run_source(source);
+
+		assert_eq!(String::from_utf8(output.stdout).unwrap(), interp_out);
+
+		let Value::Integer(exit_value) = result.unwrap() else
+		{
+			panic!("`main` should return an integer");
+		};
+
+		assert_eq!(exit_value, 300);
+		assert_eq!(output.status.code().unwrap(), exit_value as u8 as i32);
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+	#[test]
+	fn ir_lower_dump_verify()
+	{
+		use crate::ir::*;
+
+		let source = "
+			function clock() int;
+
+			function count(limit int) int
+			{
+				let i int = 0;
+
+				while i < limit and invoke clock() > 0
+				{
+					set i = i + 1;
+
+					if i == 3
+					{
+						break;
+					}
+				}
+
+				return i;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let mut module = lower::lower(&root);
+
+		assert_eq!(verify::verify(&module), Ok(()));
+
+		assert_eq!(dump::dump(&module), "\
+function clock() int;
+
+function count(%0 int) int
+bb0:
+  %1: int = copy 0
+  jump bb1
+bb1:
+  %2: bool = lt %1, %0
+  branch %2, bb2, bb3
+bb2:
+  %4: int = call clock()
+  %5: bool = gt %4, 0
+  %3: bool = copy %5
+  jump bb4
+bb3:
+  %3: bool = copy false
+  jump bb4
+bb4:
+  branch %3, bb5, bb6
+bb5:
+  %6: int = add %1, 1
+  %1: int = copy %6
+  %7: bool = eq %1, 3
+  branch %7, bb7, bb8
+bb6:
+  return %1
+bb7:
+  jump bb6
+bb8:
+  jump bb1
+");
+
+		let count = &mut module.functions[1];
+
+		count.blocks[1].terminator = Terminator::Branch
+		{
+			condition: Operand::Temp(Temp(1)),
+			then_block: BlockId(2),
+			else_block: BlockId(9)
+		};
+
+		count.blocks[2].instructions.push(Instruction::Call { dest: Temp(4), function: "clock".to_string(), arguments: vec![Operand::Temp(Temp(0))] });
+
+		let errors = verify::verify(&module).unwrap_err();
+
+		assert_eq!(errors.len(), 3);
+		assert_eq!(errors[0].to_string(), "function `count`, bb1: mismatched type for branch condition `%1`, expected `Boolean`, got `Integer`");
+		assert_eq!(errors[1].to_string(), "function `count`, bb1: jump to non-existent block `bb9`");
+		assert_eq!(errors[2].to_string(), "function `count`, bb2: mismatched argument length for `clock`, expected 0, got 1");
+	}
+
+	#[test]
+	fn ir_ssa_optimise()
+	{
+		use crate::ir::*;
+		use crate::ir::opt::*;
+
+		let source = "
+			function kernel(n int) int
+			{
+				let three int = 3;
+				let scale int = 2 * three + 4;
+				let sum int = 0;
+				let i int = 0;
+
+				while i < n
+				{
+					set sum = sum + i * scale + i * scale;
+					set i = i + 1;
+				}
+
+				if scale > 5
+				{
+					return sum;
+				}
+
+				return 0;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let lowered = lower::lower(&root);
+
+		// Reassigned variables lose their single temporary and get merged at the loop header
+		let mut ssa_form = lowered.clone();
+		ssa::construct(&mut ssa_form.functions[0]);
+
+		assert!(verify::verify_ssa(&lowered).is_err());
+		assert_eq!(verify::verify_ssa(&ssa_form), Ok(()));
+
+		let dumped = dump::dump(&ssa_form);
+
+		assert!(dumped.contains("bb1:\n  %16: int = phi [bb0: %14], [bb2: %18]\n  %17: int = phi [bb0: %15], [bb2: %19]\n"));
+
+		let mut optimised = lowered.clone();
+		PassManager::new().run(&mut optimised);
+
+		assert_eq!(verify::verify_ssa(&optimised), Ok(()));
+
+		assert_eq!(dump::dump(&optimised), "\
+function kernel(%0 int) int
+bb0:
+  jump bb1
+bb1:
+  %16: int = phi [bb0: 0], [bb2: %11]
+  %17: int = phi [bb0: 0], [bb2: %12]
+  %7: bool = lt %17, %0
+  branch %7, bb2, bb3
+bb2:
+  %8: int = mul %17, 10
+  %9: int = add %16, %8
+  %11: int = add %9, %8
+  %12: int = add %17, 1
+  jump bb1
+bb3:
+  jump bb4
+bb4:
+  return %16
+");
+
+		let mut without_constants = lowered.clone();
+
+		let mut manager = PassManager::new();
+		manager.set_enabled(Pass::ConstantPropagation, false);
+		manager.run(&mut without_constants);
+
+		let dumped = dump::dump(&without_constants);
+
+		assert!(dumped.contains("  %2: int = mul 2, 3\n  %3: int = add %2, 4\n"));
+		assert!(dumped.contains("  %8: int = mul %17, %3\n  %9: int = add %16, %8\n  %11: int = add %9, %8\n"));
+		assert!(dumped.contains("  branch %13, bb4, bb5\n"));
+
+		assert_eq!(fold(BinaryOperation::Divide, Value::Integer(i32::MIN), Value::Integer(-1)), Some(Value::Integer(i32::MIN)));
+		assert_eq!(fold(BinaryOperation::Divide, Value::Integer(1), Value::Integer(0)), None);
+	}
+
+	#[test]
+	fn constant_folding()
+	{
+		use crate::ast::expression::LiteralExpression;
+		use crate::ast::literal::{BooleanLiteral, IntegerLiteral};
+
+		let source = "
+			function main() int
+			{
+				let x int = (2 + 3) * 4 - 10 / 3;
+				print true == (1 != 1);
+				print 0 - 2147483647 - 1;
+
+				return x * 2;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let main = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let body = main.body().statements();
+
+		let declared = body[0].as_statement::<DeclareStatement>().unwrap().expression();
+		let literal = declared.as_expression::<LiteralExpression>().unwrap().literal();
+		assert_eq!(literal.as_literal::<IntegerLiteral>().unwrap().value, 17);
+
+		let printed = body[1].as_statement::<PrintStatement>().unwrap().expression();
+		let literal = printed.as_expression::<LiteralExpression>().unwrap().literal();
+		assert!(!literal.as_literal::<BooleanLiteral>().unwrap().value);
+
+		let printed = body[2].as_statement::<PrintStatement>().unwrap().expression();
+		let literal = printed.as_expression::<LiteralExpression>().unwrap().literal();
+		assert_eq!(literal.as_literal::<IntegerLiteral>().unwrap().value, i32::MIN);
+
+		// Only the constant part of `x * 2` could fold, the variable stays
+		assert!(body[3].as_statement::<FunctionReturnStatement>().unwrap().expression().as_ref().unwrap().as_expression::<LiteralExpression>().is_none());
+
+		let source = "
+			function main() void
+			{
+				print 2000000000 * 3;
+				print 7 / (3 - 3);
+				print 0 - 2147483647 - 2;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 3);
+
+		assert_eq!(errors[0].message, "arithmetic operation `Multiply` overflows type `Integer` in a constant expression");
+		assert_eq!(errors[0].line, 4);
+		assert_eq!(errors[0].column_begin, 22);
+
+		assert_eq!(errors[1].message, "division by zero in a constant expression");
+		assert_eq!(errors[1].line, 5);
+		assert_eq!(errors[1].column_begin, 13);
+
+		assert_eq!(errors[2].message, "arithmetic operation `Subtract` overflows type `Integer` in a constant expression");
+
+		// Overflow still folds into the wrapped value, the division by zero is left for the runtime
+		let main = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let body = main.body().statements();
+
+		let printed = body[0].as_statement::<PrintStatement>().unwrap().expression();
+		let literal = printed.as_expression::<LiteralExpression>().unwrap().literal();
+		assert_eq!(literal.as_literal::<IntegerLiteral>().unwrap().value, 2000000000i32.wrapping_mul(3));
+
+		let printed = body[1].as_statement::<PrintStatement>().unwrap().expression();
+		assert!(printed.as_expression::<LiteralExpression>().is_none());
+	}
+
+	#[test]
+	fn ast_kinds()
+	{
+		use crate::ast::expression::*;
+		use crate::ast::literal::*;
+
+		let source = "
+			function gcd(a int, b int) int;
+
+			function main() int
+			{
+				let x int = 12;
+
+				while x > 0 and invoke gcd(x, 8) != 4
+				{
+					set x = x - 1;
+
+					if x == 3
+					{
+						break;
+					}
+					else
+					{
+						continue;
+					}
+				}
+
+				print x;
+				return x;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		fn count_expression(expression: &Expression) -> usize
+		{
+			1 + match expression.kind()
+			{
+				ExpressionKind::Literal(_) | ExpressionKind::Variable(_) => 0,
+
+				ExpressionKind::FunctionCall(call) => call.passed_arguments().iter().map(count_expression).sum(),
+
+				ExpressionKind::Arithmetic(arith_expr) => count_expression(arith_expr.left()) + count_expression(arith_expr.right()),
+				ExpressionKind::Comparison(cmp_expr) => count_expression(cmp_expr.left()) + count_expression(cmp_expr.right()),
+				ExpressionKind::Boolean(bool_expr) => count_expression(bool_expr.left()) + count_expression(bool_expr.right())
+			}
+		}
+
+		fn count_compound(compound: &CompoundStatement) -> usize
+		{
+			compound.statements().iter().map(count_statement).sum()
+		}
+
+		// Statements and expressions, without the compound statements themselves
+		fn count_statement(statement: &Statement) -> usize
+		{
+			let kind = StatementKind::from(statement);
+
+			assert_eq!(kind.stype(), statement.stype());
+
+			// Back and forth without losing the tokens
+			let converted = Statement::from(kind);
+
+			assert_eq!(converted.stype(), statement.stype());
+			assert_eq!(converted.unparse().len(), statement.unparse().len());
+
+			match kind
+			{
+				StatementKind::FunctionDefine(function) => 1 + count_compound(function.body()),
+				StatementKind::FunctionDeclare(_) => 1,
+				StatementKind::FunctionReturn(ret) => 1 + ret.expression().as_ref().map_or(0, count_expression),
+
+				StatementKind::Expression(expr_stmt) => 1 + count_expression(expr_stmt.expression()),
+
+				StatementKind::Compound(compound) => count_compound(compound),
+				StatementKind::Declare(declare) => 1 + count_expression(declare.expression()),
+				StatementKind::Assign(assign) => 1 + count_expression(assign.expression()),
+				StatementKind::Print(print) => 1 + count_expression(print.expression()),
+
+				StatementKind::If(if_stmt) =>
+				{
+					1 + count_expression(if_stmt.condition())
+						+ count_compound(if_stmt.then_body())
+						+ if_stmt.else_body().as_ref().map_or(0, count_statement)
+				}
+
+				StatementKind::While(while_stmt) => 1 + count_expression(while_stmt.condition()) + count_compound(while_stmt.body()),
+				StatementKind::Break(_) | StatementKind::Continue(_) => 1
+			}
+		}
+
+		let count: usize = root.statements.iter().map(count_statement).sum();
+
+		// 10 statements and 18 expressions
+		assert_eq!(count, 28);
+
+		let expression = Expression::new_literal(Default::default(), Literal::new_integer(7));
+
+		assert_eq!(expression.kind().etype(), ExpressionType::Literal);
+
+		// Taken out of the box and put back into one
+		let kind = OwnedExpressionKind::from(expression);
+
+		assert_eq!(kind.etype(), ExpressionType::Literal);
+
+		let OwnedExpressionKind::Literal(literal) = Expression::from(kind).into_kind() else
+		{
+			panic!("expected a literal expression");
+		};
+
+		assert_eq!(literal.literal().as_literal::<IntegerLiteral>().unwrap().value, 7);
+	}
+
+	#[test]
+	fn ast_visitor_and_folder()
+	{
+		use crate::ast::expression::*;
+		use crate::ast::literal::*;
+		use crate::ast::visit::*;
+
+		let source = "
+			function twice(n int) int
+			{
+				return n * 2;
+			}
+
+			function main() int
+			{
+				let x int = 1;
+
+				while x < 20
+				{
+					if x > 5
+					{
+						print invoke twice(x);
+					}
+					else
+					{
+						print x;
+					}
+
+					set x = x + 3;
+				}
+
+				return invoke twice(x + 1);
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		#[derive(Default)]
+		struct Collector
+		{
+			calls: Vec<String>,
+			variables: usize,
+			literals: usize,
+			statements: usize
+		}
+
+		impl Visitor for Collector
+		{
+			fn visit_statement(&mut self, statement: &Statement)
+			{
+				self.statements += 1;
+				walk_statement(self, statement);
+			}
+
+			fn visit_function_call(&mut self, function_call: &FunctionCallExpression)
+			{
+				self.calls.push(function_call.name());
+				walk_function_call(self, function_call);
+			}
+
+			fn visit_variable(&mut self, _variable: &VariableExpression)
+			{
+				self.variables += 1;
+			}
+
+			fn visit_literal(&mut self, _literal: &LiteralExpression)
+			{
+				self.literals += 1;
+			}
+		}
+
+		let mut collector = Collector::default();
+		collector.visit_root(&root);
+
+		assert_eq!(collector.calls, vec!["twice", "twice"]);
+		assert_eq!(collector.variables, 7);
+		assert_eq!(collector.literals, 6);
+
+		// The `else` body is the only compound statement reached through `visit_statement`
+		assert_eq!(collector.statements, 11);
+
+		// Adds one to every integer literal, including those nested in calls and branches
+		struct Increment;
+
+		impl Folder for Increment
+		{
+			fn fold_literal(&mut self, literal: LiteralExpression) -> Expression
+			{
+				match literal.literal().as_literal::<IntegerLiteral>()
+				{
+					Some(integer) => Expression::new_literal(literal.tokens().clone(), Literal::new_integer(integer.value + 1)),
+					None => Expression::from(OwnedExpressionKind::Literal(literal))
+				}
+			}
+		}
+
+		let folded = Increment.fold_root(root.clone());
+
+		let mut output = Vec::new();
+		let original = run(&root, &mut output).unwrap();
+		assert_eq!(String::from_utf8(output).unwrap(), "1\n4\n14\n20\n26\n32\n38\n");
+		assert_eq!(original, Value::Integer(46));
+
+		let mut output = Vec::new();
+		let result = run(&folded, &mut output).unwrap();
+		assert_eq!(String::from_utf8(output).unwrap(), "2\n6\n30\n42\n54\n");
+		assert_eq!(result, Value::Integer(72));
+	}
+
+	#[test]
+	fn ast_spans()
+	{
+		use crate::ast::expression::*;
+
+		let source = "
+			function main() int
+			{
+				let x int = (1 + 2) * 3;
+				/* héhé */ print (x - 1) * invoke main();
+				return x;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let function = &root.statements[0];
+		let span = function.span();
+
+		assert_eq!((span.line_begin, span.column_begin), (2, 4));
+		assert_eq!((span.line_end, span.column_end), (7, 4));
+		assert!(span.text(source).starts_with("function main() int\n"));
+		assert!(span.text(source).ends_with("return x;\n\t\t\t}"));
+
+		let body = function.as_statement::<FunctionDefineStatement>().unwrap().body().statements();
+
+		// A folded literal covers the expression it replaced
+		let declared = body[0].as_statement::<DeclareStatement>().unwrap().expression();
+		assert_eq!(declared.span().text(source), "(1 + 2) * 3");
+		assert_eq!(body[0].span().text(source), "let x int = (1 + 2) * 3;");
+
+		// Byte offsets stay exact after multi-byte characters
+		let printed = body[1].as_statement::<PrintStatement>().unwrap().expression();
+		assert_eq!(printed.span().text(source), "(x - 1) * invoke main()");
+
+		let arith_expr = printed.as_expression::<ArithmeticExpression>().unwrap();
+		let left = arith_expr.left().span();
+		let right = arith_expr.right().span();
+
+		assert_eq!(left.text(source), "x - 1");
+		assert_eq!((left.line_begin, left.column_begin, left.column_end), (5, 23, 27));
+		assert_eq!(right.text(source), "invoke main()");
+		assert_eq!((right.line_end, right.column_end), (5, 44));
+
+		let built = Expression::new_variable(Default::default(), crate::data::vtype::VType::Integer, 0);
+		assert!(built.span().is_empty());
+	}
+
+	#[test]
+	fn format_round_trip()
+	{
+		use crate::fmt::*;
+
+		let source = "
+			function gcd(a int, b int) int;
+			function main() int {
+				let x int = ((2 + 3)) * (4 - (1 - 1));
+				let ok bool = (x > 1 == true) or (false and x < 0) and (true);
+
+
+				while (x > 0) and invoke gcd(x, (8)) != 4 { set x = x - 1; if x == 3 { break; } else if x == 2 { continue; } else { print (x / (x / 2)); } }
+				return x - (x - 1) - 1 * (2 / 2);
+			}
+			function gcd(a int, b int) int
+			{
+				if b == 0 { return a; }
+				return invoke gcd(b, a - a / b * b);
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let formatted = format(&root, source, &FormatOptions::default());
+
+		assert_eq!(formatted, "\
+function gcd(a int, b int) int;
+
+function main() int
+{
+	let x int = (2 + 3) * (4 - (1 - 1));
+	let ok bool = x > 1 == true or (false and x < 0) and true;
+
+	while x > 0 and invoke gcd(x, 8) != 4
+	{
+		set x = x - 1;
+		if x == 3
+		{
+			break;
+		}
+		else if x == 2
+		{
+			continue;
+		}
+		else
+		{
+			print x / (x / 2);
+		}
+	}
+	return x - (x - 1) - 1 * (2 / 2);
+}
+
+function gcd(a int, b int) int
+{
+	if b == 0
+	{
+		return a;
+	}
+	return invoke gcd(b, a - a / b * b);
+}
+");
+
+		let (reparsed, errors) = parse_root(formatted.clone());
+
+		assert_eq!(errors.len(), 0);
+		assert_eq!(format(&reparsed, &formatted, &FormatOptions::default()), formatted);
+
+		let mut before = Vec::new();
+		let mut after = Vec::new();
+
+		assert_eq!(run(&root, &mut before).unwrap(), run(&reparsed, &mut after).unwrap());
+		assert_eq!(before, after);
+
+		let spaces = format(&reparsed, &formatted, &FormatOptions { indent: Indent::Spaces(4) });
+		assert!(spaces.contains("\n    while x > 0 and invoke gcd(x, 8) != 4\n    {\n        set x = x - 1;\n"));
+	}
+
+	#[test]
+	fn lossless_syntax_tree()
+	{
+		use crate::cst::*;
+		use crate::parser::lexer::*;
+
+		let sources = [
+			"",
+			"  \n\t// only a comment\n",
+			"function main() int // entry\r\n{\r\n\t/* nested /* block */ comment */ let x int = (1 + 2) * 3;\r\n\treturn x;\r\n}\r\n",
+			"let s int = 1 é 2; // unicode\n",
+			"print 99999999999 + true;\n",
+			"function f(a int, int { set = ; }} else while { /* unterminated",
+			"if x { print 1; } else if y { } else { break } express invoke f(1, , 2)"
+		];
+
+		for source in sources
+		{
+			let (tree, _) = parse(source);
+			assert_eq!(tree.to_string(), source);
+		}
+
+		let (tree, errors) = parse("function f(a int, int { set = ; }} else while { /* unterminated");
+		assert!(tree.root.has_errors());
+		assert_eq!(errors.len(), 1);
+
+		let source = "\
+// header
+function main() int
+{
+	let x int = 1 + 2 * 3; // trailing
+	return (x);
+}
+";
+
+		let (tree, errors) = parse(source);
+
+		assert_eq!(errors.len(), 0);
+		assert!(!tree.root.has_errors());
+		assert_eq!(tree.to_string(), source);
+
+		let function = tree.root.nodes().next().unwrap();
+		assert_eq!(function.kind, NodeKind::FunctionDefine);
+		assert_eq!(function.to_string(), source.trim_end());
+
+		let block = function.nodes().find(|node| node.kind == NodeKind::Block).unwrap();
+		let kinds: Vec<NodeKind> = block.nodes().map(|node| node.kind).collect();
+		assert_eq!(kinds, vec![NodeKind::Let, NodeKind::Return]);
+
+		// `1 + 2 * 3` groups the multiplication
+		let binary = block.nodes().next().unwrap().nodes().next().unwrap();
+		assert_eq!(binary.kind, NodeKind::Binary);
+		assert_eq!(binary.nodes().map(|node| node.kind).collect::<Vec<_>>(), vec![NodeKind::Literal, NodeKind::Binary]);
+		assert_eq!(binary.to_string(), "1 + 2 * 3");
+
+		let tokens = function.tokens();
+		assert_eq!(tokens[0].text, "function");
+		assert_eq!(tokens[0].leading.iter().map(|trivia| trivia.kind).collect::<Vec<_>>(), vec![TriviaKind::LineComment, TriviaKind::Newline]);
+
+		let semicolon = tokens.iter().find(|token| token.text == ";").unwrap();
+		assert_eq!(semicolon.trailing.iter().map(|trivia| trivia.text.as_str()).collect::<Vec<_>>(), vec![" ", "// trailing"]);
+
+		assert_eq!(tree.trailing.len(), 1);
+		assert_eq!(tree.trailing[0].kind, TriviaKind::Newline);
+	}
+
+	#[test]
+	fn ast_and_token_dumps()
+	{
+		use crate::ast::dump::*;
+		use crate::parser::lexer::*;
+
+		let source = "\
+function square(x int) int
+{
+	return x * x;
+}
+
+function main() int
+{
+	let y int = invoke square(7);
+	if y > 40 and true
+	{
+		print y;
+	}
+	return 0;
+}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+		assert_eq!(dump(&root), "\
+FunctionDefine square(#0 Integer) Integer
+  Compound
+    FunctionReturn
+      Arithmetic Multiply : Integer
+        Variable #0 : Integer
+        Variable #0 : Integer
+FunctionDefine main() Integer
+  Compound
+    Declare #1 Integer
+      FunctionCall square : Integer
+        Literal 7 : Integer
+    If
+      Boolean And : Boolean
+        Comparison IsGreater : Boolean
+          Variable #1 : Integer
+          Literal 40 : Integer
+        Literal true : Boolean
+      Compound
+        Print
+          Variable #1 : Integer
+    FunctionReturn
+      Literal 0 : Integer
+");
+
+		let (tokens, errors) = lex("let b bool = 1 <= 2;".into());
+
+		assert_eq!(errors.len(), 0);
+
+		let dumped: Vec<String> = tokens.iter().map(|token| token.to_string()).collect();
+		assert_eq!(dumped, vec![
+			"Identifier let",
+			"Identifier b",
+			"Type Boolean",
+			"Symbol Equal",
+			"IntegerLiteral 1",
+			"Comparison IsLessOrEqual",
+			"IntegerLiteral 2",
+			"Symbol Semicolon"
+		]);
+	}
+
+	#[test]
+	fn repl_session()
+	{
+		use crate::repl::*;
+
+		let mut repl = Repl::new();
+		let mut out = Vec::new();
+
+		let mut feed = |line: &str| repl.feed(line, &mut out).unwrap();
+
+		assert_eq!(feed("function square(x int) int"), Status::Incomplete);
+		assert_eq!(feed("{"), Status::Incomplete);
+		assert_eq!(feed("\treturn x * x;"), Status::Incomplete);
+		assert_eq!(feed("}"), Status::Ready);
+
+		assert_eq!(feed("let y int = invoke square(3);"), Status::Ready);
+		assert_eq!(feed("invoke square(y)"), Status::Ready);
+		assert_eq!(feed("print y + 1;"), Status::Ready);
+
+		// The failed input declares nothing, `z` stays unknown
+		assert_eq!(feed("let z int = 0; print y / z;"), Status::Ready);
+		assert_eq!(feed(":type z"), Status::Ready);
+
+		assert_eq!(feed("if y > 10"), Status::Incomplete);
+		assert_eq!(feed("{ print 1; }"), Status::Incomplete);
+		assert_eq!(feed("else { print 2; }"), Status::Incomplete);
+		assert_eq!(feed(":type y == 9 and true"), Status::Ready);
+
+		assert_eq!(feed(":ast y * 2"), Status::Ready);
+		assert_eq!(feed(":tokens set y;"), Status::Ready);
+		assert_eq!(feed(":quit"), Status::Quit);
+
+		assert_eq!(String::from_utf8(out).unwrap(), "\
+81
+10
+error: division by zero
+error: identifier `z` not declared in the current scope
+ --> <input>:1:1
+  |
+1 | z
+  | ^
+
+2
+Boolean
+Arithmetic Multiply : Integer
+  Variable #1 : Integer
+  Literal 2 : Integer
+1-3\tIdentifier set
+5-5\tIdentifier y
+6-6\tSymbol Semicolon
+");
+	}
+
+	#[test]
+	fn language_server()
+	{
+		use crate::lsp::*;
+		use crate::lsp::json::Json;
+
+		let source = "\
+function square(x int) int
+{
+	return x * x;
+}
+
+function main() int
+{
+	let total int = invoke square(3);
+	print total + true;
+	return total;
+}
+";
+
+		let uri = "file:///main.faren";
+
+		let document = |text: &str| Json::object(vec![("uri", Json::string(uri)), ("text", Json::string(text))]);
+
+		let at = |line: usize, character: usize| Json::object(vec![
+			("textDocument", Json::object(vec![("uri", Json::string(uri))])),
+			("position", Json::object(vec![("line", line.into()), ("character", character.into())]))
+		]);
+
+		let request = |id: usize, method: &str, params: Json| Json::object(vec![
+			("jsonrpc", Json::string("2.0")), ("id", id.into()), ("method", Json::string(method)), ("params", params)
+		]);
+
+		let notification = |method: &str, params: Json| Json::object(vec![
+			("jsonrpc", Json::string("2.0")), ("method", Json::string(method)), ("params", params)
+		]);
+
+		let script = vec![
+			request(1, "initialize", Json::object(vec![])),
+			notification("initialized", Json::object(vec![])),
+			notification("textDocument/didOpen", Json::object(vec![("textDocument", document(source))])),
+			request(2, "textDocument/hover", at(2, 8)),
+			request(3, "textDocument/hover", at(7, 26)),
+			request(4, "textDocument/definition", at(7, 26)),
+			request(5, "textDocument/documentSymbol", Json::object(vec![("textDocument", Json::object(vec![("uri", Json::string(uri))]))])),
+			request(6, "textDocument/hover", at(1, 0)),
+			request(7, "textDocument/rename", at(2, 8)),
+			notification("textDocument/didChange", Json::object(vec![
+				("textDocument", Json::object(vec![("uri", Json::string(uri)), ("version", 2.into())])),
+				("contentChanges", Json::Array(vec![Json::object(vec![("text", Json::string(&source.replace(" + true", "")))])]))
+			])),
+			request(8, "shutdown", Json::Null),
+			notification("exit", Json::Null)
+		];
+
+		let mut input = Vec::new();
+
+		for message in script.iter()
+		{
+			write_message(&mut input, message).unwrap();
+		}
+
+		let mut output = Vec::new();
+		let clean = Server::new().run(std::io::Cursor::new(input), &mut output).unwrap();
+
+		assert!(clean);
+
+		let mut reader = std::io::Cursor::new(output);
+		let mut replies = Vec::new();
+
+		while let Some(content) = read_message(&mut reader).unwrap()
+		{
+			replies.push(Json::parse(&content).unwrap());
+		}
+
+		let texts: Vec<String> = replies.iter().map(|reply| reply.to_string()).collect();
+
+		assert_eq!(texts, vec![
+			"{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"capabilities\":{\"textDocumentSync\":1,\"hoverProvider\":true,\"definitionProvider\":true,\"documentSymbolProvider\":true},\"serverInfo\":{\"name\":\"faren-lsp\",\"version\":\"0.2.0\"}}}",
+			"{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///main.faren\",\"diagnostics\":[{\"range\":{\"start\":{\"line\":8,\"character\":13},\"end\":{\"line\":8,\"character\":14}},\"severity\":1,\"source\":\"faren\",\"message\":\"mismatched operand types for operator `Add`, expected `Integer`, got `Integer` and `Boolean`\"}]}}",
+			"{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"contents\":{\"kind\":\"markdown\",\"value\":\"```faren\\nx int\\n```\"},\"range\":{\"start\":{\"line\":2,\"character\":8},\"end\":{\"line\":2,\"character\":9}}}}",
+			"{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"contents\":{\"kind\":\"markdown\",\"value\":\"```faren\\nfunction square(x int) int\\n```\"},\"range\":{\"start\":{\"line\":7,\"character\":24},\"end\":{\"line\":7,\"character\":30}}}}",
+			"{\"jsonrpc\":\"2.0\",\"id\":4,\"result\":{\"uri\":\"file:///main.faren\",\"range\":{\"start\":{\"line\":0,\"character\":9},\"end\":{\"line\":0,\"character\":15}}}}",
+			"{\"jsonrpc\":\"2.0\",\"id\":5,\"result\":[{\"name\":\"square\",\"detail\":\"function square(x int) int\",\"kind\":12,\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":3,\"character\":1}},\"selectionRange\":{\"start\":{\"line\":0,\"character\":9},\"end\":{\"line\":0,\"character\":15}},\"children\":[]},{\"name\":\"main\",\"detail\":\"function main() int\",\"kind\":12,\"range\":{\"start\":{\"line\":5,\"character\":0},\"end\":{\"line\":10,\"character\":1}},\"selectionRange\":{\"start\":{\"line\":5,\"character\":9},\"end\":{\"line\":5,\"character\":13}},\"children\":[{\"name\":\"total\",\"detail\":\"int\",\"kind\":13,\"range\":{\"start\":{\"line\":7,\"character\":1},\"end\":{\"line\":7,\"character\":34}},\"selectionRange\":{\"start\":{\"line\":7,\"character\":5},\"end\":{\"line\":7,\"character\":10}},\"children\":[]}]}]}",
+			"{\"jsonrpc\":\"2.0\",\"id\":6,\"result\":null}",
+			"{\"jsonrpc\":\"2.0\",\"id\":7,\"error\":{\"code\":-32601,\"message\":\"unknown method `textDocument/rename`\"}}",
+			"{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///main.faren\",\"diagnostics\":[]}}",
+			"{\"jsonrpc\":\"2.0\",\"id\":8,\"result\":null}"
+		]);
+
+		let parsed = Json::parse(" { \"a\" : [1, -2.5e1, \"\\u00e9\\ud83d\\ude00\\n\", null, false] } ").unwrap();
+		assert_eq!(parsed.to_string(), "{\"a\":[1,-25,\"é😀\\n\",null,false]}");
+		assert!(Json::parse("{\"a\": }").is_err());
+	}
+
+	#[test]
+	fn resolution_index()
+	{
+		use crate::parser::resolution::*;
+
+		let source = "\
+function sum(a int, b int) int;
+
+function sum(a int, b int) int
+{
+	let total int = a + b;
+	{
+		let a int = total;
+		set total = a;
+	}
+	return total;
+}
+
+function main() int
+{
+	return invoke sum(1, 2);
+}
+";
+
+		let (_, index, errors) = parse_root_with_index(source.to_string());
+
+		assert!(errors.is_empty());
+
+		let occurrences: Vec<String> = index.resolutions()
+			.iter()
+			.map(|resolution| format!(
+				"{}:{} {:?} {} -> {}:{}",
+				resolution.info.line,
+				resolution.info.column_begin,
+				resolution.declaration.kind,
+				resolution.declaration.name,
+				resolution.declaration.info.line,
+				resolution.declaration.info.column_begin
+			))
+			.collect();
+
+		// The definition and the call refer to the first declaration of `sum`, the
+		// inner `a` shadows the parameter until the end of its block
+		assert_eq!(occurrences, vec![
+			"1:10 Function sum -> 1:10",
+			"1:14 Parameter a -> 1:14",
+			"1:21 Parameter b -> 1:21",
+			"3:10 Function sum -> 1:10",
+			"3:14 Parameter a -> 3:14",
+			"3:21 Parameter b -> 3:21",
+			"5:6 Local total -> 5:6",
+			"5:18 Parameter a -> 3:14",
+			"5:22 Parameter b -> 3:21",
+			"7:7 Local a -> 7:7",
+			"7:15 Local total -> 5:6",
+			"8:7 Local total -> 5:6",
+			"8:15 Local a -> 7:7",
+			"10:9 Local total -> 5:6",
+			"13:10 Function main -> 13:10",
+			"15:16 Function sum -> 1:10"
+		]);
+
+		// Offsets inside and at the end of a name resolve to it, others to nothing
+		let offset = source.find("set total").unwrap();
+		let total = &index.resolve(offset + 6).unwrap().declaration;
+
+		assert_eq!(total.kind, DeclarationKind::Local);
+		assert_eq!(index.resolve(offset + 9).unwrap().declaration, *total);
+		assert!(index.resolve(offset + 1).is_none());
+
+		assert_eq!(index.references(total).count(), 4);
+		assert_eq!(index.declarations().filter(|declaration| declaration.kind == DeclarationKind::Function).count(), 2);
+		assert_eq!(index.declarations().count(), 8);
+	}
+
+	#[test]
+	fn assignment_type_err()
+	{
+		let source = "
+			function main() void
+			{
+				let s int = false;
+				let b bool = 1 < 2;
+				set s = true;
+				set b = s + 1;
+				set s = s * 2;
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 3);
+
+		assert_eq!(errors[0].message, "mismatched type for variable `s` in `let` statement, expected `Integer`, got `Boolean`");
+		assert_eq!(errors[0].line, 4);
+		assert_eq!(errors[0].column_begin, 17);
+
+		assert_eq!(errors[1].message, "mismatched type for variable `s` in `set` statement, expected `Integer`, got `Boolean`");
+		assert_eq!(errors[1].line, 6);
+		assert_eq!(errors[1].column_begin, 13);
+
+		assert_eq!(errors[2].message, "mismatched type for variable `b` in `set` statement, expected `Boolean`, got `Integer`");
+		assert_eq!(errors[2].line, 7);
+		assert_eq!(errors[2].column_begin, 13);
+	}
+
+	#[test]
+	fn void_value_err()
+	{
+		let source = "
+			function log(x int) void
+			{
+				print x;
+			}
+
+			function twice(x int) int
+			{
+				return invoke log(x);
+			}
+
+			function main() void
+			{
+				let x int = invoke log(1);
+				set x = invoke log(2);
+				print invoke log(3);
+				print invoke twice(invoke log(4));
+				express invoke log(5);
+				return invoke log(6);
+			}
+		";
+
+		let (_, errors) = parse_root(source.into());
+
+		let messages: Vec<(usize, &str)> = errors.iter().map(|error| (error.line, error.message.as_str())).collect();
+
+		assert_eq!(messages, vec![
+			(9, "value of type `Void` cannot be returned from function `twice`"),
+			(14, "value of type `Void` cannot be used to initialize variable `x`"),
+			(15, "value of type `Void` cannot be assigned to variable `x`"),
+			(16, "value of type `Void` cannot be printed"),
+			(17, "value of type `Void` cannot be used as argument 1 of `twice`"),
+			(19, "value of type `Void` cannot be returned from function `main`")
+		]);
+
+		assert_eq!(errors[3].column_begin, 11);
+	}
+
+	#[test]
+	fn parameter_list_err()
+	{
+		let source = "
+			function trailing(x int,) int;
+
+			function mistyped(y int, 3) int;
+
+			function after(z int) int
+			{
+				return z;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		let messages: Vec<(usize, &str)> = errors.iter().map(|error| (error.line, error.message.as_str())).collect();
+
+		// The scope of a rejected parameter list is left with it, so `after` is
+		// still parsed at the root
+		assert_eq!(messages, vec![
+			(2, "expected a parameter after symbol `Comma` in function signature"),
+			(4, "expected identifier token")
+		]);
+
+		assert_eq!(errors[0].column_begin, 27);
+
+		assert_eq!(root.statements.len(), 1);
+		assert!(root.statements[0].as_statement::<FunctionDefineStatement>().is_some());
+	}
+
+	#[test]
+	fn c_backend_helper_names()
+	{
+		// Named like the arithmetic helpers once prefixed, these must not clash with them
+		let source = "
+			function div(a int, b int) int
+			{
+				return a / b;
+			}
+
+			function add(a int, b int) int
+			{
+				return a + b;
+			}
+
+			function div_i32(a int) int
+			{
+				return a * 2;
+			}
+
+			function main() void
+			{
+				print invoke div(invoke add(40, 2), 0 - 4);
+				print invoke div_i32(3) - 1;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let c_source = crate::codegen::c::generate(&root);
+
+		assert!(c_source.contains("int32_t faren_div(int32_t v0, int32_t v1)"));
+		assert!(c_source.contains("	return div_i32(v0, v1);"));
+		assert!(c_source.contains("faren_div(faren_add(40, 2), -4)"));
+
+		let dir = std::env::temp_dir().join(format!("faren-c-names-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+
+		std::fs::write(dir.join("main.c"), &c_source).unwrap();
+
+		let Ok(status) = std::process::Command::new("cc")
+			.args(["-std=c99", "-Wall", "-Werror", "-o"])
+			.arg(dir.join("main"))
+			.arg(dir.join("main.c"))
+			.status() else
+		{
+			return;
+		};
+
+		assert!(status.success());
+
+		let output = std::process::Command::new(dir.join("main")).output().unwrap();
+		let (_, interp_out) = run_source(source);
+
+		assert_eq!(String::from_utf8(output.stdout).unwrap(), interp_out);
+		assert_eq!(interp_out, "-10\n5\n");
+
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn wat_backend_helper_names()
+	{
+		let source = "
+			function print_int(x int) int;
+
+			function faren_div(a int, b int) int
+			{
+				return a / b;
+			}
+
+			function main() void
+			{
+				print invoke faren_div(invoke print_int(1), 2);
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let wat = crate::codegen::wat::generate(&root);
+
+		let mut names: Vec<&str> = wat.match_indices("(func $")
+			.map(|(index, _)| wat[index + 6..].split([' ', ')', '\n']).next().unwrap())
+			.collect();
+
+		names.sort();
+
+		assert_eq!(names, vec!["$faren_div", "$fn_faren_div", "$fn_main", "$fn_print_int", "$print_int"]);
+
+		assert!(wat.contains("\t(import \"env\" \"print_int\" (func $fn_print_int (param i32) (result i32)))\n"));
+		assert!(wat.contains("\t(func $fn_faren_div (export \"faren_div\")"));
+		assert!(wat.contains("\t\tcall $fn_print_int\n\t\ti32.const 2\n\t\tcall $fn_faren_div\n\t\tcall $print_int\n"));
+	}
+
+	#[test]
+	fn ir_ssa_nested_loops()
+	{
+		use crate::ir::*;
+
+		let source = "
+			function grid(n int) int
+			{
+				let total int = 0;
+				let i int = 0;
+
+				while i < n
+				{
+					let j int = 0;
+
+					while j < i
+					{
+						set total = total + j;
+						set j = j + 1;
+					}
+
+					set i = i + 1;
+				}
+
+				return total;
+			}
+		";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let mut module = lower::lower(&root);
+		ssa::construct(&mut module.functions[0]);
+
+		assert_eq!(verify::verify_ssa(&module), Ok(()));
+
+		// `total` is merged at both loop headers, `i` only at the outer one and `j`, which is
+		// dead once the inner loop exits, only at the inner one
+		assert_eq!(dump::dump(&module), "\
+function grid(%0 int) int
+bb0:
+  %9: int = copy 0
+  %10: int = copy 0
+  jump bb1
+bb1:
+  %11: int = phi [bb0: %9], [bb6: %14]
+  %12: int = phi [bb0: %10], [bb6: %16]
+  %3: bool = lt %12, %0
+  branch %3, bb2, bb3
+bb2:
+  %13: int = copy 0
+  jump bb4
+bb3:
+  return %11
+bb4:
+  %14: int = phi [bb2: %11], [bb5: %17]
+  %15: int = phi [bb2: %13], [bb5: %18]
+  %5: bool = lt %15, %12
+  branch %5, bb5, bb6
+bb5:
+  %6: int = add %14, %15
+  %17: int = copy %6
+  %7: int = add %15, 1
+  %18: int = copy %7
+  jump bb4
+bb6:
+  %8: int = add %12, 1
+  %16: int = copy %8
+  jump bb1
+");
+	}
+
+	#[test]
+	fn format_comments()
+	{
+		use crate::fmt::*;
+
+		let source = "\
+// Greatest common divisor
+function gcd(a int, b int) int; /* declared first */
+/* The entry point,
+   prints 4 */
+function main() int // entry
+{ // body
+	let x int = 8 /* eight */ + // and
+		4;
+
+	// loop down
+	while x > 0 { set x = x - 1; if x == 4 { break; } // found
+	else { continue; } }
+	print invoke gcd(x, 8);
+	return 0;
+	/* unreachable */
+}
+// end of file
+";
+
+		let (root, errors) = parse_root(source.into());
+
+		assert_eq!(errors.len(), 0);
+
+		let formatted = format(&root, source, &FormatOptions::default());
+
+		assert_eq!(formatted, "\
+// Greatest common divisor
+function gcd(a int, b int) int; /* declared first */
+
+/* The entry point,
+   prints 4 */
+function main() int // entry
+{ // body
+	let x int = 8 + 4; /* eight */ // and
+
+	// loop down
+	while x > 0
+	{
+		set x = x - 1;
+		if x == 4
+		{
+			break;
+		} // found
+		else
+		{
+			continue;
+		}
+	}
+	print invoke gcd(x, 8);
+	return 0;
+	/* unreachable */
+}
+// end of file
+");
+
+		let (reparsed, errors) = parse_root(formatted.clone());
+
+		assert_eq!(errors.len(), 0);
+		assert_eq!(format(&reparsed, &formatted, &FormatOptions::default()), formatted);
+
+		// A source of nothing but comments
+		let (root, _) = parse_root("\t/* a */ // b\n\n".into());
+		assert_eq!(format(&root, "\t/* a */ // b\n\n", &FormatOptions::default()), "/* a */ // b\n");
+	}
+
+	#[test]
+	fn lossless_syntax_tree_trivia()
+	{
+		use crate::cst::*;
+		use crate::parser::lexer::*;
+
+		let source = "\
+/* header /* nested */ */ function main() int // entry
+{
+	let a int = 1 @ 2; /* unknown # character */
+	let b int = 4294967296 + a; // overflows
+	// body
+	return a /* inside */ + b;
+}
+/* end
+";
+
+		// Every prefix, cutting comments and tokens short anywhere
+		for end in (0..=source.len()).filter(|end| source.is_char_boundary(*end))
+		{
+			let (tree, _) = parse(&source[..end]);
+			assert_eq!(tree.to_string(), &source[..end]);
+		}
+
+		let (tree, errors) = parse(source);
+
+		// The unknown character, the overflowing literal and the unterminated comment
+		assert_eq!(errors.len(), 3);
+		assert_eq!(errors.iter().map(|error| error.info.line).collect::<Vec<_>>(), vec![3, 4, 8]);
+
+		// With the unknown character left out as trivia, `2` does not fit the grammar
+		assert!(tree.root.has_errors());
+
+		let trivia: Vec<&Trivia> = tree.root
+			.tokens()
+			.into_iter()
+			.flat_map(|token| token.leading.iter().chain(token.trailing.iter()))
+			.chain(tree.trailing.iter())
+			.collect();
+
+		let texts = |kind: TriviaKind| trivia
+			.iter()
+			.filter(|trivia| trivia.kind == kind)
+			.map(|trivia| trivia.text.as_str())
+			.collect::<Vec<_>>();
+
+		assert_eq!(texts(TriviaKind::LineComment), vec!["// entry", "// overflows", "// body"]);
+		assert_eq!(texts(TriviaKind::BlockComment), vec!["/* header /* nested */ */", "/* unknown # character */", "/* inside */", "/* end\n"]);
+		assert_eq!(texts(TriviaKind::Unknown), vec!["@"]);
+
+		// Trivia offsets slice the source
+		assert!(trivia.iter().all(|trivia| source[trivia.offset..trivia.offset + trivia.text.len()] == trivia.text));
+	}
+
+	#[test]
+	fn language_server_framing()
+	{
+		use crate::lsp::*;
+		use crate::lsp::json::Json;
+
+		let frame = |content: &str| format!("Content-Length: {}\r\n\r\n{}", content.len(), content);
+
+		let serve = |input: String|
+		{
+			let mut output = Vec::new();
+			let result = Server::new().run(std::io::Cursor::new(input.into_bytes()), &mut output);
+
+			let mut reader = std::io::Cursor::new(output);
+			let mut replies = Vec::new();
+
+			while let Some(content) = read_message(&mut reader).unwrap()
+			{
+				replies.push(Json::parse(&content).unwrap().to_string());
+			}
+
+			(result, replies)
+		};
+
+		let initialize = "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{}}";
+
+		let input = [
+			frame("{\"jsonrpc\":\"2.0\",\"id\":0,\"method\":\"textDocument/hover\",\"params\":{}}"),
+
+			// Other headers are skipped, and the name of the length is not case sensitive
+			format!("Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: {}\r\n\r\n{}", initialize.len(), initialize),
+
+			frame("{\"jsonrpc\":\"2.0\",\"id\":2,"),
+			frame("{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"workspace/symbol\",\"params\":{}}"),
+			frame("{\"jsonrpc\":\"2.0\",\"method\":\"$/cancelRequest\",\"params\":{\"id\":3}}"),
+			frame("{\"jsonrpc\":\"2.0\",\"id\":4,\"method\":\"textDocument/hover\",\"params\":{}}")
+		].concat();
+
+		let (result, replies) = serve(input);
+
+		// The input ended without a shutdown, the unknown notification has no reply
+		assert!(!result.unwrap());
+		assert_eq!(replies.len(), 5);
+
+		assert_eq!(replies[0], "{\"jsonrpc\":\"2.0\",\"id\":0,\"error\":{\"code\":-32002,\"message\":\"the server is not initialized\"}}");
+		assert!(replies[1].starts_with("{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"capabilities\":"));
+		assert!(replies[2].starts_with("{\"jsonrpc\":\"2.0\",\"id\":null,\"error\":{\"code\":-32700,"));
+		assert_eq!(replies[3], "{\"jsonrpc\":\"2.0\",\"id\":3,\"error\":{\"code\":-32601,\"message\":\"unknown method `workspace/symbol`\"}}");
+		assert_eq!(replies[4], "{\"jsonrpc\":\"2.0\",\"id\":4,\"error\":{\"code\":-32602,\"message\":\"invalid parameters for `textDocument/hover`\"}}");
+
+		// A message that cannot be framed ends the session, after the replies sent so far
+		let malformed = [
+			("Content-Type: text/plain\r\n\r\n{}".to_string(), std::io::ErrorKind::InvalidData),
+			("Content-Length: ten\r\n\r\n{}".to_string(), std::io::ErrorKind::InvalidData),
+			("Content-Length: 50\r\n\r\n{}".to_string(), std::io::ErrorKind::UnexpectedEof),
+			// Cuts `é` in half
+			("Content-Length: 1\r\n\r\né".to_string(), std::io::ErrorKind::InvalidData)
+		];
+
+		for (header, kind) in malformed
+		{
+			let (result, replies) = serve(frame(initialize) + &header);
+
+			assert_eq!(result.unwrap_err().kind(), kind);
+			assert_eq!(replies.len(), 1);
+		}
+	}
+
+	#[test]
+	fn resolution_index_scopes()
+	{
+		use crate::parser::resolution::*;
+
+		let source = "\
+function main() int
+{
+	print y;
+	let y int = 1;
+	let z int = z + y;
+	{
+		let y bool = true;
+		print y;
+	}
+	print y;
+	return invoke later(y);
+}
+
+function later(n int) int
+{
+	let n int = n + 1;
+	return n;
+}
+";
+
+		let (_, index, errors) = parse_root_with_index(source.to_string());
+
+		let messages: Vec<String> = errors.iter()
+			.map(|error| format!("{}:{} {}", error.line, error.column_begin, error.message))
+			.collect();
+
+		// Names are only in scope after their declaration, including in its own initializer,
+		// and a failed statement leaves the ones after it alone
+		assert_eq!(messages, vec![
+			"3:8 identifier `y` not declared in the current scope",
+			"3:2 no expression parsed for `print` statement",
+			"5:14 identifier `z` not declared in the current scope",
+			"5:2 no expression parsed for `let` statement",
+			"11:16 function `later` not declared in the current module",
+			"11:2 no expression parsed for `return` statement",
+			"1:10 function `main` with return type `Integer` may end without returning a value"
+		]);
+
+		let occurrences: Vec<String> = index.resolutions()
+			.iter()
+			.map(|resolution| format!(
+				"{}:{} {:?} {} -> {}:{}",
+				resolution.info.line,
+				resolution.info.column_begin,
+				resolution.declaration.kind,
+				resolution.declaration.name,
+				resolution.declaration.info.line,
+				resolution.declaration.info.column_begin
+			))
+			.collect();
+
+		// The uses before a declaration resolve to nothing. The block shadows `y` until
+		// its end, and the local `n` shadows the parameter it is initialized from.
+		assert_eq!(occurrences, vec![
+			"1:10 Function main -> 1:10",
+			"4:6 Local y -> 4:6",
+			"5:6 Local z -> 5:6",
+			"7:7 Local y -> 7:7",
+			"8:9 Local y -> 7:7",
+			"10:8 Local y -> 4:6",
+			"14:10 Function later -> 14:10",
+			"14:16 Parameter n -> 14:16",
+			"16:6 Local n -> 16:6",
+			"16:14 Parameter n -> 14:16",
+			"17:9 Local n -> 16:6"
+		]);
+
+		let offset = source.find("print y").unwrap() + 6;
+		assert!(index.resolve(offset).is_none());
+
+		let outer = &index.resolve(source.find("let y").unwrap() + 4).unwrap().declaration;
+		assert_eq!(outer.kind, DeclarationKind::Local);
+		assert_eq!(index.references(outer).count(), 2);
+	}
+
+	#[test]
+	fn long_operator_chain()
+	{
+		use crate::ast::expression::LiteralExpression;
+		use crate::ast::literal::IntegerLiteral;
+		use std::time::{Duration, Instant};
+
+		// Nothing folds, every operator keeps the chain before it as its left operand
+		let terms = vec!["x"; 2000].join(" + ");
+		let source = format!("function main() int {{ let x int = 1; return {}; }}", terms);
+
+		let begin = Instant::now();
+		let (root, errors) = parse_root(source.clone());
+
+		assert_eq!(errors.len(), 0);
+		assert!(begin.elapsed() < Duration::from_secs(2), "parsing took {:?}", begin.elapsed());
+
+		// The outermost operator still covers the whole chain
+		let main = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let returned = main.body().statements()[1].as_statement::<FunctionReturnStatement>().unwrap().expression().clone().unwrap();
+		assert_eq!(returned.span().text(&source), terms);
+
+		// Constants fold as the chain is parsed, each literal keeping the one before it
+		let terms = vec!["1"; 2000].join(" + ");
+		let source = format!("function main() int {{ return {}; }}", terms);
+
+		let begin = Instant::now();
+		let (root, errors) = parse_root(source);
+
+		assert_eq!(errors.len(), 0);
+		assert!(begin.elapsed() < Duration::from_secs(2), "parsing took {:?}", begin.elapsed());
+
+		let main = root.statements[0].as_statement::<FunctionDefineStatement>().unwrap();
+		let returned = main.body().statements()[0].as_statement::<FunctionReturnStatement>().unwrap().expression().clone().unwrap();
+		let literal = returned.as_expression::<LiteralExpression>().unwrap().literal();
+		assert_eq!(literal.as_literal::<IntegerLiteral>().unwrap().value, 2000);
+	}
 }
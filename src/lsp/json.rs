@@ -0,0 +1,375 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+// Just enough JSON for the protocol, numbers are kept as `f64` like JavaScript does
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json
+{
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Json>),
+
+	// Keys keep their order, so that output is stable
+	Object(Vec<(String, Json)>)
+}
+
+#[derive(Debug, Clone)]
+pub struct JsonError
+{
+	pub message: String
+}
+
+impl fmt::Display for JsonError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "{}", self.message)
+	}
+}
+
+impl std::error::Error for JsonError {}
+
+impl Json
+{
+	pub fn object(members: Vec<(&str, Json)>) -> Self
+	{
+		Json::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+	}
+
+	pub fn string(value: &str) -> Self
+	{
+		Json::String(value.to_string())
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Json>
+	{
+		match self
+		{
+			Json::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+			_ => None
+		}
+	}
+
+	// Follows nested objects, `["textDocument", "uri"]`
+	pub fn path(&self, keys: &[&str]) -> Option<&Json>
+	{
+		keys.iter().try_fold(self, |json, key| json.get(key))
+	}
+
+	pub fn as_str(&self) -> Option<&str>
+	{
+		match self
+		{
+			Json::String(value) => Some(value),
+			_ => None
+		}
+	}
+
+	pub fn as_usize(&self) -> Option<usize>
+	{
+		match self
+		{
+			Json::Number(value) if *value >= 0.0 && value.fract() == 0.0 => Some(*value as usize),
+			_ => None
+		}
+	}
+
+	pub fn as_array(&self) -> Option<&Vec<Json>>
+	{
+		match self
+		{
+			Json::Array(values) => Some(values),
+			_ => None
+		}
+	}
+
+	pub fn parse(text: &str) -> Result<Json, JsonError>
+	{
+		let mut chars = text.chars().peekable();
+
+		let value = parse_value(&mut chars)?;
+		skip_whitespace(&mut chars);
+
+		match chars.next()
+		{
+			None => Ok(value),
+			Some(c) => Err(error(&format!("unexpected `{}` after the value", c)))
+		}
+	}
+}
+
+impl From<usize> for Json
+{
+	fn from(value: usize) -> Self
+	{
+		Json::Number(value as f64)
+	}
+}
+
+impl From<bool> for Json
+{
+	fn from(value: bool) -> Self
+	{
+		Json::Bool(value)
+	}
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result
+{
+	f.write_str("\"")?;
+
+	for c in value.chars()
+	{
+		match c
+		{
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+			c => write!(f, "{}", c)?
+		}
+	}
+
+	f.write_str("\"")
+}
+
+impl fmt::Display for Json
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		match self
+		{
+			Json::Null => f.write_str("null"),
+			Json::Bool(value) => write!(f, "{}", value),
+
+			Json::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => write!(f, "{}", *value as i64),
+			Json::Number(value) => write!(f, "{}", value),
+
+			Json::String(value) => write_string(f, value),
+
+			Json::Array(values) =>
+			{
+				f.write_str("[")?;
+
+				for (index, value) in values.iter().enumerate()
+				{
+					if index != 0
+					{
+						f.write_str(",")?;
+					}
+
+					write!(f, "{}", value)?;
+				}
+
+				f.write_str("]")
+			}
+
+			Json::Object(members) =>
+			{
+				f.write_str("{")?;
+
+				for (index, (key, value)) in members.iter().enumerate()
+				{
+					if index != 0
+					{
+						f.write_str(",")?;
+					}
+
+					write_string(f, key)?;
+					write!(f, ":{}", value)?;
+				}
+
+				f.write_str("}")
+			}
+		}
+	}
+}
+
+fn error(message: &str) -> JsonError
+{
+	JsonError { message: message.to_string() }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>)
+{
+	while chars.next_if(|c| matches!(c, ' ' | '\t' | '\n' | '\r')).is_some() {}
+}
+
+fn expect_word(chars: &mut Peekable<Chars>, word: &str, value: Json) -> Result<Json, JsonError>
+{
+	for expected in word.chars()
+	{
+		if chars.next() != Some(expected)
+		{
+			return Err(error(&format!("expected `{}`", word)));
+		}
+	}
+
+	Ok(value)
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Json, JsonError>
+{
+	skip_whitespace(chars);
+
+	match chars.peek()
+	{
+		None => Err(error("unexpected end of input")),
+
+		Some('n') => expect_word(chars, "null", Json::Null),
+		Some('t') => expect_word(chars, "true", Json::Bool(true)),
+		Some('f') => expect_word(chars, "false", Json::Bool(false)),
+
+		Some('"') => parse_string(chars).map(Json::String),
+
+		Some('[') =>
+		{
+			chars.next();
+
+			let mut values = Vec::new();
+			skip_whitespace(chars);
+
+			if chars.next_if_eq(&']').is_some()
+			{
+				return Ok(Json::Array(values));
+			}
+
+			loop
+			{
+				values.push(parse_value(chars)?);
+				skip_whitespace(chars);
+
+				match chars.next()
+				{
+					Some(',') => continue,
+					Some(']') => return Ok(Json::Array(values)),
+					_ => return Err(error("expected `,` or `]` in array"))
+				}
+			}
+		}
+
+		Some('{') =>
+		{
+			chars.next();
+
+			let mut members = Vec::new();
+			skip_whitespace(chars);
+
+			if chars.next_if_eq(&'}').is_some()
+			{
+				return Ok(Json::Object(members));
+			}
+
+			loop
+			{
+				skip_whitespace(chars);
+
+				if chars.peek() != Some(&'"')
+				{
+					return Err(error("expected a string key in object"));
+				}
+
+				let key = parse_string(chars)?;
+				skip_whitespace(chars);
+
+				if chars.next() != Some(':')
+				{
+					return Err(error("expected `:` after object key"));
+				}
+
+				members.push((key, parse_value(chars)?));
+				skip_whitespace(chars);
+
+				match chars.next()
+				{
+					Some(',') => continue,
+					Some('}') => return Ok(Json::Object(members)),
+					_ => return Err(error("expected `,` or `}` in object"))
+				}
+			}
+		}
+
+		Some(c) if *c == '-' || c.is_ascii_digit() =>
+		{
+			let mut number = String::new();
+
+			while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+			{
+				number.push(c);
+			}
+
+			number.parse::<f64>()
+				.map(Json::Number)
+				.map_err(|_| error(&format!("invalid number `{}`", number)))
+		}
+
+		Some(c) => Err(error(&format!("unexpected `{}`", c)))
+	}
+}
+
+fn parse_hex(chars: &mut Peekable<Chars>) -> Result<u32, JsonError>
+{
+	let digits: String = chars.by_ref().take(4).collect();
+
+	match u32::from_str_radix(&digits, 16)
+	{
+		Ok(value) if digits.len() == 4 => Ok(value),
+		_ => Err(error(&format!("invalid escape `\\u{}`", digits)))
+	}
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, JsonError>
+{
+	chars.next();
+
+	let mut value = String::new();
+
+	loop
+	{
+		match chars.next()
+		{
+			None => return Err(error("unterminated string")),
+			Some('"') => return Ok(value),
+
+			Some('\\') => match chars.next()
+			{
+				Some('"') => value.push('"'),
+				Some('\\') => value.push('\\'),
+				Some('/') => value.push('/'),
+				Some('b') => value.push('\u{8}'),
+				Some('f') => value.push('\u{c}'),
+				Some('n') => value.push('\n'),
+				Some('r') => value.push('\r'),
+				Some('t') => value.push('\t'),
+
+				Some('u') =>
+				{
+					let mut code = parse_hex(chars)?;
+
+					// Characters outside the basic plane come as a surrogate pair
+					if (0xd800..0xdc00).contains(&code)
+					{
+						if chars.next() != Some('\\') || chars.next() != Some('u')
+						{
+							return Err(error("unpaired surrogate in string"));
+						}
+
+						let low = parse_hex(chars)?;
+						code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+					}
+
+					value.push(char::from_u32(code).ok_or_else(|| error("invalid character in string"))?);
+				}
+
+				_ => return Err(error("invalid escape in string"))
+			},
+
+			Some(c) => value.push(c)
+		}
+	}
+}
@@ -0,0 +1,583 @@
+pub mod json;
+
+use std::collections::{
+	HashMap,
+	VecDeque
+};
+
+use std::io::{
+	self,
+	BufRead,
+	Write
+};
+
+use json::Json;
+
+use crate::ast::{
+	root::*,
+	span::*,
+	statement::*,
+	expression::*,
+	visit::*
+};
+
+use crate::fmt::{
+	format_signature,
+	type_name
+};
+
+use crate::parser::{
+	parse_root,
+	token::*,
+	ParserError
+};
+
+// JSON-RPC error codes
+const PARSE_ERROR: i32 = -32700;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const SERVER_NOT_INITIALIZED: i32 = -32002;
+
+// LSP enumerations
+const SYNC_FULL: usize = 1;
+const SEVERITY_ERROR: usize = 1;
+const SYMBOL_FUNCTION: usize = 12;
+const SYMBOL_VARIABLE: usize = 13;
+
+// Reads one message framed by a `Content-Length` header, `None` once the input ends
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>>
+{
+	let mut length = None;
+
+	loop
+	{
+		let mut header = String::new();
+
+		if reader.read_line(&mut header)? == 0
+		{
+			return Ok(None);
+		}
+
+		let header = header.trim_end();
+
+		if header.is_empty()
+		{
+			break;
+		}
+
+		if let Some((name, value)) = header.split_once(':')
+			&& name.eq_ignore_ascii_case("Content-Length")
+		{
+			length = value.trim().parse::<usize>().ok();
+		}
+	}
+
+	let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message without a `Content-Length` header"))?;
+
+	let mut content = vec![0; length];
+	reader.read_exact(&mut content)?;
+
+	String::from_utf8(content)
+		.map(Some)
+		.map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+pub fn write_message<W: Write>(writer: &mut W, message: &Json) -> io::Result<()>
+{
+	let content = message.to_string();
+
+	write!(writer, "Content-Length: {}\r\n\r\n{}", content.len(), content)?;
+	writer.flush()
+}
+
+// Positions in the protocol count UTF-16 code units from the start of the line
+fn position(source: &str, offset: usize) -> Json
+{
+	let offset = offset.min(source.len());
+	let line_begin = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+
+	let line = source[..line_begin].matches('\n').count();
+	let character: usize = source[line_begin..offset].chars().map(char::len_utf16).sum();
+
+	Json::object(vec![("line", line.into()), ("character", character.into())])
+}
+
+fn offset_at(source: &str, line: usize, character: usize) -> usize
+{
+	let line_begin = match line
+	{
+		0 => 0,
+		_ => source.match_indices('\n').nth(line - 1).map_or(source.len(), |(index, _)| index + 1)
+	};
+
+	let mut units = 0;
+
+	for (index, c) in source[line_begin..].char_indices()
+	{
+		if units >= character || c == '\n'
+		{
+			return line_begin + index;
+		}
+
+		units += c.len_utf16();
+	}
+
+	source.len()
+}
+
+fn range(source: &str, span: &Span) -> Json
+{
+	Json::object(vec![
+		("start", position(source, span.offset_begin)),
+		("end", position(source, span.offset_end))
+	])
+}
+
+// Errors only carry columns, counted in characters and inclusive at the end
+fn error_range(error: &ParserError) -> Json
+{
+	let units = |columns: usize| -> usize { error.context_line.chars().take(columns).map(char::len_utf16).sum() };
+
+	let at = |character: usize| Json::object(vec![("line", error.line.saturating_sub(1).into()), ("character", character.into())]);
+
+	Json::object(vec![
+		("start", at(units(error.column_begin.saturating_sub(1)))),
+		("end", at(units(error.column_end)))
+	])
+}
+
+fn contains(span: &Span, offset: usize) -> bool
+{
+	!span.is_empty() && span.offset_begin <= offset && offset <= span.offset_end
+}
+
+fn name_token(tokens: &VecDeque<Token>) -> Option<(String, Span)>
+{
+	let token = tokens.get(1)?;
+	let ident_token = token.as_token::<IdentifierToken>()?;
+
+	Some((ident_token.name(), Span::from_info(&token.info())))
+}
+
+#[derive(Default)]
+struct Occurrences
+{
+	variables: Vec<VariableExpression>,
+	calls: Vec<FunctionCallExpression>,
+	declarations: Vec<DeclareStatement>
+}
+
+impl Visitor for Occurrences
+{
+	fn visit_variable(&mut self, variable: &VariableExpression)
+	{
+		self.variables.push(variable.clone());
+	}
+
+	fn visit_function_call(&mut self, function_call: &FunctionCallExpression)
+	{
+		self.calls.push(function_call.clone());
+		walk_function_call(self, function_call);
+	}
+
+	fn visit_declare(&mut self, declare: &DeclareStatement)
+	{
+		self.declarations.push(declare.clone());
+		walk_declare(self, declare);
+	}
+}
+
+struct Document
+{
+	source: String,
+	root: Root,
+	errors: Vec<ParserError>
+}
+
+impl Document
+{
+	fn new(source: String) -> Self
+	{
+		let (root, errors) = parse_root(source.clone());
+
+		Self { source, root, errors }
+	}
+
+	fn occurrences(&self) -> Occurrences
+	{
+		let mut occurrences = Occurrences::default();
+		occurrences.visit_root(&self.root);
+
+		occurrences
+	}
+
+	// The `invoke` keyword and the name of the call under the cursor
+	fn call_at(&self, offset: usize) -> Option<FunctionCallExpression>
+	{
+		self.occurrences().calls.into_iter().find(|call|
+		{
+			let tokens = call.unparse();
+
+			match (tokens.front(), tokens.get(1))
+			{
+				(Some(invoke), Some(name)) => contains(&Span::from_info(&invoke.info()).to(&Span::from_info(&name.info())), offset),
+				_ => false
+			}
+		})
+	}
+
+	// A definition is preferred over a declaration
+	fn function(&self, name: &str) -> Option<&Statement>
+	{
+		let named = |statement: &&Statement| match statement.kind()
+		{
+			StatementKind::FunctionDefine(function) => function.signature().name() == name,
+			StatementKind::FunctionDeclare(function) => function.signature().name() == name,
+			_ => false
+		};
+
+		self.root.statements.iter()
+			.filter(named)
+			.find(|statement| statement.stype() == StatementType::FunctionDefine)
+			.or_else(|| self.root.statements.iter().find(named))
+	}
+}
+
+fn signature_of(statement: &Statement) -> Option<String>
+{
+	match statement.kind()
+	{
+		StatementKind::FunctionDefine(function) => Some(format_signature(&function.unparse(), function.signature())),
+		StatementKind::FunctionDeclare(function) => Some(format_signature(&function.unparse(), function.signature())),
+		_ => None
+	}
+}
+
+fn code_block(text: &str) -> Json
+{
+	Json::object(vec![
+		("kind", Json::string("markdown")),
+		("value", Json::String(format!("```faren\n{}\n```", text)))
+	])
+}
+
+fn response(id: Json, result: Json) -> Json
+{
+	Json::object(vec![("jsonrpc", Json::string("2.0")), ("id", id), ("result", result)])
+}
+
+fn error_response(id: Json, code: i32, message: &str) -> Json
+{
+	Json::object(vec![
+		("jsonrpc", Json::string("2.0")),
+		("id", id),
+		("error", Json::object(vec![("code", Json::Number(code as f64)), ("message", Json::string(message))]))
+	])
+}
+
+fn notification(method: &str, params: Json) -> Json
+{
+	Json::object(vec![("jsonrpc", Json::string("2.0")), ("method", Json::string(method)), ("params", params)])
+}
+
+// A language server over `parse_root`. Documents are synchronised in full and parsed
+// again on every change, so diagnostics, hovers and symbols always match the text.
+#[derive(Default)]
+pub struct Server
+{
+	documents: HashMap<String, Document>,
+	initialized: bool,
+	shutdown: bool,
+	exited: bool
+}
+
+impl Server
+{
+	pub fn new() -> Self
+	{
+		Self::default()
+	}
+
+	// Serves until the client sends `exit` or the input ends, returns whether the
+	// client asked for a shutdown first, as the exit code of the server depends on it
+	pub fn run<R: BufRead, W: Write>(&mut self, mut reader: R, mut writer: W) -> io::Result<bool>
+	{
+		while let Some(content) = read_message(&mut reader)?
+		{
+			let replies = match Json::parse(&content)
+			{
+				Ok(message) => self.handle(&message),
+				Err(err) => vec![error_response(Json::Null, PARSE_ERROR, &err.message)]
+			};
+
+			for reply in replies.iter()
+			{
+				write_message(&mut writer, reply)?;
+			}
+
+			if self.exited
+			{
+				break;
+			}
+		}
+
+		Ok(self.shutdown)
+	}
+
+	// Handles one request or notification, returning what is sent back
+	pub fn handle(&mut self, message: &Json) -> Vec<Json>
+	{
+		// Responses to requests of ours, there are none
+		let Some(method) = message.get("method").and_then(Json::as_str) else
+		{
+			return Vec::new();
+		};
+
+		let params = message.get("params").unwrap_or(&Json::Null);
+
+		let Some(id) = message.get("id").cloned() else
+		{
+			return self.notify(method, params);
+		};
+
+		let result = match method
+		{
+			"initialize" =>
+			{
+				self.initialized = true;
+				Some(Self::capabilities())
+			}
+
+			"shutdown" =>
+			{
+				self.shutdown = true;
+				Some(Json::Null)
+			}
+
+			_ if !self.initialized => return vec![error_response(id, SERVER_NOT_INITIALIZED, "the server is not initialized")],
+
+			"textDocument/hover" => self.hover(params),
+			"textDocument/definition" => self.definition(params),
+			"textDocument/documentSymbol" => self.document_symbols(params),
+
+			_ => return vec![error_response(id, METHOD_NOT_FOUND, &format!("unknown method `{}`", method))]
+		};
+
+		match result
+		{
+			Some(result) => vec![response(id, result)],
+			None => vec![error_response(id, INVALID_PARAMS, &format!("invalid parameters for `{}`", method))]
+		}
+	}
+
+	fn capabilities() -> Json
+	{
+		Json::object(vec![
+			("capabilities", Json::object(vec![
+				("textDocumentSync", SYNC_FULL.into()),
+				("hoverProvider", true.into()),
+				("definitionProvider", true.into()),
+				("documentSymbolProvider", true.into())
+			])),
+			("serverInfo", Json::object(vec![
+				("name", Json::string("faren-lsp")),
+				("version", Json::string(env!("CARGO_PKG_VERSION")))
+			]))
+		])
+	}
+
+	fn notify(&mut self, method: &str, params: &Json) -> Vec<Json>
+	{
+		let Some(uri) = params.path(&["textDocument", "uri"]).and_then(Json::as_str) else
+		{
+			if method == "exit"
+			{
+				self.exited = true;
+			}
+
+			return Vec::new();
+		};
+
+		let uri = uri.to_string();
+
+		let text = match method
+		{
+			"textDocument/didOpen" => params.path(&["textDocument", "text"]).and_then(Json::as_str),
+
+			// Only full synchronisation is offered, the last change holds the whole text
+			"textDocument/didChange" => params.get("contentChanges")
+				.and_then(Json::as_array)
+				.and_then(|changes| changes.last())
+				.and_then(|change| change.get("text"))
+				.and_then(Json::as_str),
+
+			"textDocument/didClose" =>
+			{
+				self.documents.remove(&uri);
+				return vec![Self::diagnostics(&uri, Vec::new())];
+			}
+
+			_ => return Vec::new()
+		};
+
+		let Some(text) = text else
+		{
+			return Vec::new();
+		};
+
+		let document = Document::new(text.to_string());
+
+		let diagnostics = document.errors.iter()
+			.map(|error| Json::object(vec![
+				("range", error_range(error)),
+				("severity", SEVERITY_ERROR.into()),
+				("source", Json::string("faren")),
+				("message", Json::string(&error.message))
+			]))
+			.collect();
+
+		self.documents.insert(uri.clone(), document);
+
+		vec![Self::diagnostics(&uri, diagnostics)]
+	}
+
+	fn diagnostics(uri: &str, diagnostics: Vec<Json>) -> Json
+	{
+		notification("textDocument/publishDiagnostics", Json::object(vec![
+			("uri", Json::string(uri)),
+			("diagnostics", Json::Array(diagnostics))
+		]))
+	}
+
+	// The document and offset a request points at, `None` when the parameters are invalid
+	// and no document when it is not open
+	fn document_at<'a>(&'a self, params: &'a Json) -> Option<(&'a str, Option<(&'a Document, usize)>)>
+	{
+		let uri = params.path(&["textDocument", "uri"])?.as_str()?;
+		let line = params.path(&["position", "line"])?.as_usize()?;
+		let character = params.path(&["position", "character"])?.as_usize()?;
+
+		let document = self.documents
+			.get(uri)
+			.map(|document| (document, offset_at(&document.source, line, character)));
+
+		Some((uri, document))
+	}
+
+	fn hover(&self, params: &Json) -> Option<Json>
+	{
+		let Some((document, offset)) = self.document_at(params)?.1 else
+		{
+			return Some(Json::Null);
+		};
+
+		let variable = document.occurrences().variables.into_iter().find(|variable| contains(&variable.span(), offset));
+
+		if let Some(variable) = variable
+		{
+			let name = variable.unparse()
+				.front()
+				.and_then(|token| token.as_token::<IdentifierToken>().map(|ident_token| ident_token.name()))
+				.unwrap_or_else(|| format!("v{}", variable.identifier()));
+
+			return Some(Json::object(vec![
+				("contents", code_block(&format!("{} {}", name, type_name(&variable.vtype())))),
+				("range", range(&document.source, &variable.span()))
+			]));
+		}
+
+		let hover = document.call_at(offset).and_then(|call|
+		{
+			let signature = signature_of(document.function(&call.name())?)?;
+			let (_, name_span) = name_token(&call.unparse())?;
+
+			Some(Json::object(vec![
+				("contents", code_block(&signature)),
+				("range", range(&document.source, &name_span))
+			]))
+		});
+
+		Some(hover.unwrap_or(Json::Null))
+	}
+
+	fn definition(&self, params: &Json) -> Option<Json>
+	{
+		let (uri, document) = self.document_at(params)?;
+
+		let Some((document, offset)) = document else
+		{
+			return Some(Json::Null);
+		};
+
+		let location = document.call_at(offset).and_then(|call|
+		{
+			let function = document.function(&call.name())?;
+
+			if function.stype() != StatementType::FunctionDefine
+			{
+				return None;
+			}
+
+			let (_, name_span) = name_token(&function.unparse())?;
+
+			Some(Json::object(vec![
+				("uri", Json::string(uri)),
+				("range", range(&document.source, &name_span))
+			]))
+		});
+
+		Some(location.unwrap_or(Json::Null))
+	}
+
+	fn document_symbols(&self, params: &Json) -> Option<Json>
+	{
+		let uri = params.path(&["textDocument", "uri"])?.as_str()?;
+
+		let Some(document) = self.documents.get(uri) else
+		{
+			return Some(Json::Null);
+		};
+
+		let source = &document.source;
+
+		let symbol = |name: String, detail: String, kind: usize, span: &Span, name_span: &Span, children: Vec<Json>| Json::object(vec![
+			("name", Json::String(name)),
+			("detail", Json::String(detail)),
+			("kind", kind.into()),
+			("range", range(source, span)),
+			("selectionRange", range(source, name_span)),
+			("children", Json::Array(children))
+		]);
+
+		let mut symbols = Vec::new();
+
+		for statement in document.root.statements.iter()
+		{
+			let Some((name, name_span)) = name_token(&statement.unparse()) else
+			{
+				continue;
+			};
+
+			let Some(signature) = signature_of(statement) else
+			{
+				continue;
+			};
+
+			let mut locals = Occurrences::default();
+			locals.visit_statement(statement);
+
+			let children = locals.declarations.iter()
+				.filter_map(|declare|
+				{
+					let (local, local_span) = name_token(&declare.unparse())?;
+					Some(symbol(local, type_name(&declare.vtype()).to_string(), SYMBOL_VARIABLE, &declare.span(), &local_span, Vec::new()))
+				})
+				.collect();
+
+			symbols.push(symbol(name, signature, SYMBOL_FUNCTION, &statement.span(), &name_span, children));
+		}
+
+		Some(Json::Array(symbols))
+	}
+}
+
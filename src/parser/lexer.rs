@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::fmt;
 
 use crate::data::{
 	vtype::VType,
@@ -8,44 +9,250 @@ use crate::data::{
 
 use super::token::*;
 
-pub fn lex(data: String) -> VecDeque<Token>
+pub struct LexerError
 {
-	let mut chars = data.chars().peekable();
+	pub message: String,
+	pub info: TokenInfo
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind
+{
+	Whitespace,
+	Newline,
+	LineComment,
+	BlockComment,
+
+	// Characters the lexer reported as unknown
+	Unknown
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia
+{
+	pub kind: TriviaKind,
+	pub offset: usize,
+	pub text: String
+}
+
+// A token together with the source text around it. Trivia on the same line after the
+// token is trailing, everything else before the next token is leading on that one.
+#[derive(Clone)]
+pub struct LosslessToken
+{
+	pub leading: Vec<Trivia>,
+	pub token: Token,
+	pub text: String,
+	pub trailing: Vec<Trivia>
+}
+
+impl fmt::Display for LosslessToken
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		for trivia in self.leading.iter()
+		{
+			f.write_str(&trivia.text)?;
+		}
+
+		f.write_str(&self.text)?;
+
+		for trivia in self.trailing.iter()
+		{
+			f.write_str(&trivia.text)?;
+		}
+
+		Ok(())
+	}
+}
+
+pub fn lex(data: String) -> (VecDeque<Token>, Vec<LexerError>)
+{
+	let (tokens, errors, _) = scan(&data);
+
+	(tokens, errors)
+}
+
+// Lossless mode, the trivia of all tokens and the trivia after the last one
+// concatenate back to `data` byte for byte, whatever errors it contains
+pub fn lex_lossless(data: &str) -> (Vec<LosslessToken>, Vec<Trivia>, Vec<LexerError>)
+{
+	let (tokens, errors, trivia) = scan(data);
+
+	let mut trivia = trivia
+		.into_iter()
+		.map(|(kind, begin, end)| Trivia { kind, offset: begin, text: data[begin..end].to_string() })
+		.peekable();
+
+	let mut lossless: Vec<LosslessToken> = Vec::with_capacity(tokens.len());
+
+	for token in tokens
+	{
+		let info = token.info();
+
+		// Whatever is left on the line of the previous token trails it
+		if let Some(previous) = lossless.last_mut()
+		{
+			while let Some(next) = trivia.next_if(|next| next.offset < info.offset_begin && next.kind != TriviaKind::Newline)
+			{
+				previous.trailing.push(next);
+			}
+		}
+
+		let mut leading = Vec::new();
+
+		while let Some(next) = trivia.next_if(|next| next.offset < info.offset_begin)
+		{
+			leading.push(next);
+		}
+
+		lossless.push(LosslessToken
+		{
+			leading,
+			text: data[info.offset_begin..info.offset_end].to_string(),
+			token,
+			trailing: Vec::new()
+		});
+	}
+
+	if let Some(previous) = lossless.last_mut()
+	{
+		while let Some(next) = trivia.next_if(|next| next.kind != TriviaKind::Newline)
+		{
+			previous.trailing.push(next);
+		}
+	}
+
+	(lossless, trivia.collect(), errors)
+}
+
+// Byte ranges of everything between tokens, in source order
+type TriviaRange = (TriviaKind, usize, usize);
+
+fn push_trivia(trivia: &mut Vec<TriviaRange>, kind: TriviaKind, begin: usize, end: usize)
+{
+	// Runs of spaces and tabs are kept together
+	if let Some((TriviaKind::Whitespace, _, last_end)) = trivia.last_mut()
+		&& kind == TriviaKind::Whitespace && *last_end == begin
+	{
+		*last_end = end;
+		return;
+	}
+
+	trivia.push((kind, begin, end));
+}
+
+fn scan(data: &str) -> (VecDeque<Token>, Vec<LexerError>, Vec<TriviaRange>)
+{
+	let mut chars = data.char_indices().peekable();
 
 	let mut line: usize = 1;
 	let mut column: usize = 1;
 
 	let mut tokens: VecDeque<Token> = VecDeque::new();
+	let mut errors: Vec<LexerError> = Vec::new();
+	let mut trivia: Vec<TriviaRange> = Vec::new();
 
-	fn make_info(line: usize, column_begin: usize, token_len: usize) -> TokenInfo
+	// Every token but an unknown character is ASCII, so its length in bytes and columns agree
+	fn make_info(line: usize, column_begin: usize, offset_begin: usize, token_len: usize) -> TokenInfo
 	{
 		TokenInfo
 		{
 			line,
 			column_begin,
-			column_end: column_begin + token_len - 1
+			column_end: column_begin + token_len - 1,
+			offset_begin,
+			offset_end: offset_begin + token_len
 		}
 	}
 
-	while let Some(c) = chars.next()
+	while let Some((offset, c)) = chars.next()
 	{
 		match c
 		{
-			' ' | '\t' =>
+			' ' | '\t' | '\r' =>
 			{
+				push_trivia(&mut trivia, TriviaKind::Whitespace, offset, offset + 1);
 				column += 1;
 				continue;
 			}
 
 			'\n' =>
 			{
+				push_trivia(&mut trivia, TriviaKind::Newline, offset, offset + 1);
 				line += 1;
 				column = 1;
 			}
 
+			'/' if matches!(chars.peek(), Some((_, '/'))) =>
+			{
+				// Line comment, the newline ending it is lexed as usual
+				while let Some((_, current)) = chars.peek()
+				{
+					if *current == '\n'
+					{
+						break;
+					}
+					chars.next();
+					column += 1;
+				}
+
+				let end = chars.peek().map_or(data.len(), |(index, _)| *index);
+				push_trivia(&mut trivia, TriviaKind::LineComment, offset, end);
+			}
+
+			'/' if matches!(chars.peek(), Some((_, '*'))) =>
+			{
+				// Block comment, these nest so that commenting out code containing one works
+				let info = make_info(line, column, offset, 2);
+
+				chars.next();
+				column += 2;
+
+				let mut depth = 1;
+
+				while depth > 0
+				{
+					match chars.next()
+					{
+						None => break,
+
+						Some((_, '\n')) =>
+						{
+							line += 1;
+							column = 1;
+						}
+
+						Some((_, '/')) if matches!(chars.peek(), Some((_, '*'))) =>
+						{
+							chars.next();
+							column += 2;
+							depth += 1;
+						}
+
+						Some((_, '*')) if matches!(chars.peek(), Some((_, '/'))) =>
+						{
+							chars.next();
+							column += 2;
+							depth -= 1;
+						}
+
+						Some(_) => column += 1
+					}
+				}
+
+				let end = chars.peek().map_or(data.len(), |(index, _)| *index);
+				push_trivia(&mut trivia, TriviaKind::BlockComment, offset, end);
+
+				if depth > 0
+				{
+					errors.push(LexerError { message: "unterminated block comment".to_string(), info });
+				}
+			}
+
 			'+' | '-' | '*' | '/' =>
 			{
-				let info = make_info(line, column, 1);
+				let info = make_info(line, column, offset, 1);
 				column += 1;
 
 				match c
@@ -65,7 +272,7 @@ pub fn lex(data: String) -> VecDeque<Token>
 
 				let mut token_len = 1;
 
-				while let Some(current) = chars.peek()
+				while let Some((_, current)) = chars.peek()
 				{
 					if !current.is_ascii_digit()
 					{
@@ -76,12 +283,24 @@ pub fn lex(data: String) -> VecDeque<Token>
 					token_len += 1;
 				}
 
-				let info = make_info(line, column, token_len);
+				let info = make_info(line, column, offset, token_len);
 				column += token_len;
 
-				if let Ok(val) = num.parse::<i32>()
+				match num.parse::<i32>()
 				{
-					tokens.push_back(Token::new_integer_literal(info, val));
+					Ok(val) => tokens.push_back(Token::new_integer_literal(info, val)),
+
+					Err(_) =>
+					{
+						errors.push(LexerError
+						{
+							message: format!("integer literal `{}` does not fit in type `{:?}`", num, VType::Integer),
+							info: info.clone()
+						});
+
+						// Keep a placeholder so the parser does not cascade on a missing operand
+						tokens.push_back(Token::new_integer_literal(info, 0));
+					}
 				}
 			}
 
@@ -92,7 +311,7 @@ pub fn lex(data: String) -> VecDeque<Token>
 
 				let mut token_len = 1;
 
-				while let Some(current) = chars.peek()
+				while let Some((_, current)) = chars.peek()
 				{
 					if !(current.is_ascii_alphanumeric() || *current == '_')
 					{
@@ -103,7 +322,7 @@ pub fn lex(data: String) -> VecDeque<Token>
 					token_len += 1;
 				}
 
-				let info = make_info(line, column, token_len);
+				let info = make_info(line, column, offset, token_len);
 				column += token_len;
 
 				match ident.as_str()
@@ -126,16 +345,16 @@ pub fn lex(data: String) -> VecDeque<Token>
 			{
 				let mut token_len = 1;
 
-				if let Some('=') = chars.peek()
+				if let Some((_, '=')) = chars.peek()
 				{
 					chars.next();
 					token_len += 1;
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsEqual));
 				}
 				else
 				{
-					let info = make_info(line, column, 1);
+					let info = make_info(line, column, offset, 1);
 					tokens.push_back(Token::new_symbol(info, Symbol::Equal));
 				}
 
@@ -146,16 +365,16 @@ pub fn lex(data: String) -> VecDeque<Token>
 			{
 				let mut token_len = 1;
 
-				if let Some('=') = chars.peek()
+				if let Some((_, '=')) = chars.peek()
 				{
 					chars.next();
 					token_len += 1;
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsNotEqual));
 				}
 				else
 				{
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_symbol(info, Symbol::Bang));
 				}
 
@@ -166,16 +385,16 @@ pub fn lex(data: String) -> VecDeque<Token>
 			{
 				let mut token_len = 1;
 
-				if let Some('=') = chars.peek()
+				if let Some((_, '=')) = chars.peek()
 				{
 					chars.next();
 					token_len += 1;
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsLessOrEqual));
 				}
 				else
 				{
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsLess));
 				}
 
@@ -186,25 +405,25 @@ pub fn lex(data: String) -> VecDeque<Token>
 			{
 				let mut token_len = 1;
 
-				if let Some('=') = chars.peek()
+				if let Some((_, '=')) = chars.peek()
 				{
 					chars.next();
 					token_len += 1;
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsGreaterOrEqual));
 				}
 				else
 				{
-					let info = make_info(line, column, token_len);
+					let info = make_info(line, column, offset, token_len);
 					tokens.push_back(Token::new_comparison(info, ComparisonOperation::IsGreater));
 				}
 				
 				column += token_len;
 			}
 
-			'(' | ')' | '{' | '}' | ';' =>
+			'(' | ')' | '{' | '}' | ',' | ';' =>
 			{
-				let info = make_info(line, column, 1);
+				let info = make_info(line, column, offset, 1);
 				column += 1;
 
 				match c
@@ -213,6 +432,7 @@ pub fn lex(data: String) -> VecDeque<Token>
 					')' => tokens.push_back(Token::new_symbol(info, Symbol::RightParen)),
 					'{' => tokens.push_back(Token::new_symbol(info, Symbol::LeftBrace)),
 					'}' => tokens.push_back(Token::new_symbol(info, Symbol::RightBrace)),
+					',' => tokens.push_back(Token::new_symbol(info, Symbol::Comma)),
 					';' => tokens.push_back(Token::new_symbol(info, Symbol::Semicolon)),
 					_ => unreachable!()
 				}
@@ -220,10 +440,16 @@ pub fn lex(data: String) -> VecDeque<Token>
 
 			_ =>
 			{
-				continue;
+				let mut info = make_info(line, column, offset, 1);
+				info.offset_end = offset + c.len_utf8();
+				column += 1;
+
+				push_trivia(&mut trivia, TriviaKind::Unknown, info.offset_begin, info.offset_end);
+
+				errors.push(LexerError { message: format!("unknown character `{}`", c), info });
 			}
 		}
 	}
 
-	tokens
+	(tokens, errors, trivia)
 }
@@ -1,19 +1,28 @@
 pub mod token;
 use token::*;
 
-mod lexer;
+pub mod lexer;
 use lexer::*;
 
-mod symbols_table;
+pub mod symbols_table;
 use symbols_table::*;
 
+pub mod resolution;
+use resolution::*;
+
+pub mod render;
+
 use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
 
 use crate::ast::{
 	root::*,
 	statement::*,
 	literal::*,
-	expression::*
+	expression::*,
+	fold
 };
 
 use crate::data::{
@@ -22,6 +31,7 @@ use crate::data::{
 	vtype::*
 };
 
+#[derive(Debug, Clone)]
 pub struct ParserError
 {
 	pub message: String,
@@ -31,29 +41,56 @@ pub struct ParserError
 	pub context_line: String
 }
 
+impl ParserError
+{
+	// An error at the token described by `info`, in the source the token was lexed from
+	pub fn new(message: &str, info: &TokenInfo, source: &str) -> Self
+	{
+		Self
+		{
+			message: message.to_string(),
+			line: info.line,
+			column_begin: info.column_begin,
+			column_end: info.column_end,
+			context_line: source.lines().nth(info.line - 1).unwrap_or_default().to_string()
+		}
+	}
+}
+
+impl fmt::Display for ParserError
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		write!(f, "{}:{}: {}", self.line, self.column_begin, self.message)
+	}
+}
+
+impl std::error::Error for ParserError {}
+
 struct ParserContext<'a>
 {
 	pub source: &'a str,
 	pub tokens: VecDeque<Token>,
 	pub symbols_table: SymbolsTable,
-	pub errors: Vec<ParserError>
+	pub errors: Vec<ParserError>,
+	pub resolutions: Vec<Resolution>,
+	pub loop_depth: usize
 }
 
 fn record_error(parser_context: &mut ParserContext, message: &str, info: &TokenInfo)
 {
-	parser_context.errors.push(
-		ParserError
-		{
-			message: message.to_string(),
-			line: info.line as usize,
-			column_begin: info.column_begin as usize,
-			column_end: info.column_end as usize,
-			context_line: parser_context.source.lines().nth((info.line - 1) as usize).unwrap_or_default().to_string()
-		}
-	);
+	parser_context.errors.push(ParserError::new(message, info, parser_context.source));
+}
+
+fn record_resolution(parser_context: &mut ParserContext, info: &TokenInfo, declaration: Option<Declaration>)
+{
+	if let Some(declaration) = declaration
+	{
+		parser_context.resolutions.push(Resolution { info: info.clone(), declaration });
+	}
 }
 
-fn recover_token_stream(tokens: &mut VecDeque<Token>) -> ()
+fn recover_token_stream(tokens: &mut VecDeque<Token>)
 {
 	while let Some(token) = tokens.pop_front()
 	{
@@ -76,6 +113,183 @@ fn recover_token_stream(tokens: &mut VecDeque<Token>) -> ()
 	}
 }
 
+// Takes the tokens of a condition up to, but not including, the `{` that begins its body
+fn take_condition_tokens(parser_context: &mut ParserContext, tokens: &mut VecDeque<Token>) -> VecDeque<Token>
+{
+	let mut expr_tokens = VecDeque::new();
+
+	while let Some(next_token) = parser_context.tokens.front()
+	{
+		if let Some(sym_token) = next_token.as_token::<SymbolToken>()
+			&& sym_token.sym() == Symbol::LeftBrace
+		{
+			break;
+		}
+
+		let token = parser_context.tokens.pop_front().unwrap();
+
+		tokens.push_back(token.clone());
+		expr_tokens.push_back(token);
+	}
+
+	expr_tokens
+}
+
+// Calls to `void` functions may only stand on their own, in an `express` statement.
+// Reports the expression otherwise, `usage` completes "cannot be ...".
+fn check_value(parser_context: &mut ParserContext, expression: &Expression, usage: &str, info: &TokenInfo) -> bool
+{
+	if expression.vtype() != VType::Void
+	{
+		return true;
+	}
+
+	record_error(
+		parser_context,
+		&format!("value of type `{:?}` cannot be {}", VType::Void, usage),
+		info);
+
+	false
+}
+
+// Checks every `return` against the function signature, returns whether the statements always return
+fn check_returns(parser_context: &mut ParserContext, signature: &FunctionSignature, statements: &[Statement]) -> bool
+{
+	let mut returns = false;
+
+	for statement in statements
+	{
+		match statement.stype()
+		{
+			StatementType::FunctionReturn =>
+			{
+				let return_statement = statement.as_statement::<FunctionReturnStatement>().unwrap();
+				let info = statement.unparse()[0].info();
+
+				let return_type = signature.return_type();
+
+				match return_statement.expression()
+				{
+					Some(expr) if expr.vtype() == VType::Void =>
+					{
+						check_value(parser_context, expr, &format!("returned from function `{}`", signature.name()), &info);
+					}
+
+					Some(_) if return_type == VType::Void =>
+					{
+						record_error(
+							parser_context,
+							&format!(
+								"`return` with a value in function `{}` with return type `{:?}`",
+								signature.name(),
+								return_type
+							),
+							&info);
+					}
+
+					Some(expr) if expr.vtype() != return_type =>
+					{
+						record_error(
+							parser_context,
+							&format!(
+								"mismatched return type in function `{}`, expected `{:?}`, got `{:?}`",
+								signature.name(),
+								return_type,
+								expr.vtype()
+							),
+							&info);
+					}
+
+					None if return_type != VType::Void =>
+					{
+						record_error(
+							parser_context,
+							&format!(
+								"`return` without a value in function `{}` with return type `{:?}`",
+								signature.name(),
+								return_type
+							),
+							&info);
+					}
+
+					_ => {}
+				}
+
+				returns = true;
+			}
+
+			StatementType::Compound =>
+			{
+				let compound = statement.as_statement::<CompoundStatement>().unwrap();
+
+				returns |= check_returns(parser_context, signature, compound.statements());
+			}
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				let then_returns = check_returns(parser_context, signature, if_statement.then_body().statements());
+
+				let else_returns = match if_statement.else_body()
+				{
+					Some(else_body) => check_returns(parser_context, signature, std::slice::from_ref(else_body)),
+					None => false
+				};
+
+				returns |= then_returns && else_returns;
+			}
+
+			StatementType::While =>
+			{
+				let while_statement = statement.as_statement::<WhileStatement>().unwrap();
+
+				check_returns(parser_context, signature, while_statement.body().statements());
+
+				// `while true` without a `break` can only be left through `return`
+				let is_infinite = while_statement.condition()
+					.as_expression::<LiteralExpression>()
+					.and_then(|lit_expr| lit_expr.literal().as_literal::<BooleanLiteral>())
+					.is_some_and(|lit| lit.value);
+
+				if is_infinite && !contains_break(while_statement.body().statements())
+				{
+					returns = true;
+				}
+			}
+
+			_ => {}
+		}
+	}
+
+	returns
+}
+
+// Whether a `break` in the statements leaves the enclosing loop, nested loops are not searched
+fn contains_break(statements: &[Statement]) -> bool
+{
+	statements.iter().any(|statement|
+	{
+		match statement.stype()
+		{
+			StatementType::Break => true,
+
+			StatementType::Compound =>
+				contains_break(statement.as_statement::<CompoundStatement>().unwrap().statements()),
+
+			StatementType::If =>
+			{
+				let if_statement = statement.as_statement::<IfStatement>().unwrap();
+
+				contains_break(if_statement.then_body().statements())
+					|| if_statement.else_body().as_ref().is_some_and(|else_body| contains_break(std::slice::from_ref(else_body)))
+			}
+
+			_ => false
+		}
+	})
+}
+
 macro_rules! parser_error
 {
 	($context:expr, $info:expr, $fmt:literal $(, $args:expr)* $(,)?) =>
@@ -135,58 +349,132 @@ macro_rules! expect_token_type
 	};
 }
 
-fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
+// For future architecture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc
 {
-	// For future architecture
-	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-	enum Assoc
-	{
-		Left,
-		// Right // TODO: implement
-	}
+	Left,
+	// Right // TODO: implement
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator
+{
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Eq,
+	Neq,
+	Gt,
+	Gte,
+	Lt,
+	Lte,
+	And,
+	Or
+}
+
+pub fn precedence_of(op: Operator) -> (u8, Assoc)
+{
+	use Operator::*;
 
-	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-	enum Operator
+	match op
 	{
-		Add,
-		Sub,
-		Mul,
-		Div,
-		Eq,
-		Neq,
-		Gt,
-		Gte,
-		Lt,
-		Lte,
-		And,
-		Or
+		Mul | Div             => (3, Assoc::Left),
+		Add | Sub             => (2, Assoc::Left),
+		Gt | Gte | Lt | Lte |
+		Eq | Neq              => (1, Assoc::Left),
+		And                   => (0, Assoc::Left),
+		Or                    => (0, Assoc::Left)
 	}
+}
+
+fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
+{
+	// Operands on the output stack keep the range of tokens they were parsed from,
+	// parentheses included, so that a binary expression covers its whole source range.
+	// Every expression shares the tokens of the whole expression instead of a copy.
+	type Operand = (Expression, Range<usize>);
 
-	fn precedence_of(op: Operator) -> (u8, Assoc)
+	fn apply_operator(parser_context: &mut ParserContext, tokens: &Rc<[Token]>, op: Operator, op_token: &Token, stack: &mut Vec<Operand>) -> Option<()>
 	{
-		use Operator::*;
+		let info = &op_token.info();
 
-		match op
+		if stack.len() < 2
 		{
-			Mul | Div             => (3, Assoc::Left),
-			Add | Sub             => (2, Assoc::Left),
-			Gt | Gte | Lt | Lte |
-			Eq | Neq              => (1, Assoc::Left),
-			And                   => (0, Assoc::Left),
-			Or                    => (0, Assoc::Left)
+			parser_error!(
+				parser_context,
+				info,
+				"expected an operand on both sides of operator `{:?}`",
+				op
+			);
 		}
-	}
 
-	let mut expr_tokens = VecDeque::new();
+		let (rhs, rhs_range) = stack.pop().unwrap();
+		let (lhs, lhs_range) = stack.pop().unwrap();
 
-	fn apply_operator(op: Operator, expr_tokens: &mut VecDeque<Token>, stack: &mut Vec<Expression>)
-	{
-		let rhs = stack.pop().expect("Missing RHS expression");
-		let lhs = stack.pop().expect("Missing LHS expression");
+		let range = lhs_range.start..rhs_range.end;
+		let slice = TokenSlice::new(tokens.clone(), range.clone());
 
 		use Operator::*;
 
-		match op
+		// Operands keep their place in the tree even when mistyped so that a single
+		// error is reported per operator instead of a cascade from the enclosing expression
+		let expected = match op
+		{
+			Add | Sub | Mul | Div |
+			Gt | Gte | Lt | Lte   => Some(VType::Integer),
+			And | Or              => Some(VType::Boolean),
+			Eq | Neq              => None
+		};
+
+		if lhs.vtype() == VType::Void || rhs.vtype() == VType::Void
+		{
+			record_error(
+				parser_context,
+				&format!(
+					"value of type `{:?}` cannot be used as an operand of operator `{:?}`",
+					VType::Void,
+					op
+				),
+				info);
+		}
+		else
+		{
+			match expected
+			{
+				Some(vtype) if lhs.vtype() != vtype || rhs.vtype() != vtype =>
+				{
+					record_error(
+						parser_context,
+						&format!(
+							"mismatched operand types for operator `{:?}`, expected `{:?}`, got `{:?}` and `{:?}`",
+							op,
+							vtype,
+							lhs.vtype(),
+							rhs.vtype()
+						),
+						info);
+				}
+
+				None if lhs.vtype() != rhs.vtype() =>
+				{
+					record_error(
+						parser_context,
+						&format!(
+							"mismatched operand types for operator `{:?}`, got `{:?}` and `{:?}`",
+							op,
+							lhs.vtype(),
+							rhs.vtype()
+						),
+						info);
+				}
+
+				_ => {}
+			}
+		}
+
+		let expression = match op
 		{
 			Add | Sub | Mul | Div =>
 			{
@@ -199,15 +487,13 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					_   => unreachable!()
 				};
 
-				stack.push(Expression::new_arithmetic(
-					expr_tokens.clone(),
+				Expression::new_arithmetic(
+					slice,
 					VType::Integer,
 					arith_op,
 					lhs,
 					rhs
-				));
-
-				expr_tokens.clear();
+				)
 			}
 
 			Eq | Neq | Gt | Gte | Lt | Lte =>
@@ -223,14 +509,12 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					_   => unreachable!()
 				};
 
-				stack.push(Expression::new_comparison(
-					expr_tokens.clone(),
+				Expression::new_comparison(
+					slice,
 					cmp_op,
 					lhs,
 					rhs
-				));
-
-				expr_tokens.clear();
+				)
 			}
 
 			And | Or =>
@@ -242,50 +526,60 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					_   => unreachable!()
 				};
 
-				stack.push(Expression::new_boolean(
-					expr_tokens.clone(),
+				Expression::new_boolean(
+					slice,
 					bool_op,
 					lhs,
 					rhs
-				));
-
-				expr_tokens.clear();
+				)
 			}
+		};
+
+		// Operands were folded when their own operators were applied, so one level is enough
+		let (folded, error) = fold::fold(expression);
+
+		if let Some(error) = error
+		{
+			record_error(parser_context, &error.to_string(), info);
 		}
+
+		stack.push((folded, range));
+
+		Some(())
 	}
 
-	let mut output_stack: Vec<Expression> = Vec::new();
-	let mut operator_stack: Vec<Operator> = Vec::new();
+	let mut output_stack: Vec<Operand> = Vec::new();
+	let mut operator_stack: Vec<(Operator, Token)> = Vec::new();
 
-	let binding = parser_context.tokens.clone();
+	let binding: Rc<[Token]> = parser_context.tokens.iter().cloned().collect();
 
-	if binding.len() == 0
+	if binding.is_empty()
 	{
 		return None; // TODO: fix this issue with a more detailed error
 	}
 
- 	let first_token = binding.front();
+ 	let first_token = binding.first();
 
 	while let Some(token) = parser_context.tokens.pop_front()
 	{
-		expr_tokens.push_back(token.clone());
+		let position = binding.len() - parser_context.tokens.len() - 1;
 
 		match token.get_type()
 		{
 			TokenType::IntegerLiteral =>
 			{
 				let lit = token.as_token::<IntegerLiteralToken>().unwrap().clone();
-				output_stack.push(Expression::new_literal(expr_tokens.clone(), Literal::new_integer(lit.value())));
+				let range = position..position + 1;
 
-				expr_tokens.clear();
+				output_stack.push((Expression::new_literal(TokenSlice::new(binding.clone(), range.clone()), Literal::new_integer(lit.value())), range));
 			}
 
 			TokenType::BooleanLiteral =>
 			{
 				let lit = token.as_token::<BooleanLiteralToken>().unwrap().clone();
-				output_stack.push(Expression::new_literal(expr_tokens.clone(), Literal::new_boolean(lit.value())));
+				let range = position..position + 1;
 
-				expr_tokens.clear();
+				output_stack.push((Expression::new_literal(TokenSlice::new(binding.clone(), range.clone()), Literal::new_boolean(lit.value())), range));
 			}
 
 			TokenType::Arithmetic =>
@@ -301,9 +595,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 				let (prec, assoc) = precedence_of(op);
 
-				while let Some(&top_op) = operator_stack.last()
+				while let Some((top_op, _)) = operator_stack.last()
 				{
-					let (top_prec, _) = precedence_of(top_op);
+					let (top_prec, _) = precedence_of(*top_op);
 
 					let should_apply = match assoc
 					{
@@ -313,7 +607,8 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 					if should_apply
 					{
-						operator_stack.pop().map(|op| apply_operator(op, &mut expr_tokens, &mut output_stack));
+						let (top_op, top_token) = operator_stack.pop().unwrap();
+						apply_operator(parser_context, &binding, top_op, &top_token, &mut output_stack)?;
 					}
 					else
 					{
@@ -321,7 +616,7 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					}
 				}
 
-				operator_stack.push(op);
+				operator_stack.push((op, token.clone()));
 			}
 
 			TokenType::Comparison =>
@@ -339,9 +634,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 				let (prec, assoc) = precedence_of(op);
 
-				while let Some(&top_op) = operator_stack.last()
+				while let Some((top_op, _)) = operator_stack.last()
 				{
-					let (top_prec, _) = precedence_of(top_op);
+					let (top_prec, _) = precedence_of(*top_op);
 
 					let should_apply = match assoc
 					{
@@ -351,7 +646,8 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 					if should_apply
 					{
-						operator_stack.pop().map(|op| apply_operator(op, &mut expr_tokens, &mut output_stack));
+						let (top_op, top_token) = operator_stack.pop().unwrap();
+						apply_operator(parser_context, &binding, top_op, &top_token, &mut output_stack)?;
 					}
 					else
 					{
@@ -359,7 +655,7 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					}
 				}
 
-				operator_stack.push(op);
+				operator_stack.push((op, token.clone()));
 			}
 
 			TokenType::Boolean =>
@@ -373,9 +669,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 				let (prec, assoc) = precedence_of(op);
 
-				while let Some(&top_op) = operator_stack.last()
+				while let Some((top_op, _)) = operator_stack.last()
 				{
-					let (top_prec, _) = precedence_of(top_op);
+					let (top_prec, _) = precedence_of(*top_op);
 
 					let should_apply = match assoc
 					{
@@ -385,7 +681,8 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 					if should_apply
 					{
-						operator_stack.pop().map(|op| apply_operator(op, &mut expr_tokens, &mut output_stack));
+						let (top_op, top_token) = operator_stack.pop().unwrap();
+						apply_operator(parser_context, &binding, top_op, &top_token, &mut output_stack)?;
 					}
 					else
 					{
@@ -393,7 +690,7 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					}
 				}
 
-				operator_stack.push(op);
+				operator_stack.push((op, token.clone()));
 			}
 
 			TokenType::Symbol =>
@@ -409,8 +706,6 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 						while let Some(next_token) = parser_context.tokens.pop_front()
 						{
-							expr_tokens.push_back(next_token.clone());
-
 							if next_token.get_type() == TokenType::Symbol
 							{
 								let sub_sym_token = next_token.as_token::<SymbolToken>().unwrap();
@@ -447,7 +742,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 							source: parser_context.source,
 							tokens: sub_tokens,
 							symbols_table: parser_context.symbols_table.clone(),
-							errors: vec![]
+							errors: vec![],
+							resolutions: vec![],
+							loop_depth: parser_context.loop_depth
 						};
 
 						let inner_expr = parse_expression(&mut inner_context);
@@ -462,8 +759,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 						}
 
 						parser_context.errors.append(&mut inner_context.errors);
+						parser_context.resolutions.append(&mut inner_context.resolutions);
 
-						output_stack.push(inner_expr.unwrap());
+						output_stack.push((inner_expr.unwrap(), position..binding.len() - parser_context.tokens.len()));
 					},
 
 					Symbol::RightParen =>
@@ -496,7 +794,6 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 				if name == "invoke"
 				{
 					let ident_token = next_token!(parser_context, ident_token, "an identifier token");
-					expr_tokens.push_back(ident_token.clone());
 
 					let func_name = expect_token_type!(
 						parser_context,
@@ -505,6 +802,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 						"expected identifier token after `invoke`"
 					).name();
 
+					let declaration = parser_context.symbols_table.function_declaration(&func_name).cloned();
+					record_resolution(parser_context, &ident_token.info(), declaration);
+
 					let func_sign_opt = parser_context.symbols_table
 						.get_function(&func_name);
 
@@ -523,7 +823,6 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 					let return_type = func_sign.return_type().clone();
 					
 					let begin_token = next_token!(parser_context, ident_token, "a symbol token");
-					expr_tokens.push_back(begin_token.clone());
 
 					let begin_token = expect_token_type!(
 						parser_context,
@@ -548,8 +847,6 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 					while let Some(next_token) = parser_context.tokens.pop_front()
 					{
-						expr_tokens.push_back(next_token.clone());
-
 						if next_token.get_type() == TokenType::Symbol
 						{
 							let sub_sym_token = next_token.as_token::<SymbolToken>().unwrap();
@@ -581,34 +878,48 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 						);
 					}
 
+					let range = position..binding.len() - parser_context.tokens.len();
+
 					let mut expressions_passed= VecDeque::new();
 					let mut current_expression = VecDeque::new();
 
+					// Only commas outside of nested parentheses separate arguments
+					let mut depth = 0;
+
 					while let Some(sub_token) = sub_tokens.pop_front()
 					{
-						expr_tokens.push_back(sub_token.clone());
-
-						if sub_token.get_type() == TokenType::Symbol
+						if let Some(sub_sym_token) = sub_token.as_token::<SymbolToken>()
 						{
-							let sub_token = expect_token_type!(
-								parser_context,
-								sub_token,
-								SymbolToken,
-								"???"
-							);
-
-							if sub_token.sym() == Symbol::Comma
+							match sub_sym_token.sym()
 							{
-								expressions_passed.push_back(current_expression.clone());
-								current_expression.clear();
-								continue;
+								Symbol::LeftParen => depth += 1,
+								Symbol::RightParen => depth -= 1,
+
+								Symbol::Comma if depth == 0 =>
+								{
+									if current_expression.is_empty()
+									{
+										parser_error!(
+											parser_context,
+											sub_sym_token.info(),
+											"expected an argument expression before `{:?}`",
+											Symbol::Comma
+										);
+									}
+
+									expressions_passed.push_back(current_expression.clone());
+									current_expression.clear();
+									continue;
+								}
+
+								_ => {}
 							}
 						}
 
 						current_expression.push_back(sub_token);
 					}
 
-					if current_expression.len() != 0
+					if !current_expression.is_empty()
 					{
 						expressions_passed.push_back(current_expression);
 					}
@@ -624,20 +935,26 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 						);
 					}
 
+					let parameters = func_sign.parameters();
 					let mut passed_arguments = VecDeque::new();
 
-					for expr_tokens in expressions_passed
+					for (position, expr_tokens) in expressions_passed.into_iter().enumerate()
 					{
 						let mut expr_context = ParserContext
 						{
 							source: parser_context.source,
 							tokens: expr_tokens.clone(),
 							symbols_table: parser_context.symbols_table.clone(),
-							errors: vec![]
+							errors: vec![],
+							resolutions: vec![],
+							loop_depth: parser_context.loop_depth
 						};
 
 						let expr = parse_expression(&mut expr_context);
 
+						parser_context.errors.append(&mut expr_context.errors);
+						parser_context.resolutions.append(&mut expr_context.resolutions);
+
 						if expr.is_none()
 						{
 							parser_error!(
@@ -647,15 +964,31 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 							);
 						}
 
-						parser_context.errors.append(&mut expr_context.errors);
+						let expr = expr.unwrap();
+						let param_vtype = parameters[position].vtype();
+
+						let usage = format!("used as argument {} of `{}`", position + 1, func_name);
+
+						if check_value(parser_context, &expr, &usage, &expr_tokens[0].info()) && expr.vtype() != param_vtype
+						{
+							record_error(
+								parser_context,
+								&format!(
+									"mismatched type for argument {} of `{}`, expected `{:?}`, got `{:?}`",
+									position + 1,
+									func_name,
+									param_vtype,
+									expr.vtype()
+								),
+								&expr_tokens[0].info());
+						}
 
-						passed_arguments.push_back(expr.unwrap());
+						passed_arguments.push_back(expr);
 					}
 
-					let function_call_expr = Expression::new_function_call(expr_tokens.clone(), return_type, func_name, passed_arguments);
-					expr_tokens.clear();
+					let function_call_expr = Expression::new_function_call(TokenSlice::new(binding.clone(), range.clone()), return_type, func_name, passed_arguments);
 
-					output_stack.push(function_call_expr);
+					output_stack.push((function_call_expr, range));
 				}
 				else
 				{
@@ -673,12 +1006,15 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 
 					let id = id.unwrap();
 	
-					let vtype = parser_context.symbols_table.lookup(&name).unwrap();
+					let vtype = parser_context.symbols_table.lookup(&name).unwrap().clone();
 
-					let var_ref_expr = Expression::new_variable(expr_tokens.clone(), vtype.clone(), id);
-					expr_tokens.clear();
+					let declaration = parser_context.symbols_table.declaration(id).cloned();
+					record_resolution(parser_context, &token.info(), declaration);
 
-					output_stack.push(var_ref_expr);
+					let range = position..position + 1;
+					let var_ref_expr = Expression::new_variable(TokenSlice::new(binding.clone(), range.clone()), vtype.clone(), id);
+
+					output_stack.push((var_ref_expr, range));
 				}
 			}
 
@@ -694,9 +1030,9 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 		}
 	}
 
-	while let Some(op) = operator_stack.pop()
+	while let Some((op, op_token)) = operator_stack.pop()
 	{
-		apply_operator(op, &mut expr_tokens, &mut output_stack);
+		apply_operator(parser_context, &binding, op, &op_token, &mut output_stack)?;
 	}
 
 	if output_stack.len() != 1
@@ -709,94 +1045,295 @@ fn parse_expression(parser_context: &mut ParserContext) -> Option<Expression>
 		);
 	}
 
-	output_stack.pop()
+	output_stack.pop().map(|(expression, _)| expression)
 }
 
-fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Option<Statement>
+// The rest of a function statement, from its parameters on, parsed in the scope of the parameters
+fn parse_function(parser_context: &mut ParserContext, mut tokens: VecDeque<Token>, mut sub_tokens: VecDeque<Token>, t_name: Token, func_name: String) -> Option<Statement>
 {
-	let mut tokens = VecDeque::new();
+	let mut parameters = VecDeque::new();
 
-	while let Some(t) = parser_context.tokens.pop_front()
+	while let Some(sub_token) = sub_tokens.pop_front()
 	{
-		tokens.push_back(t.clone());
+		let param_id = expect_token_type!(
+			parser_context,
+			sub_token,
+			IdentifierToken,
+			"expected identifier token"
+		).name();
 
-		match t.get_type()
+		let Some(type_token) = sub_tokens.pop_front() else
 		{
-			token::TokenType::Symbol =>
-			{
-				let t = t.as_token::<SymbolToken>().unwrap().clone();
-
-				if t.sym() != Symbol::LeftBrace
-				{
-					parser_error!(
-						parser_context,
-						t.info(),
-						"expected symbol `{:?}` when beginning a statement, got `{:?}`",
-						Symbol::LeftBrace,
-						t.sym()
-					);
-				}
-				
-				let mut depth = 1;
-				let mut sub_tokens = VecDeque::new();
-				
-				while let Some(next_token) = parser_context.tokens.pop_front()
-				{
-					tokens.push_back(next_token.clone());
-
-					if next_token.get_type() == TokenType::Symbol
-					{
-						let sub_sym_token = next_token.as_token::<SymbolToken>().unwrap();
+			record_error(
+				parser_context,
+				"tokens should not end here, expected a type token",
+				&sub_token.info());
+			
+			parser_context.tokens.clear();
 
-						match sub_sym_token.sym()
-						{
-							Symbol::LeftBrace => depth += 1,
-							Symbol::RightBrace =>
-							{
-								depth -= 1;
-								if depth == 0
-								{
-									break;
-								}
-							}
-							_ => {}
-						}
-					}
+			return None;
+		};
 
-					sub_tokens.push_back(next_token);
-				}
+		tokens.push_back(type_token.clone());
 
-				if depth != 0
-				{
-					parser_error!(
-						parser_context,
-						t.info(),
-						"no close braces found for compound statement"
-					);
-				}
+		let param_vtype = expect_token_type!(
+			parser_context,
+			type_token,
+			TypeToken,
+			"expected type token after param identifier `{}`",
+			param_id
+		).vtype();
 
-				let mut sub_context = ParserContext
-				{
-					source: parser_context.source,
-					tokens: sub_tokens,
-					symbols_table: parser_context.symbols_table.clone(),
-					errors: vec![],
-				};
+		if let Some(maybe_comma_token) = sub_tokens.pop_front()
+		{
+			tokens.push_back(maybe_comma_token.clone());
+			
+			let comma_token = expect_token_type!(
+				parser_context,
+				maybe_comma_token,
+				SymbolToken,
+				"expected symbol token after param type, got `{:?}`",
+				maybe_comma_token.get_type()
+			);
 
-				if manage_scope
-				{
-					sub_context.symbols_table.push_scope();
-				}
+			let sym = comma_token.sym();
 
-				let mut statements = Vec::new();
+			if sym != Symbol::Comma
+			{
+				parser_error!(
+					parser_context,
+					comma_token.info(),
+					"expected symbol `{:?}` to end param entry, got `{:?}`",
+					Symbol::Comma,
+					sym
+				);
+			}
 
-				while !sub_context.tokens.is_empty()
+			if sub_tokens.is_empty()
+			{
+				parser_error!(
+					parser_context,
+					comma_token.info(),
+					"expected a parameter after symbol `{:?}` in function signature",
+					Symbol::Comma
+				);
+			}
+		}
+
+		parser_context.symbols_table.define(&param_id, param_vtype.clone(), DeclarationKind::Parameter, sub_token.info());
+
+		let param = Parameter::new(
+			parser_context.symbols_table.get_id(&param_id).unwrap(),
+			param_vtype.clone()
+		);
+
+		let declaration = parser_context.symbols_table.declaration(param.id()).cloned();
+		record_resolution(parser_context, &sub_token.info(), declaration);
+
+		parameters.push_back(param);
+	}
+
+	let t_type_token = next_token!(parser_context, t_name, "a type token");
+	tokens.push_back(t_type_token.clone());
+
+	let vtype = expect_token_type!(
+		parser_context,
+		t_type_token,
+		TypeToken,
+		"expected type token to end function signature with identifier `{}`",
+		func_name
+	).vtype();
+
+	let previous_sign = parser_context.symbols_table.get_function(&func_name).cloned();
+
+	if let Some(previous_sign) = previous_sign
+	{
+		// A function may be declared any number of times as long as the signatures agree
+		let same_signature = previous_sign.return_type() == vtype
+			&& previous_sign.parameters().len() == parameters.len()
+			&& previous_sign.parameters().iter().zip(parameters.iter()).all(|(a, b)| a.vtype() == b.vtype());
+
+		if !same_signature
+		{
+			record_error(
+				parser_context,
+				&format!(
+					"conflicting signature for function `{}`, previously declared with a different signature",
+					func_name
+				),
+				&t_name.info());
+		}
+	}
+	else
+	{
+		parser_context.symbols_table.define_function(&func_name, vtype.clone(), parameters.clone(), t_name.info());
+	}
+
+	let declaration = parser_context.symbols_table.function_declaration(&func_name).cloned();
+	record_resolution(parser_context, &t_name.info(), declaration);
+
+	let next_token = next_token!(parser_context, t_name, "a symbol token");
+	tokens.push_back(next_token.clone());
+
+	let sym = expect_token_type!(
+		parser_context,
+		next_token,
+		SymbolToken,
+		"expected a symbol token after function signature"
+	).sym();
+	
+	let func_sign = FunctionSignature::new(func_name, vtype.clone(), parameters);
+
+	match sym
+	{
+		Symbol::Semicolon =>
+		{
+			Some(Statement::new_function_declare(tokens, func_sign))
+		},
+
+		Symbol::LeftBrace =>
+		{
+			if !parser_context.symbols_table.define_function_body(&func_sign.name())
+			{
+				record_error(
+					parser_context,
+					&format!("function `{}` is already defined", func_sign.name()),
+					&t_name.info());
+			}
+
+			// The brace belongs to the body, which is appended once parsed
+			tokens.pop_back();
+
+			parser_context.tokens.push_front(next_token); // reinsert token
+			let result = parse_statement(parser_context, false)?;
+
+			let body = result
+				.as_statement::<CompoundStatement>()
+				.expect("Expected compound statement")
+				.clone();
+
+			let always_returns = check_returns(parser_context, &func_sign, body.statements());
+
+			if func_sign.return_type() != VType::Void && !always_returns
+			{
+				record_error(
+					parser_context,
+					&format!(
+						"function `{}` with return type `{:?}` may end without returning a value",
+						func_sign.name(),
+						func_sign.return_type()
+					),
+					&t_name.info());
+			}
+
+			tokens.extend(body.unparse());
+
+			Some(Statement::new_function_define(
+				tokens,
+				func_sign,
+				body
+			))
+		},
+
+		_ =>
+		{
+			parser_error!(
+				parser_context,
+				next_token.info(),
+				"expected symbol `{:?}` or `{:?}` after function signature, got `{:?}`",
+				Symbol::Semicolon,
+				Symbol::LeftBrace,
+				sym
+			);
+		}
+	}
+}
+
+fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Option<Statement>
+{
+	let mut tokens = VecDeque::new();
+
+	if let Some(t) = parser_context.tokens.pop_front()
+	{
+		tokens.push_back(t.clone());
+
+		match t.get_type()
+		{
+			token::TokenType::Symbol =>
+			{
+				let t = t.as_token::<SymbolToken>().unwrap().clone();
+
+				if t.sym() != Symbol::LeftBrace
+				{
+					parser_error!(
+						parser_context,
+						t.info(),
+						"expected symbol `{:?}` when beginning a statement, got `{:?}`",
+						Symbol::LeftBrace,
+						t.sym()
+					);
+				}
+				
+				let mut depth = 1;
+				let mut sub_tokens = VecDeque::new();
+				
+				while let Some(next_token) = parser_context.tokens.pop_front()
+				{
+					tokens.push_back(next_token.clone());
+
+					if next_token.get_type() == TokenType::Symbol
+					{
+						let sub_sym_token = next_token.as_token::<SymbolToken>().unwrap();
+
+						match sub_sym_token.sym()
+						{
+							Symbol::LeftBrace => depth += 1,
+							Symbol::RightBrace =>
+							{
+								depth -= 1;
+								if depth == 0
+								{
+									break;
+								}
+							}
+							_ => {}
+						}
+					}
+
+					sub_tokens.push_back(next_token);
+				}
+
+				if depth != 0
+				{
+					parser_error!(
+						parser_context,
+						t.info(),
+						"no close braces found for compound statement"
+					);
+				}
+
+				let mut sub_context = ParserContext
 				{
-					let result = parse_statement(&mut sub_context, true);
+					source: parser_context.source,
+					tokens: sub_tokens,
+					symbols_table: parser_context.symbols_table.clone(),
+					errors: vec![],
+					resolutions: vec![],
+					loop_depth: parser_context.loop_depth
+				};
 
-					if result.is_some()
+				if manage_scope
+				{
+					sub_context.symbols_table.push_scope();
+				}
+
+				let mut statements = Vec::new();
+
+				while !sub_context.tokens.is_empty()
+				{
+					if let Some(statement) = parse_statement(&mut sub_context, true)
 					{
-						statements.push(result.unwrap());
+						statements.push(statement);
 					}
 				}
 
@@ -806,6 +1343,7 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 				}
 
 				parser_context.errors.append(&mut sub_context.errors);
+				parser_context.resolutions.append(&mut sub_context.resolutions);
 				parser_context.symbols_table = sub_context.symbols_table;
 
 				let statement = Statement::new_compound(tokens, statements);
@@ -878,155 +1416,14 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						sub_tokens.push_back(next_token);
 					}
 
-					let mut parameters = VecDeque::new();
-
+					// Early returns leave the scope of the parameters too
 					parser_context.symbols_table.push_scope();
 
-					while let Some(sub_token) = sub_tokens.pop_front()
-					{
-						let param_id = expect_token_type!(
-							parser_context,
-							sub_token,
-							IdentifierToken,
-							"expected identifier token"
-						).name();
-
-						let type_token_opt = sub_tokens.pop_front();
-						let type_token;
-
-						if type_token_opt.is_none()
-						{
-							record_error(
-								parser_context,
-								"tokens should not end here, expected a type token",
-								&sub_token.info());
-							
-							parser_context.tokens.clear();
-
-							return None;
-						}
-						else
-						{
-							type_token = type_token_opt.unwrap();
-						}
-
-						tokens.push_back(type_token.clone());
-
-						let param_vtype = expect_token_type!(
-							parser_context,
-							type_token,
-							TypeToken,
-							"expected type token after param identifier `{}`",
-							param_id
-						).vtype();
-
-						let maybe_comma_token = sub_tokens.pop_front();
-
-						if maybe_comma_token.is_some()
-						{
-							tokens.push_back(maybe_comma_token.as_ref().unwrap().clone());
-							
-							let comma_token = expect_token_type!(
-								parser_context,
-								maybe_comma_token.as_ref().unwrap(),
-								SymbolToken,
-								"expected symbol token after param type, got `{:?}`",
-								maybe_comma_token.as_ref().unwrap().get_type()
-							);
-
-							let sym = comma_token.sym();
-
-							if sym != Symbol::Comma
-							{
-								parser_error!(
-									parser_context,
-									comma_token.info(),
-									"expected symbol `{:?}` to end param entry, got `{:?}`",
-									Symbol::Comma,
-									sym
-								);
-							}
-							
-						}
-
-						parser_context.symbols_table.define(&param_id, param_vtype.clone());
-
-						let param = Parameter::new(
-							parser_context.symbols_table.get_id(&param_id).unwrap(),
-							param_vtype.clone()
-						);
-
-						parameters.push_back(param);
-					}
-
-					let t_type_token = next_token!(parser_context, t_name, "a type token");
-					tokens.push_back(t_type_token.clone());
-
-					let vtype = expect_token_type!(
-						parser_context,
-						t_type_token,
-						TypeToken,
-						"expected type token to end function signature with identifier `{}`",
-						func_name
-					).vtype();
-
-					parser_context.symbols_table.define_function(&func_name, vtype.clone(), parameters.clone());
-
-					let next_token = next_token!(parser_context, t_name, "a symbol token");
-					tokens.push_back(next_token.clone());
-
-					let sym = expect_token_type!(
-						parser_context,
-						next_token,
-						SymbolToken,
-						"expected a symbol token after function signature"
-					).sym();
-					
-					let func_sign = FunctionSignature::new(func_name, vtype.clone(), parameters);
-
-					match sym
-					{
-						Symbol::Semicolon =>
-						{
-							parser_context.symbols_table.pop_scope();
-
-							let func_declare_statement = Statement::new_function_declare(tokens, func_sign);
+					let statement = parse_function(parser_context, tokens, sub_tokens, t_name, func_name);
 
-							return Some(func_declare_statement);
-						},
+					parser_context.symbols_table.pop_scope();
 
-						Symbol::LeftBrace =>
-						{
-							parser_context.tokens.push_front(next_token); // reinsert token
-							let result = parse_statement(parser_context, false);
-
-							let func_define_statement = Statement::new_function_define(
-								tokens,
-								func_sign,
-								result
-									.unwrap()
-									.as_statement::<CompoundStatement>()
-									.expect("Expected compound statement")
-									.clone()
-							);
-							
-							parser_context.symbols_table.pop_scope();
-		
-							return Some(func_define_statement);
-						},
-
-						_ =>
-						{
-							parser_error!(
-								parser_context,
-								next_token.info(),
-								"expected symbol `{:?}` or `{:?}` after function signature, got `{:?}`",
-								Symbol::Semicolon,
-								Symbol::LeftBrace,
-								sym
-							);
-						}
-					}
+					return statement;
 				}
 				else if t.name() == "return"
 				{
@@ -1041,13 +1438,8 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 
 					let mut expr_tokens = VecDeque::new();
 
-					loop
+					while let Some(next_token) = parser_context.tokens.front()
 					{
-						let Some(next_token) = parser_context.tokens.front() else
-						{
-							break;
-						};
-
 						tokens.push_back(next_token.clone());
 
 						if next_token.get_type() == TokenType::Symbol
@@ -1055,12 +1447,10 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 							let sym_token_opt = next_token.as_token::<SymbolToken>();
 
 							if let Some(sym_token) = sym_token_opt
+								&& sym_token.sym() == Symbol::Semicolon
 							{
-								if sym_token.sym() == Symbol::Semicolon
-								{
-									parser_context.tokens.pop_front(); // consume ;
-									break;
-								}
+								parser_context.tokens.pop_front(); // consume ;
+								break;
 							}
 						}
 
@@ -1072,7 +1462,7 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 
 					let expr;
 
-					if expr_tokens.len() == 0
+					if expr_tokens.is_empty()
 					{
 						expr = None;
 					}
@@ -1083,20 +1473,25 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 							source: parser_context.source,
 							tokens: expr_tokens,
 							symbols_table: parser_context.symbols_table.clone(),
-							errors: vec![]
+							errors: vec![],
+							resolutions: vec![],
+							loop_depth: parser_context.loop_depth
 						};
 
 						expr = parse_expression(&mut expr_context);
 
 						parser_context.errors.append(&mut expr_context.errors);
+						parser_context.resolutions.append(&mut expr_context.resolutions);
 
+						// The statement is taken up to its `;` already, there is nothing to recover
 						if expr.is_none()
 						{
-							parser_error!(
+							record_error(
 								parser_context,
-								t.info(),
-								"no expression parsed for `return` statement"
-							);
+								"no expression parsed for `return` statement",
+								&t.info());
+
+							return None;
 						}
 					}
 
@@ -1173,15 +1568,11 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						tokens.push_back(next_token.clone());
 
 						if next_token.get_type() == TokenType::Symbol
+							&& let Some(sym_token) = next_token.as_token::<SymbolToken>()
+							&& sym_token.sym() == Symbol::Semicolon
 						{
-							if let Some(sym_token) = next_token.as_token::<SymbolToken>()
-							{
-								if sym_token.sym() == Symbol::Semicolon
-								{
-									parser_context.tokens.pop_front(); // consume ;
-									break;
-								}
-							}
+							parser_context.tokens.pop_front(); // consume ;
+							break;
 						}
 
 						if let Some(tok) = parser_context.tokens.pop_front()
@@ -1190,30 +1581,58 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						}
 					}
 
+					let expr_info = expr_tokens.front().map(|token| token.info());
+
 					let mut expr_context = ParserContext
 					{
 						source: parser_context.source,
 						tokens: expr_tokens,
 						symbols_table: parser_context.symbols_table.clone(),
-						errors: vec![]
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
 					};
 
 					let expr = parse_expression(&mut expr_context);
 
 					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
+
+					parser_context.symbols_table.define(&i_name, vtype.clone(), DeclarationKind::Local, t_name.info());
 
-					parser_context.symbols_table.define(&i_name, vtype.clone());
+					let id = parser_context.symbols_table.get_id(&i_name).unwrap();
+					let declaration = parser_context.symbols_table.declaration(id).cloned();
+					record_resolution(parser_context, &t_name.info(), declaration);
 
 					if expr.is_none()
 					{
-						parser_error!(
+						record_error(
 							parser_context,
-							t.info(),
-							"no expression parsed for `let` statement"
-						);
+							"no expression parsed for `let` statement",
+							&t.info());
+
+						return None;
+					}
+
+					let expr = expr.unwrap();
+					let expr_info = expr_info.unwrap();
+
+					let usage = format!("used to initialize variable `{}`", i_name);
+
+					if check_value(parser_context, &expr, &usage, &expr_info) && expr.vtype() != vtype
+					{
+						record_error(
+							parser_context,
+							&format!(
+								"mismatched type for variable `{}` in `let` statement, expected `{:?}`, got `{:?}`",
+								i_name,
+								vtype,
+								expr.vtype()
+							),
+							&expr_info);
 					}
 
-					let statement = Statement::new_declare(tokens, vtype, parser_context.symbols_table.get_id(&i_name).unwrap(), expr.unwrap());
+					let statement = Statement::new_declare(tokens, vtype, id, expr);
 
 					return Some(statement);
 				}
@@ -1253,6 +1672,9 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 
 					let id = id.unwrap();
 
+					let declaration = parser_context.symbols_table.declaration(id).cloned();
+					record_resolution(parser_context, &t_name.info(), declaration);
+
 					let eq_token = next_token!(parser_context, t_name, "'=' after identifier token");
 					tokens.push_back(eq_token.clone());
 
@@ -1281,15 +1703,11 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						tokens.push_back(next_token.clone());
 
 						if next_token.get_type() == TokenType::Symbol
+							&& let Some(sym_token) = next_token.as_token::<SymbolToken>()
+							&& sym_token.sym() == Symbol::Semicolon
 						{
-							if let Some(sym_token) = next_token.as_token::<SymbolToken>()
-							{
-								if sym_token.sym() == Symbol::Semicolon
-								{
-									parser_context.tokens.pop_front(); // consume ;
-									break;
-								}
-							}
+							parser_context.tokens.pop_front(); // consume ;
+							break;
 						}
 
 						if let Some(tok) = parser_context.tokens.pop_front()
@@ -1298,28 +1716,53 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						}
 					}
 
+					let expr_info = expr_tokens.front().map(|token| token.info());
+
 					let mut expr_context = ParserContext
 					{
 						source: parser_context.source,
 						tokens: expr_tokens,
 						symbols_table: parser_context.symbols_table.clone(),
-						errors: vec![]
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
 					};
 
 					let expr = parse_expression(&mut expr_context);
 
 					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
 
 					if expr.is_none()
 					{
-						parser_error!(
+						record_error(
 							parser_context,
-							t.info(),
-							"no expression parsed for `set` statement"
-						);
+							"no expression parsed for `set` statement",
+							&t.info());
+
+						return None;
 					}
 
-					let statement = Statement::new_assign(tokens, id, expr.unwrap());
+					let expr = expr.unwrap();
+					let expr_info = expr_info.unwrap();
+					let vtype = parser_context.symbols_table.lookup(&i_name).unwrap().clone();
+
+					let usage = format!("assigned to variable `{}`", i_name);
+
+					if check_value(parser_context, &expr, &usage, &expr_info) && expr.vtype() != vtype
+					{
+						record_error(
+							parser_context,
+							&format!(
+								"mismatched type for variable `{}` in `set` statement, expected `{:?}`, got `{:?}`",
+								i_name,
+								vtype,
+								expr.vtype()
+							),
+							&expr_info);
+					}
+
+					let statement = Statement::new_assign(tokens, id, expr);
 
 					return Some(statement);
 				}
@@ -1336,13 +1779,8 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 
 					let mut expr_tokens: VecDeque<Token> = VecDeque::new();
 
-					loop
+					while let Some(next_token) = parser_context.tokens.front()
 					{
-						let Some(next_token) = parser_context.tokens.front() else
-						{
-							break;
-						};
-
 						tokens.push_back(next_token.clone());
 
 						if next_token.get_type() == TokenType::Symbol
@@ -1350,12 +1788,10 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 							let sym_token_opt = next_token.as_token::<SymbolToken>();
 
 							if let Some(sym_token) = sym_token_opt
+								&& sym_token.sym() == Symbol::Semicolon
 							{
-								if sym_token.sym() == Symbol::Semicolon
-								{
-									parser_context.tokens.pop_front(); // consume ;
-									break;
-								}
+								parser_context.tokens.pop_front(); // consume ;
+								break;
 							}
 						}
 
@@ -1365,28 +1801,38 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						}
 					}
 
+					let expr_info = expr_tokens.front().map(|token| token.info());
+
 					let mut expr_context = ParserContext
 					{
 						source: parser_context.source,
 						tokens: expr_tokens,
 						symbols_table: parser_context.symbols_table.clone(),
-						errors: vec![]
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
 					};
 
 					let expr = parse_expression(&mut expr_context);
 
 					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
 
 					if expr.is_none()
 					{
-						parser_error!(
+						record_error(
 							parser_context,
-							t.info(),
-							"no expression parsed for `print` statement"
-						);
+							"no expression parsed for `print` statement",
+							&t.info());
+
+						return None;
 					}
 
-					return Some(Statement::new_print(tokens, expr.unwrap()));
+					let expr = expr.unwrap();
+
+					check_value(parser_context, &expr, "printed", &expr_info.unwrap());
+
+					return Some(Statement::new_print(tokens, expr));
 				}
 				else if t.name() == "express"
 				{
@@ -1401,13 +1847,8 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 
 					let mut expr_tokens: VecDeque<Token> = VecDeque::new();
 
-					loop
+					while let Some(next_token) = parser_context.tokens.front()
 					{
-						let Some(next_token) = parser_context.tokens.front() else
-						{
-							break;
-						};
-
 						tokens.push_back(next_token.clone());
 
 						if next_token.get_type() == TokenType::Symbol
@@ -1415,12 +1856,10 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 							let sym_token_opt = next_token.as_token::<SymbolToken>();
 
 							if let Some(sym_token) = sym_token_opt
+								&& sym_token.sym() == Symbol::Semicolon
 							{
-								if sym_token.sym() == Symbol::Semicolon
-								{
-									parser_context.tokens.pop_front(); // consume ;
-									break;
-								}
+								parser_context.tokens.pop_front(); // consume ;
+								break;
 							}
 						}
 
@@ -1435,23 +1874,258 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 						source: parser_context.source,
 						tokens: expr_tokens,
 						symbols_table: parser_context.symbols_table.clone(),
-						errors: vec![]
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
 					};
 
 					let expr = parse_expression(&mut expr_context);
 
 					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
 
 					if expr.is_none()
+					{
+						record_error(
+							parser_context,
+							"no expression parsed for `express` statement",
+							&t.info());
+
+						return None;
+					}
+
+					return Some(Statement::new_expression(tokens, expr.unwrap()));
+				}
+				else if t.name() == "if"
+				{
+					if parser_context.symbols_table.scope() == 1
 					{
 						parser_error!(
 							parser_context,
 							t.info(),
-							"no expression parsed for `express` statement"
+							"`if` statement is not allowed here"
 						);
 					}
 
-					return Some(Statement::new_expression(tokens, expr.unwrap()));
+					// Conditional: if <expr> { ... } [else if <expr> { ... }] [else { ... }]
+					let expr_tokens = take_condition_tokens(parser_context, &mut tokens);
+
+					if parser_context.tokens.is_empty()
+					{
+						parser_error!(
+							parser_context,
+							t.info(),
+							"expected symbol `{:?}` to begin `if` body",
+							Symbol::LeftBrace
+						);
+					}
+
+					let mut expr_context = ParserContext
+					{
+						source: parser_context.source,
+						tokens: expr_tokens,
+						symbols_table: parser_context.symbols_table.clone(),
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
+					};
+
+					let expr = parse_expression(&mut expr_context);
+
+					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
+
+					match &expr
+					{
+						None => record_error(
+							parser_context,
+							"no expression parsed for `if` condition",
+							&t.info()),
+
+						Some(condition) if condition.vtype() != VType::Boolean => record_error(
+							parser_context,
+							&format!(
+								"expected condition of type `{:?}` in `if` statement, got `{:?}`",
+								VType::Boolean,
+								condition.vtype()
+							),
+							&t.info()),
+
+						_ => ()
+					}
+
+					let then_body = parse_statement(parser_context, true)?;
+					tokens.extend(then_body.unparse());
+
+					let mut else_body = None;
+
+					let has_else = parser_context.tokens
+						.front()
+						.and_then(|next_token| next_token.as_token::<IdentifierToken>())
+						.is_some_and(|ident_token| ident_token.name() == "else");
+
+					if has_else
+					{
+						let t_else = parser_context.tokens.pop_front().unwrap();
+						tokens.push_back(t_else.clone());
+
+						let t_next = next_token!(parser_context, t_else, "`if` or a symbol token");
+
+						let is_else_if = t_next
+							.as_token::<IdentifierToken>()
+							.is_some_and(|ident_token| ident_token.name() == "if");
+
+						let is_else_body = t_next
+							.as_token::<SymbolToken>()
+							.is_some_and(|sym_token| sym_token.sym() == Symbol::LeftBrace);
+
+						if !is_else_if && !is_else_body
+						{
+							parser_error!(
+								parser_context,
+								t_next.info(),
+								"expected `if` or symbol `{:?}` after `else`",
+								Symbol::LeftBrace
+							);
+						}
+
+						parser_context.tokens.push_front(t_next); // reinsert token
+
+						let statement = parse_statement(parser_context, true)?;
+						tokens.extend(statement.unparse());
+
+						else_body = Some(statement);
+					}
+
+					let statement = Statement::new_if(
+						tokens,
+						expr?,
+						then_body
+							.as_statement::<CompoundStatement>()
+							.expect("Expected compound statement")
+							.clone(),
+						else_body
+					);
+
+					return Some(statement);
+				}
+				else if t.name() == "while"
+				{
+					if parser_context.symbols_table.scope() == 1
+					{
+						parser_error!(
+							parser_context,
+							t.info(),
+							"`while` statement is not allowed here"
+						);
+					}
+
+					// Loop: while <expr> { ... }
+					let expr_tokens = take_condition_tokens(parser_context, &mut tokens);
+
+					if parser_context.tokens.is_empty()
+					{
+						parser_error!(
+							parser_context,
+							t.info(),
+							"expected symbol `{:?}` to begin `while` body",
+							Symbol::LeftBrace
+						);
+					}
+
+					let mut expr_context = ParserContext
+					{
+						source: parser_context.source,
+						tokens: expr_tokens,
+						symbols_table: parser_context.symbols_table.clone(),
+						errors: vec![],
+						resolutions: vec![],
+						loop_depth: parser_context.loop_depth
+					};
+
+					let expr = parse_expression(&mut expr_context);
+
+					parser_context.errors.append(&mut expr_context.errors);
+					parser_context.resolutions.append(&mut expr_context.resolutions);
+
+					match &expr
+					{
+						None => record_error(
+							parser_context,
+							"no expression parsed for `while` condition",
+							&t.info()),
+
+						Some(condition) if condition.vtype() != VType::Boolean => record_error(
+							parser_context,
+							&format!(
+								"expected condition of type `{:?}` in `while` statement, got `{:?}`",
+								VType::Boolean,
+								condition.vtype()
+							),
+							&t.info()),
+
+						_ => ()
+					}
+
+					parser_context.loop_depth += 1;
+					let body = parse_statement(parser_context, true);
+					parser_context.loop_depth -= 1;
+
+					let body = body?;
+					tokens.extend(body.unparse());
+
+					let statement = Statement::new_while(
+						tokens,
+						expr?,
+						body
+							.as_statement::<CompoundStatement>()
+							.expect("Expected compound statement")
+							.clone()
+					);
+
+					return Some(statement);
+				}
+				else if t.name() == "break" || t.name() == "continue"
+				{
+					if parser_context.loop_depth == 0
+					{
+						parser_error!(
+							parser_context,
+							t.info(),
+							"`{}` statement is not allowed here",
+							t.name()
+						);
+					}
+
+					let t_sym = next_token!(parser_context, t, "a symbol token");
+					tokens.push_back(t_sym.clone());
+
+					let sym = expect_token_type!(
+						parser_context,
+						t_sym,
+						SymbolToken,
+						"expected symbol `;` after `{}`",
+						t.name()
+					).sym();
+
+					if sym != Symbol::Semicolon
+					{
+						parser_error!(
+							parser_context,
+							t_sym.info(),
+							"expected symbol `{:?}` after `{}`, got `{:?}`",
+							Symbol::Semicolon,
+							t.name(),
+							sym
+						);
+					}
+
+					if t.name() == "break"
+					{
+						return Some(Statement::new_break(tokens));
+					}
+
+					return Some(Statement::new_continue(tokens));
 				}
 				else
 				{
@@ -1480,31 +2154,146 @@ fn parse_statement(parser_context: &mut ParserContext, manage_scope: bool) -> Op
 }
 
 pub fn parse_root(source: String) -> (Root, Vec<ParserError>)
+{
+	let (root, _, errors) = parse_root_with_index(source);
+
+	(root, errors)
+}
+
+// Also records, for every identifier that resolves, where its name is declared
+pub fn parse_root_with_index(source: String) -> (Root, ResolutionIndex, Vec<ParserError>)
 {
 	let ref_source = source.as_str();
 
-	let tokens = lex(ref_source.into());
+	let (tokens, lexer_errors) = lex(ref_source.into());
 	let mut root = Root::new();
 
 	let mut parser_context = ParserContext
 	{
 		source: ref_source,
-		tokens: tokens,
+		tokens,
 		symbols_table: SymbolsTable::new(),
-		errors: Vec::new()
+		errors: Vec::new(),
+		resolutions: Vec::new(),
+		loop_depth: 0
 	};
 
+	for lexer_error in lexer_errors
+	{
+		record_error(&mut parser_context, &lexer_error.message, &lexer_error.info);
+	}
+
 	parser_context.symbols_table.push_scope();
 
 	while !parser_context.tokens.is_empty()
 	{
-		let result = parse_statement(&mut parser_context, true);
-		
-		if result.is_some()
+		if let Some(statement) = parse_statement(&mut parser_context, true)
+		{
+			root.add(statement);
+		}
+	}
+
+	(root, ResolutionIndex::new(parser_context.resolutions), parser_context.errors)
+}
+
+// The scope of the statements of an interactive session, inside the root one
+const INTERACTIVE_SCOPE: usize = 2;
+
+// Parses one input of an interactive session against a table that outlives it. Functions
+// are parsed at the root as usual, anything else as if it were in the body of a function
+// spanning the whole session, so the variables it declares stay in `symbols_table` for
+// the inputs that follow. The table may be left half updated when there are errors.
+pub fn parse_interactive(source: String, symbols_table: &mut SymbolsTable) -> (Vec<Statement>, Vec<ParserError>)
+{
+	let ref_source = source.as_str();
+
+	let (tokens, lexer_errors) = lex(ref_source.into());
+	let mut statements = Vec::new();
+
+	while symbols_table.scope() < INTERACTIVE_SCOPE
+	{
+		symbols_table.push_scope();
+	}
+
+	let mut parser_context = ParserContext
+	{
+		source: ref_source,
+		tokens,
+		symbols_table: symbols_table.clone(),
+		errors: Vec::new(),
+		resolutions: Vec::new(),
+		loop_depth: 0
+	};
+
+	for lexer_error in lexer_errors
+	{
+		record_error(&mut parser_context, &lexer_error.message, &lexer_error.info);
+	}
+
+	while let Some(token) = parser_context.tokens.front()
+	{
+		let is_function = token
+			.as_token::<IdentifierToken>()
+			.is_some_and(|ident_token| ident_token.name() == "function");
+
+		let result = if is_function
+		{
+			// Function bodies must not see the variables of the session
+			let session_scope = parser_context.symbols_table.pop_scope().unwrap();
+			let result = parse_statement(&mut parser_context, true);
+
+			while parser_context.symbols_table.scope() > INTERACTIVE_SCOPE - 1
+			{
+				parser_context.symbols_table.pop_scope();
+			}
+
+			parser_context.symbols_table.restore_scope(session_scope);
+			result
+		}
+		else
 		{
-			root.add(result.unwrap());
+			parse_statement(&mut parser_context, true)
+		};
+
+		if let Some(statement) = result
+		{
+			statements.push(statement);
 		}
 	}
 
-	(root, parser_context.errors)
+	*symbols_table = parser_context.symbols_table;
+
+	(statements, parser_context.errors)
+}
+
+// Parses a lone expression in the session scope, for inspecting it without running it
+pub fn parse_interactive_expression(source: String, symbols_table: &SymbolsTable) -> (Option<Expression>, Vec<ParserError>)
+{
+	let ref_source = source.as_str();
+
+	let (tokens, lexer_errors) = lex(ref_source.into());
+
+	let mut parser_context = ParserContext
+	{
+		source: ref_source,
+		tokens,
+		symbols_table: symbols_table.clone(),
+		errors: Vec::new(),
+		resolutions: Vec::new(),
+		loop_depth: 0
+	};
+
+	for lexer_error in lexer_errors
+	{
+		record_error(&mut parser_context, &lexer_error.message, &lexer_error.info);
+	}
+
+	while parser_context.symbols_table.scope() < INTERACTIVE_SCOPE
+	{
+		parser_context.symbols_table.push_scope();
+	}
+
+	let expression = if parser_context.tokens.is_empty() { None } else { parse_expression(&mut parser_context) };
+
+	(expression, parser_context.errors)
 }
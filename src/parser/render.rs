@@ -0,0 +1,69 @@
+use super::ParserError;
+
+const TAB_WIDTH: usize = 4;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Renders an error as an annotated snippet:
+//
+// error: message
+//  --> file.faren:3:10
+//   |
+// 3 |     print true + 1;
+//   |                ^
+pub fn render_error(file_name: &str, error: &ParserError, colour: bool) -> String
+{
+	let paint = |style: &'static str| if colour { style } else { "" };
+
+	let red = paint(RED);
+	let blue = paint(BLUE);
+	let bold = paint(BOLD);
+	let reset = paint(RESET);
+
+	let line_number = error.line.to_string();
+	let gutter = " ".repeat(line_number.len());
+
+	// Tabs are expanded so that the carets line up with what a terminal shows
+	let mut source_line = String::new();
+	let mut padding = 0;
+	let mut carets = 0;
+
+	for (index, c) in error.context_line.chars().enumerate()
+	{
+		let column = index + 1;
+		let width = if c == '\t' { TAB_WIDTH } else { 1 };
+
+		if c == '\t'
+		{
+			source_line.push_str(&" ".repeat(TAB_WIDTH));
+		}
+		else
+		{
+			source_line.push(c);
+		}
+
+		if column < error.column_begin
+		{
+			padding += width;
+		}
+		else if column <= error.column_end
+		{
+			carets += width;
+		}
+	}
+
+	let carets = carets.max(1);
+
+	let mut output = String::new();
+
+	output.push_str(&format!("{red}error{reset}{bold}: {}{reset}\n", error.message));
+	output.push_str(&format!("{gutter}{blue}-->{reset} {}:{}:{}\n", file_name, error.line, error.column_begin));
+	output.push_str(&format!("{gutter} {blue}|{reset}\n"));
+	output.push_str(&format!("{blue}{line_number} |{reset} {}\n", source_line));
+	output.push_str(&format!("{gutter} {blue}|{reset} {}{red}{}{reset}\n", " ".repeat(padding), "^".repeat(carets)));
+
+	output
+}
@@ -0,0 +1,85 @@
+use super::token::TokenInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclarationKind
+{
+	Parameter,
+	Local,
+	Function
+}
+
+// Where a name is declared. A function declared more than once is declared where
+// its name first appears, the later declarations and its definition refer to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration
+{
+	pub kind: DeclarationKind,
+	pub name: String,
+	pub info: TokenInfo
+}
+
+// One occurrence of an identifier and the declaration it resolves to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution
+{
+	pub info: TokenInfo,
+	pub declaration: Declaration
+}
+
+impl Resolution
+{
+	pub fn is_declaration(&self) -> bool
+	{
+		self.info == self.declaration.info
+	}
+}
+
+// Every identifier the parser resolved, in source order. Keywords and identifiers
+// that do not resolve, and so are reported as errors, are not in it.
+#[derive(Debug, Clone, Default)]
+pub struct ResolutionIndex
+{
+	resolutions: Vec<Resolution>
+}
+
+impl ResolutionIndex
+{
+	pub fn new(mut resolutions: Vec<Resolution>) -> Self
+	{
+		resolutions.sort_by_key(|resolution| resolution.info.offset_begin);
+		resolutions.dedup();
+
+		Self { resolutions }
+	}
+
+	pub fn resolutions(&self) -> &[Resolution]
+	{
+		&self.resolutions
+	}
+
+	// The occurrence covering a byte offset of the source, its end included
+	pub fn resolve(&self, offset: usize) -> Option<&Resolution>
+	{
+		let index = self.resolutions.partition_point(|resolution| resolution.info.offset_end < offset);
+
+		self.resolutions
+			.get(index)
+			.filter(|resolution| resolution.info.offset_begin <= offset)
+	}
+
+	pub fn declarations(&self) -> impl Iterator<Item = &Declaration>
+	{
+		self.resolutions
+			.iter()
+			.filter(|resolution| resolution.is_declaration())
+			.map(|resolution| &resolution.declaration)
+	}
+
+	// Every occurrence of a declared name, its declaration included
+	pub fn references<'a>(&'a self, declaration: &'a Declaration) -> impl Iterator<Item = &'a Resolution>
+	{
+		self.resolutions
+			.iter()
+			.filter(move |resolution| resolution.declaration == *declaration)
+	}
+}
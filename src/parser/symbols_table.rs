@@ -1,7 +1,10 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::data::vtype::*;
 
+use super::resolution::*;
+use super::token::TokenInfo;
+
 #[derive(Debug, Clone)]
 pub struct Scope
 {
@@ -60,8 +63,15 @@ pub struct SymbolsTable
 {
 	functions: HashMap<String, FunctionSignature>,
 
+	// Functions given a body, which unlike a declaration may only happen once
+	function_bodies: HashSet<String>,
+
 	scopes: VecDeque<Scope>,
-	next_id: u16
+	next_id: u16,
+
+	// Where every id and function was declared, kept across scopes
+	declarations: HashMap<u16, Declaration>,
+	function_declarations: HashMap<String, Declaration>
 }
 
 impl SymbolsTable
@@ -71,21 +81,36 @@ impl SymbolsTable
 		Self
 		{
 			functions: HashMap::new(),
+			function_bodies: HashSet::new(),
 			scopes: VecDeque::new(),
-			next_id: 0
+			next_id: 0,
+			declarations: HashMap::new(),
+			function_declarations: HashMap::new()
 		}
 	}
 
-	pub fn define_function(&mut self, name: &str, return_type: VType, parameters: VecDeque<Parameter>)
+	pub fn define_function(&mut self, name: &str, return_type: VType, parameters: VecDeque<Parameter>, info: TokenInfo)
 	{
-		let info = FunctionSignature::new(name.to_string(), return_type, parameters);
+		let signature = FunctionSignature::new(name.to_string(), return_type, parameters);
 
 		if self.functions.contains_key(name)
 		{
 			panic!("Function {} already defined", name);
 		}
 
-		self.functions.insert(name.to_string(), info);
+		self.functions.insert(name.to_string(), signature);
+		self.function_declarations.insert(name.to_string(), Declaration { kind: DeclarationKind::Function, name: name.to_string(), info });
+	}
+
+	// Records that the function has a body, false if it already had one
+	pub fn define_function_body(&mut self, name: &str) -> bool
+	{
+		self.function_bodies.insert(name.to_string())
+	}
+
+	pub fn function_declaration(&self, name: &str) -> Option<&Declaration>
+	{
+		self.function_declarations.get(name)
 	}
 
 	pub fn get_function(&self, name: &str) -> Option<&FunctionSignature>
@@ -100,16 +125,24 @@ impl SymbolsTable
 		self.scopes.push_front(new_scope);
 	}
 
-	pub fn pop_scope(&mut self)
+	pub fn pop_scope(&mut self) -> Option<Scope>
 	{
-		self.scopes.pop_front();
+		self.scopes.pop_front()
 	}
 
-	pub fn define(&mut self, name: &str, vtype: VType)
+	// Puts back a scope taken off with `pop_scope`
+	pub fn restore_scope(&mut self, scope: Scope)
+	{
+		self.scopes.push_front(scope);
+	}
+
+	pub fn define(&mut self, name: &str, vtype: VType, kind: DeclarationKind, info: TokenInfo)
 	{
 		let id: u16 = self.next_id;
 		self.next_id += 1;
 
+		self.declarations.insert(id, Declaration { kind, name: name.to_string(), info });
+
 		if let Some(scope) = self.scopes.front_mut()
 		{
 			scope.define(name, id, vtype);
@@ -133,4 +166,17 @@ impl SymbolsTable
 	{
 		self.scopes.front()?.lookup_id(name)
 	}
+
+	pub fn declaration(&self, id: u16) -> Option<&Declaration>
+	{
+		self.declarations.get(&id)
+	}
+}
+
+impl Default for SymbolsTable
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
 }
@@ -1,17 +1,24 @@
 use dyn_clone::DynClone;
 use std::any::Any;
+use std::fmt;
+use std::ops::{Deref, Range};
+use std::rc::Rc;
 
 use crate::data;
 use crate::data::syms::Symbol;
 use crate::data::vtype::VType;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TokenInfo
 {
 	// Debug:
 	pub line: usize,
 	pub column_begin: usize,
-	pub column_end: usize
+	pub column_end: usize,
+
+	// Byte offsets into the source, the end is exclusive
+	pub offset_begin: usize,
+	pub offset_end: usize
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -375,3 +382,51 @@ impl Token
 		self.token.as_any().downcast_ref::<T>()
 	}
 }
+
+// The type followed by the value, `Identifier main` or `Symbol LeftParen`
+impl fmt::Display for Token
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+	{
+		let token_type = self.get_type();
+
+		match token_type
+		{
+			TokenType::Identifier => write!(f, "{:?} {}", token_type, self.as_token::<IdentifierToken>().unwrap().name()),
+			TokenType::Type => write!(f, "{:?} {:?}", token_type, self.as_token::<TypeToken>().unwrap().vtype()),
+			TokenType::Symbol => write!(f, "{:?} {:?}", token_type, self.as_token::<SymbolToken>().unwrap().sym()),
+			TokenType::Arithmetic => write!(f, "{:?} {:?}", token_type, self.as_token::<ArithmeticToken>().unwrap().op()),
+			TokenType::Comparison => write!(f, "{:?} {:?}", token_type, self.as_token::<ComparisonToken>().unwrap().op()),
+			TokenType::Boolean => write!(f, "{:?} {:?}", token_type, self.as_token::<BooleanToken>().unwrap().op()),
+			TokenType::BooleanLiteral => write!(f, "{:?} {}", token_type, self.as_token::<BooleanLiteralToken>().unwrap().value()),
+			TokenType::IntegerLiteral => write!(f, "{:?} {}", token_type, self.as_token::<IntegerLiteralToken>().unwrap().value())
+		}
+	}
+}
+
+// A range of tokens shared with every node parsed from the same expression, so that
+// nesting does not copy the tokens of the operands into each node above them
+#[derive(Clone, Default)]
+pub struct TokenSlice
+{
+	tokens: Rc<[Token]>,
+	range: Range<usize>
+}
+
+impl TokenSlice
+{
+	pub fn new(tokens: Rc<[Token]>, range: Range<usize>) -> Self
+	{
+		Self { tokens, range }
+	}
+}
+
+impl Deref for TokenSlice
+{
+	type Target = [Token];
+
+	fn deref(&self) -> &[Token]
+	{
+		&self.tokens[self.range.clone()]
+	}
+}
@@ -0,0 +1,417 @@
+use std::collections::{
+	HashMap,
+	VecDeque
+};
+
+use std::io::{
+	self,
+	Write
+};
+
+use crate::ast::{
+	dump::*,
+	root::*,
+	statement::*
+};
+
+use crate::cst::STATEMENT_KEYWORDS;
+use crate::data::syms::Symbol;
+use crate::interp::*;
+
+use crate::parser::{
+	lexer::lex,
+	parse_interactive,
+	parse_interactive_expression,
+	render::render_error,
+	symbols_table::SymbolsTable,
+	token::*,
+	ParserError
+};
+
+const FILE_NAME: &str = "<input>";
+
+const HELP: &str = "\
+Enter statements and function definitions as in a source file, or an expression to
+evaluate it. A statement continues on the next line until it ends with `;` or `}`,
+an `if` waits for a line starting with `else`. An empty line ends any input.
+
+  :ast [input]    print the tree of the input, or of every function defined so far
+  :tokens input   print the tokens of the input
+  :type expr      print the type of an expression without evaluating it
+  :help           print this message
+  :quit           leave the session";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status
+{
+	Ready,
+
+	// The input so far is not a whole statement, more lines are needed
+	Incomplete,
+
+	Quit
+}
+
+// An interactive session. Every complete input is parsed against the symbols of the
+// ones before it and run straight away, an input that fails to parse or run leaves no
+// declarations behind, though whatever it printed stays printed.
+pub struct Repl
+{
+	symbols_table: SymbolsTable,
+	definitions: Root,
+	bindings: HashMap<u16, Value>,
+	buffer: String,
+
+	// The buffer is complete but ends with an `if` that an `else` on the next line continues
+	awaiting_else: bool
+}
+
+impl Default for Repl
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
+
+fn is_symbol(token: &Token, sym: Symbol) -> bool
+{
+	token.as_token::<SymbolToken>().is_some_and(|sym_token| sym_token.sym() == sym)
+}
+
+fn is_keyword(token: &Token, keyword: &str) -> bool
+{
+	token.as_token::<IdentifierToken>().is_some_and(|ident_token| ident_token.name() == keyword)
+}
+
+#[derive(PartialEq, Eq)]
+enum Completion
+{
+	Complete,
+	Incomplete,
+
+	// Complete, unless the next line begins with `else`
+	EndsWithIf
+}
+
+// Statements are complete once every brace is closed and the last one has ended
+fn completion(tokens: &VecDeque<Token>) -> Completion
+{
+	let mut depth = 0;
+
+	// Where the statement being read and the last one that ended begin
+	let mut begin = 0;
+	let mut last_begin = 0;
+
+	for (index, token) in tokens.iter().enumerate()
+	{
+		let mut ends = false;
+
+		if is_symbol(token, Symbol::LeftBrace)
+		{
+			depth += 1;
+		}
+		else if is_symbol(token, Symbol::RightBrace)
+		{
+			depth -= 1;
+			ends = depth <= 0 && !tokens.get(index + 1).is_some_and(|next| is_keyword(next, "else"));
+		}
+		else if is_symbol(token, Symbol::Semicolon)
+		{
+			ends = depth <= 0;
+		}
+
+		if ends
+		{
+			last_begin = begin;
+			begin = index + 1;
+		}
+	}
+
+	if depth > 0 || begin != tokens.len()
+	{
+		return Completion::Incomplete;
+	}
+
+	if tokens.get(last_begin).is_some_and(|token| is_keyword(token, "if"))
+	{
+		return Completion::EndsWithIf;
+	}
+
+	Completion::Complete
+}
+
+// Anything that does not begin like a statement is taken as an expression to evaluate
+fn is_statement(tokens: &VecDeque<Token>) -> bool
+{
+	let Some(token) = tokens.front() else
+	{
+		return true;
+	};
+
+	if let Some(ident_token) = token.as_token::<IdentifierToken>()
+	{
+		return STATEMENT_KEYWORDS.contains(&ident_token.name().as_str());
+	}
+
+	token.as_token::<SymbolToken>().is_some_and(|sym_token| sym_token.sym() == Symbol::LeftBrace)
+}
+
+// A lone expression may still be ended like a statement
+fn strip_semicolon(input: &str) -> String
+{
+	let input = input.trim_end();
+	input.strip_suffix(';').unwrap_or(input).to_string()
+}
+
+fn report<W: Write>(out: &mut W, errors: &[ParserError]) -> io::Result<()>
+{
+	for error in errors
+	{
+		writeln!(out, "{}", render_error(FILE_NAME, error, false))?;
+	}
+
+	Ok(())
+}
+
+impl Repl
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			symbols_table: SymbolsTable::new(),
+			definitions: Root::new(),
+			bindings: HashMap::new(),
+			buffer: String::new(),
+			awaiting_else: false
+		}
+	}
+
+	pub fn prompt(&self) -> &'static str
+	{
+		if self.buffer.is_empty() { "> " } else { ". " }
+	}
+
+	// Takes one line of input, anything it prints or reports goes to `out`
+	pub fn feed<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<Status>
+	{
+		if self.awaiting_else
+		{
+			self.awaiting_else = false;
+
+			if !line.trim_start().starts_with("else")
+			{
+				self.submit(out)?;
+				return self.feed(line, out);
+			}
+		}
+
+		if self.buffer.is_empty() && line.trim_start().starts_with(':')
+		{
+			return self.command(line.trim(), out);
+		}
+
+		if line.trim().is_empty()
+		{
+			self.submit(out)?;
+			return Ok(Status::Ready);
+		}
+
+		self.buffer.push_str(line);
+		self.buffer.push('\n');
+
+		let (tokens, _) = lex(self.buffer.clone());
+
+		if is_statement(&tokens)
+		{
+			match completion(&tokens)
+			{
+				Completion::Complete => (),
+				Completion::Incomplete => return Ok(Status::Incomplete),
+
+				Completion::EndsWithIf =>
+				{
+					self.awaiting_else = true;
+					return Ok(Status::Incomplete);
+				}
+			}
+		}
+
+		self.submit(out)?;
+
+		Ok(Status::Ready)
+	}
+
+	// Runs whatever input is pending, complete or not
+	pub fn submit<W: Write>(&mut self, out: &mut W) -> io::Result<()>
+	{
+		self.awaiting_else = false;
+
+		let input = std::mem::take(&mut self.buffer);
+		let (tokens, _) = lex(input.clone());
+
+		if tokens.is_empty()
+		{
+			return Ok(());
+		}
+
+		if is_statement(&tokens)
+		{
+			self.execute(input, out)
+		}
+		else
+		{
+			self.evaluate(input, out)
+		}
+	}
+
+	fn execute<W: Write>(&mut self, input: String, out: &mut W) -> io::Result<()>
+	{
+		let mut symbols_table = self.symbols_table.clone();
+		let (statements, errors) = parse_interactive(input, &mut symbols_table);
+
+		if !errors.is_empty()
+		{
+			return report(out, &errors);
+		}
+
+		let mut definitions = self.definitions.clone();
+		let mut body = Vec::new();
+
+		for statement in statements
+		{
+			match statement.stype()
+			{
+				StatementType::FunctionDefine => definitions.add(statement),
+				StatementType::FunctionDeclare => (),
+				_ => body.push(statement)
+			}
+		}
+
+		let result = Interpreter::new(&definitions, &mut *out).execute(&body, &mut self.bindings);
+
+		match result
+		{
+			Ok(value) =>
+			{
+				self.symbols_table = symbols_table;
+				self.definitions = definitions;
+
+				match value
+				{
+					Some(value) if value != Value::Void => writeln!(out, "{}", value),
+					_ => Ok(())
+				}
+			}
+
+			Err(err) => writeln!(out, "error: {}", err)
+		}
+	}
+
+	fn evaluate<W: Write>(&mut self, input: String, out: &mut W) -> io::Result<()>
+	{
+		let (expression, errors) = parse_interactive_expression(strip_semicolon(&input), &self.symbols_table);
+
+		if !errors.is_empty()
+		{
+			return report(out, &errors);
+		}
+
+		let Some(expression) = expression else
+		{
+			return Ok(());
+		};
+
+		let result = Interpreter::new(&self.definitions, &mut *out).evaluate(&expression, &self.bindings);
+
+		match result
+		{
+			Ok(Value::Void) => Ok(()),
+			Ok(value) => writeln!(out, "{}", value),
+			Err(err) => writeln!(out, "error: {}", err)
+		}
+	}
+
+	fn command<W: Write>(&mut self, line: &str, out: &mut W) -> io::Result<Status>
+	{
+		let (command, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+		let argument = argument.trim();
+
+		match command
+		{
+			":quit" | ":q" => return Ok(Status::Quit),
+
+			":help" => writeln!(out, "{}", HELP)?,
+
+			":ast" if argument.is_empty() => write!(out, "{}", dump(&self.definitions))?,
+
+			":ast" =>
+			{
+				let (tokens, _) = lex(argument.to_string());
+
+				if is_statement(&tokens)
+				{
+					// Parsed against a copy, nothing is declared
+					let mut symbols_table = self.symbols_table.clone();
+					let (statements, errors) = parse_interactive(argument.to_string(), &mut symbols_table);
+
+					let mut root = Root::new();
+					statements.into_iter().for_each(|statement| root.add(statement));
+
+					write!(out, "{}", dump(&root))?;
+					report(out, &errors)?;
+				}
+				else
+				{
+					let (expression, errors) = parse_interactive_expression(strip_semicolon(argument), &self.symbols_table);
+
+					if let Some(expression) = expression
+					{
+						write!(out, "{}", dump_expression(&expression))?;
+					}
+
+					report(out, &errors)?;
+				}
+			}
+
+			":tokens" =>
+			{
+				let (tokens, errors) = lex(argument.to_string());
+
+				for token in tokens.iter()
+				{
+					let info = token.info();
+					writeln!(out, "{}-{}\t{}", info.column_begin, info.column_end, token)?;
+				}
+
+				for error in errors.iter()
+				{
+					writeln!(out, "error: {} at column {}", error.message, error.info.column_begin)?;
+				}
+			}
+
+			":type" =>
+			{
+				let (expression, errors) = parse_interactive_expression(strip_semicolon(argument), &self.symbols_table);
+
+				if !errors.is_empty()
+				{
+					report(out, &errors)?;
+				}
+				else if let Some(expression) = expression
+				{
+					writeln!(out, "{:?}", expression.vtype())?;
+				}
+				else
+				{
+					writeln!(out, "error: expected an expression after `:type`")?;
+				}
+			}
+
+			_ => writeln!(out, "error: unknown command `{}`, see `:help`", command)?
+		}
+
+		Ok(Status::Ready)
+	}
+}